@@ -0,0 +1,33 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var input_tex: texture_2d<f32>;
+
+@group(0) @binding(1)
+var input_sampler: sampler;
+
+@group(0) @binding(2)
+var depth_tex: texture_depth_2d;
+
+// Must match the camera's near and far planes
+const NEAR: f32 = 0.1;
+const FAR: f32 = 50.0;
+
+const FOG_START: f32 = 2.0;
+const FOG_END: f32 = 30.0;
+const FOG_COLOR: vec3<f32> = vec3<f32>(0.6, 0.65, 0.7);
+
+fn linearize_depth(depth: f32) -> f32 {
+    return NEAR * FAR / (FAR - depth * (FAR - NEAR));
+}
+
+@fragment
+fn main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_tex, input_sampler, input.tex_coords).xyz;
+    let depth = textureLoad(depth_tex, vec2<i32>(input.position.xy), 0);
+    let fog = clamp((linearize_depth(depth) - FOG_START) / (FOG_END - FOG_START), 0.0, 1.0);
+    return vec4f(mix(color, FOG_COLOR, fog), 1.0);
+}
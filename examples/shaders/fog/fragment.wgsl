@@ -0,0 +1,11 @@
+struct VertexOutput {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) world_normal: vec3<f32>,
+}
+
+@fragment
+fn main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let light_dir = normalize(vec3f(0.4, 1.0, -0.6));
+    let diffuse = max(dot(in.world_normal, light_dir), 0.0);
+    return vec4f(vec3f(0.9, 0.5, 0.2) * (0.2 + 0.8 * diffuse), 1.0);
+}
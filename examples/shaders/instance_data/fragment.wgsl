@@ -0,0 +1,11 @@
+struct VertexOutput {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@fragment
+fn main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let stripes = step(0.5, fract(in.tex_coords.x * 2.0));
+    return vec4f(in.color.rgb * (0.6 + 0.4 * stripes), in.color.a);
+}
@@ -0,0 +1,38 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+struct TransformData {
+    @location(2) mat_0: vec4<f32>,
+    @location(3) mat_1: vec4<f32>,
+    @location(4) mat_2: vec4<f32>,
+    @location(5) mat_3: vec4<f32>,
+}
+
+struct InstanceData {
+    @location(6) color: vec4<f32>,
+    @location(7) uv_offset: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn main(input: VertexInput, transform: TransformData, instance: InstanceData) -> VertexOutput {
+    let mat = mat4x4<f32>(
+        transform.mat_0,
+        transform.mat_1,
+        transform.mat_2,
+        transform.mat_3,
+    );
+
+    var out: VertexOutput;
+    out.pos = mat * vec4f(input.position, 1.0);
+    out.tex_coords = input.tex_coords + instance.uv_offset;
+    out.color = instance.color;
+    return out;
+}
@@ -0,0 +1,10 @@
+struct VertexOutput {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+}
+
+@fragment
+fn main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let light = max(dot(in.normal, normalize(vec3f(0.4, 0.6, -0.7))), 0.0);
+    return vec4f(vec3f(0.9, 0.5, 0.2) * (0.25 + 0.75 * light), 1.0);
+}
@@ -0,0 +1,31 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+}
+
+struct TransformData {
+    @location(2) mat_0: vec4<f32>,
+    @location(3) mat_1: vec4<f32>,
+    @location(4) mat_2: vec4<f32>,
+    @location(5) mat_3: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+}
+
+@vertex
+fn main(input: VertexInput, transform: TransformData) -> VertexOutput {
+    let mat = mat4x4<f32>(
+        transform.mat_0,
+        transform.mat_1,
+        transform.mat_2,
+        transform.mat_3,
+    );
+
+    var out: VertexOutput;
+    out.pos = mat * vec4f(input.position, 1.0);
+    out.normal = normalize((mat * vec4f(input.normal, 0.0)).xyz);
+    return out;
+}
@@ -0,0 +1,28 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct ScreenSpaceUniforms {
+    time: f32,
+    resolution: vec2<f32>,
+    mouse: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var input_tex: texture_2d<f32>;
+
+@group(0) @binding(1)
+var input_sampler: sampler;
+
+@group(0) @binding(3)
+var<uniform> uniforms: ScreenSpaceUniforms;
+
+@fragment
+fn main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let wavelength = 40.0 / uniforms.resolution.y;
+    let amplitude = 6.0 / uniforms.resolution.x;
+    let offset = amplitude * sin(input.tex_coords.y / wavelength + uniforms.time * 3.0);
+    let color = textureSample(input_tex, input_sampler, input.tex_coords + vec2f(offset, 0.0));
+    return vec4f(color.xyz, 1.0);
+}
@@ -0,0 +1,68 @@
+use nalgebra::Vector3;
+use v4::{
+    V4,
+    builtin_components::{
+        animated_sprite_component::AnimatedSpriteComponent, sprite_component::SpriteComponent,
+        transform_component::TransformComponent,
+    },
+    ecs::{
+        material::{ShaderAttachment, ShaderTextureAttachment},
+        scene::Scene,
+    },
+    engine_support::texture_support::{TextureBundle, TextureProperties},
+};
+
+/// Plays the four frames of the sprite strip as a walk cycle
+#[tokio::main]
+pub async fn main() {
+    let mut engine = V4::builder().build().await;
+    let rendering_manager = engine.rendering_manager();
+    let device = rendering_manager.device();
+    let queue = rendering_manager.queue();
+
+    let (_, texture_bundle) = TextureBundle::from_path(
+        "./assets/testing_textures/sprite_strip.png",
+        device,
+        queue,
+        TextureProperties::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut animated_sprite_scene = Scene::default();
+    let material = animated_sprite_scene.create_material(
+        SpriteComponent::pipeline_id(false),
+        vec![ShaderAttachment::Texture(ShaderTextureAttachment {
+            texture_bundle,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+        })],
+        Vec::new(),
+        Vec::new(),
+        true,
+    );
+
+    animated_sprite_scene.create_entity(
+        None,
+        vec![
+            Box::new(
+                TransformComponent::builder()
+                    .position(Vector3::new(0.0, 0.0, 0.0))
+                    .build(),
+            ),
+            Box::new(
+                AnimatedSpriteComponent::builder()
+                    .frames(AnimatedSpriteComponent::strip_frames(4))
+                    .frames_per_second(6.0)
+                    .build(),
+            ),
+            Box::new(SpriteComponent::builder().size([1.0, 1.0]).build()),
+        ],
+        Vec::new(),
+        Some(material),
+        true,
+    );
+
+    engine.attach_scene(animated_sprite_scene);
+
+    engine.main_loop().await;
+}
@@ -1,9 +1,12 @@
+use std::time::Duration;
+
 use v4::{
     V4,
     ecs::{
         compute::Compute,
         material::{ShaderAttachment, ShaderBufferAttachment},
     },
+    engine_support::input_state::MockInput,
     scene,
 };
 
@@ -32,7 +35,7 @@ pub async fn main() {
                             bytemuck::cast_slice(&[0.0_f32,0.0,0.0,0.0, 0.0, 0.0, 0.0, 0.0]),
                             wgpu::BufferBindingType::Storage { read_only: false },
                             wgpu::ShaderStages::COMPUTE,
-                            wgpu::BufferUsages::empty(),
+                            wgpu::BufferUsages::COPY_SRC,
                         )),
                     ],
                     shader_path: "shaders/compute/compute.wgsl",
@@ -45,5 +48,17 @@ pub async fn main() {
 
     engine.attach_scene(thing);
 
+    // Run the compute once so its output can be read back
+    engine.step_frame(Duration::from_secs_f32(1.0 / 60.0), &MockInput::default());
+    let output = engine.active_scene().unwrap().computes()[0]
+        .read_output(
+            engine.rendering_manager().device(),
+            engine.rendering_manager().queue(),
+        )
+        .await
+        .unwrap();
+    let output: &[f32] = bytemuck::cast_slice(&output);
+    println!("Compute output: {output:?}");
+
     engine.main_loop().await;
 }
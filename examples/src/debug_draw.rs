@@ -0,0 +1,78 @@
+use algoe::bivector::Bivector;
+use nalgebra::Vector3;
+use v4::{
+    V4,
+    builtin_components::{
+        camera_component::CameraComponent,
+        debug_draw_component::DebugDrawComponent,
+        mesh_component::{MeshComponent, VertexDescriptor},
+        transform_component::TransformComponent,
+    },
+    scene,
+};
+use winit::keyboard::KeyCode;
+
+use crate::fog::Vertex;
+
+/// Press B to show the bounding boxes and normals of the cubes
+#[tokio::main]
+pub async fn main() {
+    let mut engine = V4::builder()
+        .clear_color(wgpu::Color {
+            r: 0.6,
+            g: 0.65,
+            b: 0.7,
+            a: 1.0,
+        })
+        .build()
+        .await;
+
+    scene! {
+        scene: debug_draw_scene,
+        active_camera: "cam",
+        "cam_ent" = {
+            components: [
+                CameraComponent(field_of_view: 80.0, aspect_ratio: 1.0, near_plane: 0.1, far_plane: 50.0, sensitivity: 0.002, movement_speed: 0.01, ident: "cam"),
+                TransformComponent(position: Vector3::new(0.0, 1.0, -5.0), rotation: Bivector::new(0.0, 0.0, 0.0).exponentiate(), uses_buffer: false),
+            ]
+        },
+        _ = {
+            material: {
+                pipeline: {
+                    vertex_shader_path: "shaders/debug_draw/shaded.wgsl",
+                    fragment_shader_path: "shaders/debug_draw/shaded.wgsl",
+                    vertex_entry: "vs_main",
+                    fragment_entry: "fs_main",
+                    vertex_layouts: [Vertex::vertex_layout(), TransformComponent::vertex_layout::<3>()],
+                    uses_camera: true,
+                },
+            },
+            components: [
+                TransformComponent(position: Vector3::new(0.0, 0.0, 0.0)),
+                MeshComponent<Vertex>::from_obj("assets/models/basic_cube.obj", true).await.unwrap(),
+                DebugDrawComponent<Vertex>(toggle_key: Some(KeyCode::KeyB)),
+            ],
+        },
+        _ = {
+            material: {
+                pipeline: {
+                    vertex_shader_path: "shaders/debug_draw/shaded.wgsl",
+                    fragment_shader_path: "shaders/debug_draw/shaded.wgsl",
+                    vertex_entry: "vs_main",
+                    fragment_entry: "fs_main",
+                    vertex_layouts: [Vertex::vertex_layout(), TransformComponent::vertex_layout::<3>()],
+                    uses_camera: true,
+                },
+            },
+            components: [
+                TransformComponent(position: Vector3::new(3.0, 0.5, 4.0), rotation: Bivector::new(0.3, 0.2, 0.0).exponentiate(), scale: Vector3::new(1.0, 2.0, 1.0)),
+                MeshComponent<Vertex>::from_obj("assets/models/basic_cube.obj", true).await.unwrap(),
+                DebugDrawComponent<Vertex>(draw_normals: false),
+            ],
+        },
+    }
+
+    engine.attach_scene(debug_draw_scene);
+
+    engine.main_loop().await;
+}
@@ -0,0 +1,124 @@
+use algoe::bivector::Bivector;
+use nalgebra::Vector3;
+use v4::{
+    V4,
+    builtin_components::{
+        camera_component::CameraComponent,
+        mesh_component::{MeshComponent, VertexData, VertexDescriptor},
+        transform_component::TransformComponent,
+    },
+    scene,
+};
+use wgpu::vertex_attr_array;
+
+#[tokio::main]
+pub async fn main() {
+    let mut engine = V4::builder()
+        .clear_color(wgpu::Color {
+            r: 0.6,
+            g: 0.65,
+            b: 0.7,
+            a: 1.0,
+        })
+        .build()
+        .await;
+
+    scene! {
+        scene: fog_scene,
+        active_camera: "cam",
+        screen_space_materials: [
+            {
+                pipeline: {
+                    fragment_shader_path: "shaders/fog/fog.wgsl",
+                }
+            }
+        ],
+        "cam_ent" = {
+            components: [
+                CameraComponent(field_of_view: 80.0, aspect_ratio: 1.0, near_plane: 0.1, far_plane: 50.0, sensitivity: 0.002, movement_speed: 0.01, ident: "cam"),
+                TransformComponent(position: Vector3::new(0.0, 1.0, -5.0), rotation: Bivector::new(0.0, 0.0, 0.0).exponentiate(), uses_buffer: false),
+            ]
+        },
+        _ = {
+            material: {
+                pipeline: {
+                    vertex_shader_path: "shaders/fog/vertex.wgsl",
+                    fragment_shader_path: "shaders/fog/fragment.wgsl",
+                    vertex_layouts: [Vertex::vertex_layout(), TransformComponent::vertex_layout::<3>()],
+                    uses_camera: true,
+                },
+            },
+            components: [
+                TransformComponent(position: Vector3::new(0.0, 0.0, 0.0)),
+                MeshComponent<Vertex>::from_obj("assets/models/basic_cube.obj", true).ident("near_cube").await.unwrap(),
+            ],
+        },
+        _ = {
+            material: {
+                pipeline: {
+                    vertex_shader_path: "shaders/fog/vertex.wgsl",
+                    fragment_shader_path: "shaders/fog/fragment.wgsl",
+                    vertex_layouts: [Vertex::vertex_layout(), TransformComponent::vertex_layout::<3>()],
+                    uses_camera: true,
+                },
+            },
+            components: [
+                TransformComponent(position: Vector3::new(3.0, 0.0, 10.0)),
+                MeshComponent<Vertex>::from_obj("assets/models/basic_cube.obj", true).ident("middle_cube").await.unwrap(),
+            ],
+        },
+        _ = {
+            material: {
+                pipeline: {
+                    vertex_shader_path: "shaders/fog/vertex.wgsl",
+                    fragment_shader_path: "shaders/fog/fragment.wgsl",
+                    vertex_layouts: [Vertex::vertex_layout(), TransformComponent::vertex_layout::<3>()],
+                    uses_camera: true,
+                },
+            },
+            components: [
+                TransformComponent(position: Vector3::new(-4.0, 0.0, 25.0)),
+                MeshComponent<Vertex>::from_obj("assets/models/basic_cube.obj", true).ident("far_cube").await.unwrap(),
+            ],
+        },
+    }
+
+    engine.attach_scene(fog_scene);
+
+    engine.main_loop().await;
+}
+
+#[repr(C)]
+#[derive(Debug, bytemuck::Pod, bytemuck::Zeroable, Clone, Copy, Default)]
+pub struct Vertex {
+    pos: [f32; 3],
+    normal: [f32; 3],
+    tex_coords: [f32; 2],
+}
+
+impl VertexDescriptor for Vertex {
+    const ATTRIBUTES: &[wgpu::VertexAttribute] =
+        &vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x2];
+
+    fn from_data(
+        VertexData {
+            pos,
+            normal,
+            tex_coords,
+            ..
+        }: VertexData,
+    ) -> Self {
+        Self {
+            pos,
+            normal,
+            tex_coords,
+        }
+    }
+    fn position(&self) -> Option<[f32; 3]> {
+        Some(self.pos)
+    }
+
+    fn normal(&self) -> Option<[f32; 3]> {
+        Some(self.normal)
+    }
+}
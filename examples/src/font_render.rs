@@ -22,6 +22,7 @@ struct ToggleComponent {
 pub async fn main() {
     let mut engine = V4::builder()
         .clear_color(wgpu::Color::BLACK)
+        .font_paths(vec!["./assets/fonts/inter.ttf".to_string()])
         .window_attributes(
             WindowAttributes::default()
                 .with_surface_size(winit::dpi::PhysicalSize::new(500, 500))
@@ -54,7 +55,7 @@ impl ComponentSystem for TextComponent {
                 text: self.text.clone(),
                 text_attributes: glyphon::Attrs::new()
                     .color(glyphon::Color::rgb(255, 0, 0))
-                    .family(glyphon::Family::Name("AntiquarianScribeW01-Reg"))
+                    .family(glyphon::Family::Name("Inter"))
                     .into(),
                 text_metrics: glyphon::Metrics {
                     font_size: 20.0,
@@ -65,6 +66,8 @@ impl ComponentSystem for TextComponent {
                     on_screen_height: 1000.0,
                     top_left_pos: [20.0; 2],
                     scale: 1.0,
+                    alignment: None,
+                    wrap: glyphon::Wrap::WordOrGlyph,
                 },
             }),
         })]
@@ -72,9 +75,13 @@ impl ComponentSystem for TextComponent {
 
     fn update(
         &mut self,
-        UpdateParams { input_manager, .. }: UpdateParams<'_, '_>,
+        UpdateParams {
+            input_manager,
+            engine_details,
+            ..
+        }: UpdateParams<'_, '_>,
     ) -> v4::ecs::actions::ActionQueue {
-        let text = input_manager.text();
+        let text = engine_details.typed_this_frame();
 
         if input_manager.key_held(winit::keyboard::KeyCode::Backspace) {
             self.text.pop();
@@ -85,21 +92,20 @@ impl ComponentSystem for TextComponent {
                 text_attributes: None,
                 text_metrics: None,
                 text_display_info: None,
+                alignment: None,
             })];
         }
 
         if !text.is_empty() {
-            for key in text {
-                if let winit::keyboard::Key::Character(char) = key {
-                    self.text.push_str(char);
-                }
-            }
+            self.text
+                .extend(text.chars().filter(|character| !character.is_control()));
             return vec![Box::new(UpdateTextComponentAction {
                 component_id: self.id(),
                 text: Some(self.text.clone()),
                 text_attributes: None,
                 text_metrics: None,
                 text_display_info: None,
+                alignment: None,
             })];
         }
         Vec::new()
@@ -1,8 +1,12 @@
 use algoe::bivector::Bivector;
 use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
 use v4::builtin_components::mesh_component::VertexData;
+use v4::builtin_components::register_builtin_components;
 use v4::ecs::compute::Compute;
 use v4::ecs::material::{ShaderAttachment, ShaderTextureAttachment};
+use v4::ecs::scene::Scene;
+use v4::ecs::scene_descriptor::{ComponentRegistry, SceneDescriptor, SerializableComponent};
 use v4::engine_support::texture_support::TextureProperties;
 use v4::{
     V4,
@@ -252,13 +256,35 @@ pub async fn main() {
         },
     }
 
-    engine.attach_scene(hello_scene);
+    // Saves the scene to RON and loads it back. The pipelines and textures of the materials are
+    // not saved, so they are taken from the original scene.
+    let mut registry = ComponentRegistry::new();
+    register_builtin_components::<Vertex>(&mut registry);
+    registry.register::<HideComponent>("HideComponent");
+
+    let scene_ron = hello_scene
+        .to_descriptor(&registry)
+        .to_ron()
+        .expect("Failed to save the scene");
+    let descriptor = SceneDescriptor::from_ron(&scene_ron).expect("Failed to parse the scene");
+    let loaded_scene = Scene::from_descriptor(&descriptor, &registry, |material| {
+        let original = hello_scene.get_material(material.id).unwrap();
+        (
+            original.pipeline_id().clone(),
+            original.attachments().to_vec(),
+        )
+    })
+    .expect("Failed to load the scene");
+
+    engine.attach_scene(loaded_scene);
 
     engine.main_loop().await;
 }
 
 #[repr(C)]
-#[derive(Debug, bytemuck::Pod, bytemuck::Zeroable, Clone, Copy, Default)]
+#[derive(
+    Debug, bytemuck::Pod, bytemuck::Zeroable, Clone, Copy, Default, Serialize, Deserialize,
+)]
 struct Vertex {
     pos: [f32; 3],
     tex_coords: [f32; 2],
@@ -342,3 +368,31 @@ impl ComponentSystem for HideComponent {
         Vec::new()
     }
 }
+
+#[derive(Serialize, Deserialize)]
+struct HideParameters {
+    showing: bool,
+    entity: EntityId,
+    immediate_mat: ComponentId,
+}
+
+impl SerializableComponent for HideComponent {
+    type Parameters = HideParameters;
+
+    fn to_parameters(&self) -> HideParameters {
+        HideParameters {
+            showing: self.showing,
+            entity: self.entity,
+            immediate_mat: self.immediate_mat,
+        }
+    }
+
+    fn from_parameters(parameters: HideParameters, id: ComponentId) -> Self {
+        HideComponent::builder()
+            .showing(parameters.showing)
+            .entity(parameters.entity)
+            .immediate_mat(parameters.immediate_mat)
+            .id(id)
+            .build()
+    }
+}
@@ -0,0 +1,132 @@
+use nalgebra::Vector3;
+use v4::{
+    V4,
+    builtin_components::{
+        instance_data_component::InstanceDataComponent,
+        mesh_component::{MeshComponent, VertexData, VertexDescriptor},
+        transform_component::TransformComponent,
+    },
+    scene,
+};
+
+const GRID_SIZE: usize = 10;
+
+/// 100 quads drawn with one material, each tinted by its own `InstanceDataComponent`
+#[tokio::main]
+pub async fn main() {
+    let mut engine = V4::builder().build().await;
+
+    scene! {
+        scene: instance_data_scene,
+        "first_quad" = {
+            material: {
+                pipeline: {
+                    vertex_shader_path: "shaders/instance_data/vertex.wgsl",
+                    fragment_shader_path: "shaders/instance_data/fragment.wgsl",
+                    vertex_layouts: [QuadVertex::vertex_layout(), TransformComponent::vertex_layout::<2>(), InstanceDataComponent::vertex_layout::<6>()],
+                    uses_camera: false,
+                },
+            },
+            components: [
+                TransformComponent(position: quad_position(0), scale: Vector3::new(0.08, 0.08, 1.0)),
+                InstanceDataComponent(color: quad_color(0)),
+                MeshComponent(vertices: vec![quad_vertices()], indices: vec![vec![0, 1, 2, 0, 2, 3]], enabled_models: vec![(0, None)]),
+            ]
+        }
+    }
+
+    let material = instance_data_scene
+        .get_entity(entity_first_quad)
+        .unwrap()
+        .active_material();
+
+    for index in 1..GRID_SIZE * GRID_SIZE {
+        instance_data_scene.create_entity(
+            None,
+            vec![
+                Box::new(
+                    TransformComponent::builder()
+                        .position(quad_position(index))
+                        .scale(Vector3::new(0.08, 0.08, 1.0))
+                        .build(),
+                ),
+                Box::new(
+                    InstanceDataComponent::builder()
+                        .color(quad_color(index))
+                        .uv_offset([index as f32 * 0.05, 0.0])
+                        .build(),
+                ),
+                Box::new(
+                    MeshComponent::builder()
+                        .vertices(vec![quad_vertices()])
+                        .indices(vec![vec![0, 1, 2, 0, 2, 3]])
+                        .enabled_models(vec![(0, None)])
+                        .build(),
+                ),
+            ],
+            Vec::new(),
+            material,
+            true,
+        );
+    }
+
+    engine.attach_scene(instance_data_scene);
+
+    engine.main_loop().await;
+}
+
+fn quad_position(index: usize) -> Vector3<f32> {
+    let (x, y) = (index % GRID_SIZE, index / GRID_SIZE);
+    Vector3::new(-0.9 + x as f32 * 0.2, -0.9 + y as f32 * 0.2, 0.0)
+}
+
+fn quad_color(index: usize) -> [f32; 4] {
+    let (x, y) = (index % GRID_SIZE, index / GRID_SIZE);
+    [
+        x as f32 / (GRID_SIZE - 1) as f32,
+        y as f32 / (GRID_SIZE - 1) as f32,
+        1.0 - x as f32 / (GRID_SIZE - 1) as f32,
+        1.0,
+    ]
+}
+
+fn quad_vertices() -> Vec<QuadVertex> {
+    vec![
+        QuadVertex {
+            pos: [-1.0, 1.0, 0.0],
+            tex_coords: [0.0, 0.0],
+        },
+        QuadVertex {
+            pos: [-1.0, -1.0, 0.0],
+            tex_coords: [0.0, 1.0],
+        },
+        QuadVertex {
+            pos: [1.0, -1.0, 0.0],
+            tex_coords: [1.0, 1.0],
+        },
+        QuadVertex {
+            pos: [1.0, 1.0, 0.0],
+            tex_coords: [1.0, 0.0],
+        },
+    ]
+}
+
+#[repr(C)]
+#[derive(Debug, bytemuck::Pod, bytemuck::Zeroable, Clone, Copy)]
+struct QuadVertex {
+    pos: [f32; 3],
+    tex_coords: [f32; 2],
+}
+
+impl VertexDescriptor for QuadVertex {
+    const ATTRIBUTES: &[wgpu::VertexAttribute] =
+        &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2];
+
+    fn from_data(
+        VertexData {
+            pos, tex_coords, ..
+        }: VertexData,
+    ) -> Self {
+        Self { pos, tex_coords }
+    }
+}
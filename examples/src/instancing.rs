@@ -0,0 +1,102 @@
+use nalgebra::{Matrix4, Rotation3, Vector3};
+use v4::{
+    V4,
+    builtin_components::{
+        mesh_component::{MeshComponent, VertexData, VertexDescriptor},
+        transform_component::{RawTransformData, TransformComponent},
+    },
+    scene,
+};
+
+const GRID_SIZE: usize = 10;
+
+/// 100 cubes drawn by a single mesh in one instanced draw call
+#[tokio::main]
+pub async fn main() {
+    let mut engine = V4::builder().build().await;
+
+    scene! {
+        scene: instancing_scene,
+        "cubes" = {
+            material: {
+                pipeline: {
+                    vertex_shader_path: "shaders/instancing/vertex.wgsl",
+                    fragment_shader_path: "shaders/instancing/fragment.wgsl",
+                    vertex_layouts: [CubeVertex::vertex_layout(), TransformComponent::vertex_layout::<2>()],
+                    uses_camera: false,
+                },
+            },
+            components: [
+                MeshComponent(vertices: vec![cube_vertices()], indices: vec![cube_indices()], enabled_models: vec![(0, None)], instances: cube_instances()),
+            ]
+        }
+    }
+
+    engine.attach_scene(instancing_scene);
+
+    engine.main_loop().await;
+}
+
+fn cube_instances() -> Vec<RawTransformData> {
+    (0..GRID_SIZE * GRID_SIZE)
+        .map(|index| {
+            let (x, y) = (index % GRID_SIZE, index / GRID_SIZE);
+            let translation = Vector3::new(-0.9 + x as f32 * 0.2, -0.9 + y as f32 * 0.2, 0.5);
+            let rotation = Rotation3::from_euler_angles(x as f32 * 0.3, y as f32 * 0.3, 0.4);
+            RawTransformData::new(
+                Matrix4::new_translation(&translation)
+                    * rotation.to_homogeneous()
+                    * Matrix4::new_scaling(0.05),
+            )
+        })
+        .collect()
+}
+
+/// Four vertices per face, so every face has its own normal
+fn cube_vertices() -> Vec<CubeVertex> {
+    let faces = [
+        Vector3::x(),
+        -Vector3::x(),
+        Vector3::y(),
+        -Vector3::y(),
+        Vector3::z(),
+        -Vector3::z(),
+    ];
+    faces
+        .into_iter()
+        .flat_map(|normal: Vector3<f32>| {
+            let tangent = if normal.x.abs() > 0.5 {
+                Vector3::y()
+            } else {
+                Vector3::x()
+            };
+            let bitangent = normal.cross(&tangent);
+            [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(u, v)| CubeVertex {
+                pos: (normal + tangent * u + bitangent * v).into(),
+                normal: normal.into(),
+            })
+        })
+        .collect()
+}
+
+fn cube_indices() -> Vec<u32> {
+    (0..6)
+        .flat_map(|face| [0, 1, 2, 0, 2, 3].map(|corner| face * 4 + corner))
+        .collect()
+}
+
+#[repr(C)]
+#[derive(Debug, bytemuck::Pod, bytemuck::Zeroable, Clone, Copy)]
+struct CubeVertex {
+    pos: [f32; 3],
+    normal: [f32; 3],
+}
+
+impl VertexDescriptor for CubeVertex {
+    const ATTRIBUTES: &[wgpu::VertexAttribute] =
+        &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3];
+
+    fn from_data(VertexData { pos, normal, .. }: VertexData) -> Self {
+        Self { pos, normal }
+    }
+}
@@ -1,9 +1,17 @@
+mod animated_sprite;
 mod compute;
+mod debug_draw;
 mod egui;
+mod fog;
 mod font_render;
 mod hello_world;
+mod instance_data;
+mod instancing;
+mod render_target;
+mod sprite;
 mod textures;
 mod workload_test;
+mod wobble;
 
 fn main() {
     match std::env::args().nth(1) {
@@ -26,6 +34,30 @@ fn main() {
             "egui" => {
                 egui::main();
             }
+            "fog" => {
+                fog::main();
+            }
+            "wobble" => {
+                wobble::main();
+            }
+            "render_target" => {
+                render_target::main();
+            }
+            "debug_draw" => {
+                debug_draw::main();
+            }
+            "instance_data" => {
+                instance_data::main();
+            }
+            "instancing" => {
+                instancing::main();
+            }
+            "sprite" => {
+                sprite::main();
+            }
+            "animated_sprite" => {
+                animated_sprite::main();
+            }
             _ => {
                 println!("Please select a valid example")
             }
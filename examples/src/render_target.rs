@@ -0,0 +1,133 @@
+use algoe::bivector::Bivector;
+use nalgebra::Vector3;
+use v4::{
+    V4,
+    builtin_components::{
+        camera_component::CameraComponent,
+        mesh_component::{MeshComponent, VertexData, VertexDescriptor},
+        transform_component::TransformComponent,
+    },
+    ecs::render_target::RenderTarget,
+    scene,
+};
+
+use crate::fog::Vertex;
+
+#[tokio::main]
+pub async fn main() {
+    let mut engine = V4::builder()
+        .clear_color(wgpu::Color {
+            r: 0.6,
+            g: 0.65,
+            b: 0.7,
+            a: 1.0,
+        })
+        .build()
+        .await;
+    let rendering_manager = engine.rendering_manager();
+    let device = rendering_manager.device();
+
+    let target = RenderTarget::new(device, 512, 512, rendering_manager.depth_format())
+        .with_clear_color(wgpu::Color {
+            r: 0.1,
+            g: 0.1,
+            b: 0.2,
+            a: 1.0,
+        });
+    let target_id = target.id();
+
+    scene! {
+        scene: render_target_scene,
+        active_camera: "cam",
+        "cam_ent" = {
+            components: [
+                CameraComponent(field_of_view: 80.0, aspect_ratio: 1.0, near_plane: 0.1, far_plane: 50.0, sensitivity: 0.002, movement_speed: 0.01, ident: "cam"),
+                TransformComponent(position: Vector3::new(0.0, 1.0, -5.0), rotation: Bivector::new(0.0, 0.0, 0.0).exponentiate(), uses_buffer: false),
+            ]
+        },
+        "target_cam_ent" = {
+            components: [
+                CameraComponent(field_of_view: 60.0, aspect_ratio: 1.0, near_plane: 0.1, far_plane: 50.0, render_target: Some(target_id)),
+                TransformComponent(position: Vector3::new(0.0, 4.0, -4.0), rotation: Bivector::new(0.0, 0.5, 0.0).exponentiate(), uses_buffer: false),
+            ]
+        },
+        _ = {
+            material: {
+                pipeline: {
+                    vertex_shader_path: "shaders/fog/vertex.wgsl",
+                    fragment_shader_path: "shaders/fog/fragment.wgsl",
+                    vertex_layouts: [Vertex::vertex_layout(), TransformComponent::vertex_layout::<3>()],
+                    uses_camera: true,
+                },
+            },
+            components: [
+                TransformComponent(position: Vector3::new(0.0, 0.0, 0.0)),
+                MeshComponent<Vertex>::from_obj("assets/models/basic_cube.obj", true).await.unwrap(),
+            ],
+        },
+        _ = {
+            material: {
+                pipeline: {
+                    vertex_shader_path: "shaders/textures/vertex.wgsl",
+                    fragment_shader_path: "shaders/textures/fragment.wgsl",
+                    vertex_layouts: [QuadVertex::vertex_layout()],
+                    uses_camera: false,
+                },
+                attachments: [Texture (
+                    texture_bundle: target.texture_bundle().clone(),
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                )],
+            },
+            components: [
+                MeshComponent(
+                    vertices: vec![vec![
+                        QuadVertex {
+                            pos: [0.4, 0.9, 0.0],
+                            tex_coords: [0.0, 0.0],
+                        },
+                        QuadVertex {
+                            pos: [0.4, 0.4, 0.0],
+                            tex_coords: [0.0, 1.0],
+                        },
+                        QuadVertex {
+                            pos: [0.9, 0.4, 0.0],
+                            tex_coords: [1.0, 1.0],
+                        },
+                        QuadVertex {
+                            pos: [0.9, 0.9, 0.0],
+                            tex_coords: [1.0, 0.0],
+                        },
+                    ]],
+                    indices: vec![vec![0, 1, 2, 0, 2, 3]],
+                    enabled_models: vec![(0, None)],
+                ),
+            ]
+        }
+    }
+
+    render_target_scene.add_render_target(target);
+
+    engine.attach_scene(render_target_scene);
+
+    engine.main_loop().await;
+}
+
+#[repr(C)]
+#[derive(Debug, bytemuck::Pod, bytemuck::Zeroable, Clone, Copy)]
+struct QuadVertex {
+    pos: [f32; 3],
+    tex_coords: [f32; 2],
+}
+
+impl VertexDescriptor for QuadVertex {
+    const ATTRIBUTES: &[wgpu::VertexAttribute] =
+        &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2];
+
+    fn from_data(
+        VertexData {
+            pos, tex_coords, ..
+        }: VertexData,
+    ) -> Self {
+        Self { pos, tex_coords }
+    }
+}
@@ -0,0 +1,81 @@
+use nalgebra::Vector3;
+use v4::{
+    V4,
+    builtin_components::{
+        sprite_component::SpriteComponent, transform_component::TransformComponent,
+    },
+    ecs::{
+        material::{ShaderAttachment, ShaderTextureAttachment},
+        scene::Scene,
+    },
+    engine_support::texture_support::{TextureBundle, TextureProperties},
+};
+
+/// The whole sprite strip on top and its third cell, picked out as an atlas sub-rect, below
+#[tokio::main]
+pub async fn main() {
+    let mut engine = V4::builder().build().await;
+    let rendering_manager = engine.rendering_manager();
+    let device = rendering_manager.device();
+    let queue = rendering_manager.queue();
+
+    let (_, texture_bundle) = TextureBundle::from_path(
+        "./assets/testing_textures/sprite_strip.png",
+        device,
+        queue,
+        TextureProperties::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut sprite_scene = Scene::default();
+    let material = sprite_scene.create_material(
+        SpriteComponent::pipeline_id(false),
+        vec![ShaderAttachment::Texture(ShaderTextureAttachment {
+            texture_bundle,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+        })],
+        Vec::new(),
+        Vec::new(),
+        true,
+    );
+
+    sprite_scene.create_entity(
+        None,
+        vec![
+            Box::new(
+                TransformComponent::builder()
+                    .position(Vector3::new(0.0, 0.5, 0.0))
+                    .build(),
+            ),
+            Box::new(SpriteComponent::builder().size([1.6, 0.4]).build()),
+        ],
+        Vec::new(),
+        Some(material),
+        true,
+    );
+
+    sprite_scene.create_entity(
+        None,
+        vec![
+            Box::new(
+                TransformComponent::builder()
+                    .position(Vector3::new(0.0, -0.4, 0.0))
+                    .build(),
+            ),
+            Box::new(
+                SpriteComponent::builder()
+                    .size([0.8, 0.8])
+                    .uv_rect(SpriteComponent::atlas_cell(4, 1, 2))
+                    .build(),
+            ),
+        ],
+        Vec::new(),
+        Some(material),
+        true,
+    );
+
+    engine.attach_scene(sprite_scene);
+
+    engine.main_loop().await;
+}
@@ -0,0 +1,54 @@
+use algoe::bivector::Bivector;
+use nalgebra::Vector3;
+use v4::{
+    V4,
+    builtin_components::{
+        camera_component::CameraComponent,
+        mesh_component::{MeshComponent, VertexDescriptor},
+        transform_component::TransformComponent,
+    },
+    scene,
+};
+
+use crate::fog::Vertex;
+
+#[tokio::main]
+pub async fn main() {
+    let mut engine = V4::builder().build().await;
+
+    scene! {
+        scene: wobble_scene,
+        active_camera: "cam",
+        screen_space_materials: [
+            {
+                pipeline: {
+                    fragment_shader_path: "shaders/wobble/wobble.wgsl",
+                }
+            }
+        ],
+        "cam_ent" = {
+            components: [
+                CameraComponent(field_of_view: 80.0, aspect_ratio: 1.0, near_plane: 0.1, far_plane: 50.0, sensitivity: 0.002, movement_speed: 0.01, ident: "cam"),
+                TransformComponent(position: Vector3::new(0.0, 1.0, -5.0), rotation: Bivector::new(0.0, 0.0, 0.0).exponentiate(), uses_buffer: false),
+            ]
+        },
+        _ = {
+            material: {
+                pipeline: {
+                    vertex_shader_path: "shaders/fog/vertex.wgsl",
+                    fragment_shader_path: "shaders/fog/fragment.wgsl",
+                    vertex_layouts: [Vertex::vertex_layout(), TransformComponent::vertex_layout::<3>()],
+                    uses_camera: true,
+                },
+            },
+            components: [
+                TransformComponent(position: Vector3::new(0.0, 0.0, 0.0)),
+                MeshComponent<Vertex>::from_obj("assets/models/basic_cube.obj", true).ident("cube").await.unwrap(),
+            ],
+        },
+    }
+
+    engine.attach_scene(wobble_scene);
+
+    engine.main_loop().await;
+}
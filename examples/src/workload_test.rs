@@ -1,5 +1,7 @@
 use v4::{
-    builtin_actions::{CreateEntityAction, WorkloadAction, WorkloadOutputFreeAction},
+    builtin_actions::{
+        CreateEntityAction, IntervalWorkloadAction, IntervalWorkloadOutputFreeAction,
+    },
     component,
     ecs::{
         component::{ComponentDetails, ComponentSystem, UpdateParams},
@@ -31,6 +33,8 @@ pub async fn main() {
 struct WorkloadTesterComponent {
     initialized_time: std::time::Instant,
     duration: u64,
+    #[default]
+    awaited_launch: u64,
 }
 
 impl WorkloadTesterComponent {
@@ -46,30 +50,38 @@ impl ComponentSystem for WorkloadTesterComponent {
         self.initialized_time = std::time::Instant::now();
         self.set_initialized();
         println!("Initialized!");
-        Vec::new()
+        let duration = self.duration;
+        vec![Box::new(IntervalWorkloadAction {
+            component_id: self.id(),
+            interval: std::time::Duration::from_secs(1),
+            factory: Box::new(move || Box::pin(Self::create_workload(duration))),
+        })]
     }
 
     fn update(
         &mut self,
-        UpdateParams { workload_outputs, .. }: UpdateParams<'_, '_>,
+        UpdateParams {
+            interval_workload_outputs,
+            ..
+        }: UpdateParams<'_, '_>,
     ) -> v4::ecs::actions::ActionQueue {
-        if self.initialized_time.elapsed().as_secs_f32() % 1.0 <= 0.01 {
-            return vec![Box::new(WorkloadAction(
+        if let Some(output) = interval_workload_outputs
+            .get(&self.id())
+            .and_then(|outputs| outputs.get(&self.awaited_launch))
+        {
+            let start_time: std::time::Instant = *output.downcast_ref().unwrap();
+            println!(
+                "Finished workload {} after {} seconds",
+                self.awaited_launch,
+                start_time.elapsed().as_secs_f32()
+            );
+            let launch = self.awaited_launch;
+            self.awaited_launch += 1;
+            return vec![Box::new(IntervalWorkloadOutputFreeAction(
                 self.id(),
-                Box::pin(Self::create_workload(self.duration)),
+                launch,
             ))];
         }
-        if let Some(outputs) = workload_outputs.get(&self.id()) {
-            let last = outputs.first();
-            if let Some(last) = last {
-                let start_time: std::time::Instant = *last.downcast_ref().unwrap();
-                println!(
-                    "Finished a workload after {} seconds",
-                    start_time.elapsed().as_secs_f32()
-                );
-                return vec![Box::new(WorkloadOutputFreeAction(self.id(), 0))];
-            }
-        }
 
         if self.initialized_time.elapsed().as_secs_f32() >= 1.0 {
             return vec![Box::new(CreateEntityAction {
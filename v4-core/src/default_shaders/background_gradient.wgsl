@@ -0,0 +1,26 @@
+struct BackgroundColors {
+    top: vec4<f32>,
+    bottom: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) height: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> colors: BackgroundColors;
+
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    var output: VertexOutput;
+    output.position = vec4f(uv * 2.0 - 1.0, 1.0, 1.0);
+    output.height = uv.y;
+    return output;
+}
+
+@fragment
+fn fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    return mix(colors.bottom, colors.top, clamp(input.height, 0.0, 1.0));
+}
@@ -1,48 +1,95 @@
 use downcast_rs::{impl_downcast, DowncastSync};
 use egui::Context;
-use std::{collections::HashMap, fmt::Debug, ops::Range};
-use wgpu::{CommandEncoder, Device, Queue, RenderPass};
-use winit_input_helper::WinitInputHelper;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use wgpu::{CommandEncoder, Device, Queue, RenderBundleEncoder, RenderPass};
 
-use crate::EngineDetails;
+use crate::{
+    EngineDetails,
+    engine_support::{gamepad_state::GamepadState, input_state::InputState},
+};
 
 use super::{
     actions::ActionQueue,
     compute::Compute,
+    dense_storage::DenseStorage,
     entity::{Entity, EntityId},
     material::Material,
-    scene::WorkloadOutput,
+    scene::{SequencedWorkloadOutputCollection, WorkloadOutput},
+    ui_hit_testing::UiHitState,
 };
 
 pub type ComponentId = u64;
 
+/// The `scene!` macro numbers the components it names from 1, generated ids start far above them
+static NEXT_COMPONENT_ID: AtomicU64 = AtomicU64::new(1 << 32);
+
+/// A new id for a component or compute built without an explicit one, unique within the process
+pub fn next_component_id() -> ComponentId {
+    NEXT_COMPONENT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Keeps `next_component_id` from handing out an id that was generated by an earlier run, e.g. one
+/// loaded from a `SceneDescriptor`
+pub fn reserve_component_id(id: ComponentId) {
+    NEXT_COMPONENT_ID.fetch_max(id.saturating_add(1), Ordering::Relaxed);
+}
+
 pub type Component = Box<dyn ComponentSystem>;
 
 pub struct UpdateParams<'a: 'b, 'b> {
     pub device: &'a Device,
     pub queue: &'a Queue,
-    pub input_manager: &'a WinitInputHelper,
+    pub input_manager: &'a dyn InputState,
+    pub gamepads: &'a GamepadState,
     pub other_components: &'a mut[&'b mut Component],
     pub computes: &'a mut [Compute],
     pub materials: &'a mut [&'b mut Material],
     pub engine_details: &'a EngineDetails,
     pub workload_outputs: &'a HashMap<ComponentId, Vec<WorkloadOutput>>,
+    pub sequenced_workload_outputs: &'a SequencedWorkloadOutputCollection,
+    /// Outputs of interval workloads, by launch count
+    pub interval_workload_outputs: &'a SequencedWorkloadOutputCollection,
+    /// Latest progress reported by the component's workload, until its output arrives
+    pub workload_progress: &'a HashMap<ComponentId, f32>,
+    /// The hover and click state of components that registered UI bounds with the scene
+    pub ui_hit_states: &'a HashMap<ComponentId, UiHitState>,
     pub entities: &'a HashMap<EntityId, Entity>,
     pub entity_component_groupings: HashMap<EntityId, Range<usize>>,
     pub active_camera: Option<ComponentId>,
+    /// The scene's dense components, already updated for this frame
+    pub dense_storage: &'a DenseStorage,
 }
 
 #[allow(unused)]
+#[async_trait::async_trait]
 pub trait ComponentSystem: ComponentDetails + Debug + DowncastSync + Send + Sync {
     fn initialize(&mut self, device: &Device) -> ActionQueue {
         self.set_initialized();
         Vec::new()
     }
 
+    /// Called by the scene instead of `initialize`, which it delegates to unless overridden.
+    /// Implementations must be annotated with `#[async_trait::async_trait]`.
+    async fn initialize_async(&mut self, device: &Device, queue: &Queue) -> ActionQueue {
+        self.initialize(device)
+    }
+
     fn update(&mut self, params: UpdateParams<'_, '_>) -> ActionQueue {
         Vec::new()
     }
 
+    /// Runs at the rate given to `V4Builder::fixed_timestep`, before `update`, with `fixed_delta`
+    /// as the time step. Never called without a fixed timestep.
+    fn fixed_update(&mut self, params: UpdateParams<'_, '_>, fixed_delta: Duration) -> ActionQueue {
+        Vec::new()
+    }
+
     fn render(
         &self,
         device: &Device,
@@ -52,6 +99,21 @@ pub trait ComponentSystem: ComponentDetails + Debug + DowncastSync + Send + Sync
     ) {
     }
 
+    /// Records the same draws as `render` into a material's render bundle. Returning false, the
+    /// default, makes the material fall back to recording its draws every frame.
+    fn render_bundle<'a>(
+        &'a self,
+        device: &Device,
+        queue: &Queue,
+        bundle_encoder: &mut RenderBundleEncoder<'a>,
+        other_components: &[&'a Component],
+    ) -> bool {
+        false
+    }
+
+    /// Called every frame for every enabled component, in the order they were added to the scene.
+    /// The work is recorded into the frame's encoder after the geometry pass, so it is submitted
+    /// after this frame's draws and before the screen-space effects and UI.
     fn command_encoder_operations(
         &self,
         device: &Device,
@@ -64,6 +126,13 @@ pub trait ComponentSystem: ComponentDetails + Debug + DowncastSync + Send + Sync
     }
 
     fn ui_render(&mut self, ctx: &Context) {}
+
+    /// Drops the GPU resources created in `initialize` and marks the component as uninitialized,
+    /// so the scene initializes it again. Components that keep buffers or bind groups should
+    /// override this to drop them.
+    fn release_gpu_resources(&mut self) {
+        self.set_uninitialized();
+    }
 }
 impl_downcast!(sync ComponentSystem);
 
@@ -74,6 +143,8 @@ pub trait ComponentDetails {
 
     fn set_initialized(&mut self);
 
+    fn set_uninitialized(&mut self);
+
     fn parent_entity_id(&self) -> EntityId;
 
     fn set_parent_entity(&mut self, parent_id: EntityId);
@@ -86,4 +157,7 @@ pub trait ComponentDetails {
     fn rendering_order(&self) -> i32 {
         0
     }
+
+    /// Use `Scene::set_component_rendering_order` so the entity's components are re-sorted
+    fn set_rendering_order(&mut self, _rendering_order: i32) {}
 }
@@ -1,16 +1,18 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
+use std::future::Future;
 
 use wgpu::{
     BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, ComputePass, ComputePipeline,
-    Device, ShaderStages,
+    Device, Queue, ShaderStages,
 };
 
-use crate::engine_management::pipeline::{PipelineShader, load_shader_module_descriptor};
+use crate::engine_management::pipeline::{
+    PipelineError, PipelineShader, load_validated_shader_module,
+};
 
 use super::{
-    component::{ComponentDetails, ComponentId, ComponentSystem},
+    component::{ComponentDetails, ComponentId, ComponentSystem, next_component_id},
     entity::EntityId,
-    material::ShaderAttachment,
+    material::{ShaderAttachment, ShaderBufferAttachment},
 };
 
 pub trait DynamicWorkgroupCounts: std::fmt::Debug + Send + Sync {
@@ -23,15 +25,38 @@ pub enum WorkgroupCounts {
     Dynamic(Box<dyn DynamicWorkgroupCounts>)
 }
 
+impl WorkgroupCounts {
+    /// The number of workgroups of `local_size` needed to cover `total_items` in each dimension.
+    /// No items need no workgroups. Panics if a dimension of `local_size` is zero.
+    pub fn workgroup_counts_for(
+        total_items: (u32, u32, u32),
+        local_size: (u32, u32, u32),
+    ) -> WorkgroupCounts {
+        assert!(
+            local_size.0 != 0 && local_size.1 != 0 && local_size.2 != 0,
+            "The local size {local_size:?} has a dimension of zero"
+        );
+        WorkgroupCounts::Static(
+            total_items.0.div_ceil(local_size.0),
+            total_items.1.div_ceil(local_size.1),
+            total_items.2.div_ceil(local_size.2),
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct Compute {
     attachments: Vec<ShaderAttachment>,
     shader_path: &'static str,
+    entry_point: &'static str,
     is_spirv: bool,
-    workgroup_counts: WorkgroupCounts,
+    /// `None` when the compute is dispatched from its indirect buffer
+    workgroup_counts: Option<WorkgroupCounts>,
+    indirect_buffer: Option<ShaderBufferAttachment>,
     bind_group_layout: Option<BindGroupLayout>,
     bind_group: Option<BindGroup>,
     pipeline: Option<ComputePipeline>,
+    pipeline_error: Option<PipelineError>,
     id: ComponentId,
     is_enabled: bool,
     is_initialized: bool,
@@ -50,11 +75,6 @@ impl Compute {
         match attachment {
             ShaderAttachment::Texture(tex) => {
                 let props = tex.texture_bundle.properties();
-                let view_dimension = if props.is_cubemap {
-                    wgpu::TextureViewDimension::D2Array
-                } else {
-                    wgpu::TextureViewDimension::D2
-                };
                 if let Some(storage_tex_access) = props.storage_texture {
                     BindGroupLayoutEntry {
                         binding,
@@ -62,7 +82,12 @@ impl Compute {
                         ty: wgpu::BindingType::StorageTexture {
                             access: storage_tex_access,
                             format: props.format,
-                            view_dimension,
+                            // Storage textures can't be bound as cubes, so cubemaps are arrays
+                            view_dimension: if props.is_cubemap {
+                                wgpu::TextureViewDimension::D2Array
+                            } else {
+                                wgpu::TextureViewDimension::D2
+                            },
                         },
                         count: None,
                     }
@@ -74,7 +99,11 @@ impl Compute {
                             sample_type: wgpu::TextureSampleType::Float {
                                 filterable: props.is_filtered,
                             },
-                            view_dimension,
+                            view_dimension: if props.is_cubemap {
+                                wgpu::TextureViewDimension::Cube
+                            } else {
+                                wgpu::TextureViewDimension::D2
+                            },
                             multisampled: false,
                         },
                         count: None,
@@ -114,27 +143,35 @@ impl Compute {
         device: &Device,
         bind_group_layout: &BindGroupLayout,
         shader_path: &'static str,
+        entry_point: &'static str,
         compute_id: ComponentId,
         is_spirv: bool,
-    ) -> ComputePipeline {
+    ) -> Result<ComputePipeline, PipelineError> {
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some(&format!("Compute {compute_id} pipeline layout")),
             bind_group_layouts: &[bind_group_layout],
             immediate_size: 0,
         });
 
-        let module =
-            load_shader_module_descriptor(device, &PipelineShader::Path(shader_path), is_spirv)
-                .unwrap();
+        let shader = PipelineShader::Path(shader_path);
+        let module = load_validated_shader_module(device, &shader, is_spirv)?;
 
-        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        let error_scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
             label: Some(&format!("Compute {compute_id} pipeline")),
             layout: Some(&layout),
             module: &module,
-            entry_point: Some("main"),
+            entry_point: Some(entry_point),
             compilation_options: Default::default(),
             cache: None,
-        })
+        });
+        match pollster::block_on(error_scope.pop()) {
+            Some(error) => Err(PipelineError {
+                shader: Some(shader),
+                message: error.to_string(),
+            }),
+            None => Ok(pipeline),
+        }
     }
 
     pub fn calculate(&self, compute_pass: &mut ComputePass) {
@@ -142,21 +179,106 @@ impl Compute {
             "The compute pipeline was not created. Remember to initialize the compute before executing it.",
         ));
         compute_pass.set_bind_group(0, self.bind_group.as_ref().expect("The compute bind group was not created. Remember to initialize the compute before executing it."), &[]);
+        if let Some(indirect_buffer) = &self.indirect_buffer {
+            compute_pass.dispatch_workgroups_indirect(indirect_buffer.buffer(), 0);
+            return;
+        }
         let (x, y, z) = match &self.workgroup_counts {
-            WorkgroupCounts::Static(x, y, z) => (*x, *y, *z),
-            WorkgroupCounts::Dynamic(func) => func.counts(),
+            Some(WorkgroupCounts::Static(x, y, z)) => (*x, *y, *z),
+            Some(WorkgroupCounts::Dynamic(func)) => func.counts(),
+            None => unreachable!("Computes without an indirect buffer have workgroup counts"),
         };
         compute_pass.dispatch_workgroups(x, y, z);
     }
 
+    pub fn indirect_buffer(&self) -> Option<&ShaderBufferAttachment> {
+        self.indirect_buffer.as_ref()
+    }
+
     pub fn attachments(&self) -> &[ShaderAttachment] {
         &self.attachments
     }
 
+    /// Copies the output, the last attachment, back to the CPU. The output has to be a buffer
+    /// created with `BufferUsages::COPY_SRC` in its extra usages. The copy is submitted right away,
+    /// so it sees every dispatch submitted before this is called.
+    pub fn read_output(
+        &self,
+        device: &Device,
+        queue: &Queue,
+    ) -> impl Future<Output = Result<Vec<u8>, String>> + use<> {
+        let staging_buffer = match self.attachments.last() {
+            Some(ShaderAttachment::Buffer(output))
+                if !output
+                    .buffer()
+                    .usage()
+                    .contains(wgpu::BufferUsages::COPY_SRC) =>
+            {
+                Err(format!(
+                    "The output buffer of compute {} was not created with BufferUsages::COPY_SRC.",
+                    self.id
+                ))
+            }
+            Some(ShaderAttachment::Buffer(output)) => {
+                let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(&format!("Compute {} output staging buffer", self.id)),
+                    size: output.buffer().size(),
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some(&format!("Compute {} output readback encoder", self.id)),
+                });
+                encoder.copy_buffer_to_buffer(
+                    output.buffer(),
+                    0,
+                    &staging_buffer,
+                    0,
+                    output.buffer().size(),
+                );
+                queue.submit([encoder.finish()]);
+                Ok(staging_buffer)
+            }
+            Some(ShaderAttachment::Texture(_)) => Err(format!(
+                "The output of compute {} is a texture, only buffer outputs can be read back.",
+                self.id
+            )),
+            None => Err(format!("Compute {} has no attachments to read.", self.id)),
+        };
+        let device = device.clone();
+
+        async move {
+            let staging_buffer = staging_buffer?;
+            let (sender, receiver) = futures::channel::oneshot::channel();
+            staging_buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let _ = sender.send(result);
+                });
+            device
+                .poll(wgpu::PollType::wait_indefinitely())
+                .map_err(|err| err.to_string())?;
+            receiver
+                .await
+                .map_err(|err| err.to_string())?
+                .map_err(|err| err.to_string())?;
+
+            let output = staging_buffer.slice(..).get_mapped_range().to_vec();
+            staging_buffer.unmap();
+            Ok(output)
+        }
+    }
+
     pub fn attachments_mut(&mut self) -> &mut [ShaderAttachment] {
         &mut self.attachments
     }
 
+    /// Why the compute pipeline could not be created. Such a compute is skipped when computes are
+    /// dispatched.
+    pub fn pipeline_error(&self) -> Option<&PipelineError> {
+        self.pipeline_error.as_ref()
+    }
+
     pub fn iterate_count(&self) -> usize {
         self.iterate_count
     }
@@ -194,13 +316,26 @@ impl ComponentSystem for Compute {
             entries: &bind_group_entries,
         });
 
-        self.pipeline = Some(Self::create_compute_pipeline(
+        match Self::create_compute_pipeline(
             device,
             &bind_group_layout,
             self.shader_path,
+            self.entry_point,
             self.id,
             self.is_spirv,
-        ));
+        ) {
+            Ok(pipeline) => {
+                self.pipeline = Some(pipeline);
+                self.pipeline_error = None;
+            }
+            Err(error) => {
+                log::warn!(
+                    "Failed to create the pipeline of compute {}: {error}",
+                    self.id
+                );
+                self.pipeline_error = Some(error);
+            }
+        }
 
         self.bind_group_layout = Some(bind_group_layout);
         self.bind_group = Some(bind_group);
@@ -208,6 +343,13 @@ impl ComponentSystem for Compute {
         self.set_initialized();
         vec![]
     }
+
+    fn release_gpu_resources(&mut self) {
+        self.pipeline = None;
+        self.bind_group = None;
+        self.bind_group_layout = None;
+        self.set_uninitialized();
+    }
 }
 
 impl ComponentDetails for Compute {
@@ -223,6 +365,10 @@ impl ComponentDetails for Compute {
         self.is_initialized = true;
     }
 
+    fn set_uninitialized(&mut self) {
+        self.is_initialized = false;
+    }
+
     fn parent_entity_id(&self) -> EntityId {
         self.parent_entity
     }
@@ -244,8 +390,10 @@ impl ComponentDetails for Compute {
 pub struct ComputeBuilder {
     attachments: Vec<ShaderAttachment>,
     shader_path: &'static str,
+    entry_point: &'static str,
     is_spirv: bool,
     workgroup_counts: Option<WorkgroupCounts>,
+    indirect_buffer: Option<ShaderBufferAttachment>,
     id: ComponentId,
     enabled: bool,
     iterate_count: usize,
@@ -256,8 +404,10 @@ impl Default for ComputeBuilder {
         Self {
             attachments: Vec::new(),
             shader_path: "",
+            entry_point: "main",
             is_spirv: false,
             workgroup_counts: None,
+            indirect_buffer: None,
             id: 0,
             enabled: true,
             iterate_count: 1,
@@ -276,6 +426,11 @@ impl ComputeBuilder {
         self
     }
 
+    pub fn entry_point(mut self, entry_point: &'static str) -> Self {
+        self.entry_point = entry_point;
+        self
+    }
+
     pub fn is_spirv(mut self, is_spirv: bool) -> Self {
         self.is_spirv = is_spirv;
         self
@@ -286,6 +441,15 @@ impl ComputeBuilder {
         self
     }
 
+    /// Dispatches the compute with the workgroup counts stored in the buffer, three `u32`s at the
+    /// start of it, instead of `workgroup_counts`. The buffer has to be created with
+    /// `BufferUsages::INDIRECT` in its extra usages, and can also be attached to a compute that
+    /// writes the counts.
+    pub fn indirect_buffer(mut self, indirect_buffer: ShaderBufferAttachment) -> Self {
+        self.indirect_buffer = Some(indirect_buffer);
+        self
+    }
+
     pub fn id(mut self, id: ComponentId) -> Self {
         self.id = id;
         self
@@ -302,20 +466,40 @@ impl ComputeBuilder {
     }
 
     pub fn build(self) -> Compute {
+        if let Some(WorkgroupCounts::Static(x, y, z)) = self.workgroup_counts
+            && (x == 0 || y == 0 || z == 0)
+        {
+            panic!(
+                "Invalid workgroup counts ({x}, {y}, {z}) for compute shader {}: every count must be at least 1.",
+                self.shader_path
+            );
+        }
+        if let Some(indirect_buffer) = &self.indirect_buffer {
+            assert!(
+                indirect_buffer
+                    .buffer()
+                    .usage()
+                    .contains(wgpu::BufferUsages::INDIRECT),
+                "The indirect buffer of compute shader {} lacks BufferUsages::INDIRECT.",
+                self.shader_path
+            );
+        } else if self.workgroup_counts.is_none() {
+            panic!("No workgroup counts function provided");
+        }
+
         Compute {
             attachments: self.attachments,
             shader_path: self.shader_path,
+            entry_point: self.entry_point,
             is_spirv: self.is_spirv,
-            workgroup_counts: self
-                .workgroup_counts
-                .expect("No workgroup counts function provided"),
+            workgroup_counts: self.workgroup_counts,
+            indirect_buffer: self.indirect_buffer,
             bind_group_layout: None,
             bind_group: None,
             pipeline: None,
+            pipeline_error: None,
             id: if self.id == 0 {
-                let mut hasher = DefaultHasher::new();
-                std::time::Instant::now().hash(&mut hasher);
-                hasher.finish()
+                next_component_id()
             } else {
                 self.id
             },
@@ -326,3 +510,37 @@ impl ComputeBuilder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(total_items: (u32, u32, u32), local_size: (u32, u32, u32)) -> (u32, u32, u32) {
+        match WorkgroupCounts::workgroup_counts_for(total_items, local_size) {
+            WorkgroupCounts::Static(x, y, z) => (x, y, z),
+            WorkgroupCounts::Dynamic(_) => panic!("Expected static workgroup counts"),
+        }
+    }
+
+    #[test]
+    fn exact_multiples_need_no_extra_workgroup() {
+        assert_eq!(counts((256, 64, 1), (64, 8, 1)), (4, 8, 1));
+    }
+
+    #[test]
+    fn remainders_round_up() {
+        assert_eq!(counts((257, 65, 3), (64, 8, 2)), (5, 9, 2));
+        assert_eq!(counts((1, 1, 1), (64, 64, 64)), (1, 1, 1));
+    }
+
+    #[test]
+    fn no_items_need_no_workgroups() {
+        assert_eq!(counts((0, 16, 1), (8, 8, 1)), (0, 2, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "has a dimension of zero")]
+    fn zero_local_size_is_rejected() {
+        counts((16, 16, 1), (8, 0, 1));
+    }
+}
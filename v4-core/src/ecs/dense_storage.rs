@@ -0,0 +1,206 @@
+use std::{any::TypeId, collections::HashMap, fmt::Debug};
+
+use downcast_rs::{DowncastSync, impl_downcast};
+use wgpu::{Device, Queue, RenderBundleEncoder, RenderPass};
+
+use crate::EngineDetails;
+
+use super::entity::EntityId;
+
+/// Data that can be stored contiguously, outside of the boxed component list. Each column is
+/// updated in a single typed pass, without dynamic dispatch per element.
+pub trait DenseComponent: Debug + Send + Sync + 'static {
+    fn update_dense(
+        &mut self,
+        _entity_id: EntityId,
+        _device: &Device,
+        _queue: &Queue,
+        _engine_details: &EngineDetails,
+    ) {
+    }
+
+    /// Called by a material drawing the entity, before it draws the entity's components
+    fn render(&self, _render_pass: &mut RenderPass) {}
+
+    /// Records the same state as `render` into a material's render bundle
+    fn render_bundle<'a>(&'a self, _bundle_encoder: &mut RenderBundleEncoder<'a>) {}
+
+    fn release_gpu_resources(&mut self) {}
+}
+
+#[derive(Debug)]
+pub struct DenseColumn<T: DenseComponent> {
+    entities: Vec<EntityId>,
+    data: Vec<T>,
+    /// The position of every entity in `entities` and `data`
+    indices: HashMap<EntityId, usize>,
+}
+
+impl<T: DenseComponent> Default for DenseColumn<T> {
+    fn default() -> Self {
+        Self {
+            entities: Vec::new(),
+            data: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+}
+
+impl<T: DenseComponent> DenseColumn<T> {
+    pub fn entities(&self) -> &[EntityId] {
+        &self.entities
+    }
+
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    fn index_of(&self, entity_id: EntityId) -> Option<usize> {
+        self.indices.get(&entity_id).copied()
+    }
+}
+
+trait ErasedDenseColumn: Debug + DowncastSync {
+    fn update(&mut self, device: &Device, queue: &Queue, engine_details: &EngineDetails);
+
+    fn render_entity(&self, entity_id: EntityId, render_pass: &mut RenderPass);
+
+    fn render_bundle_entity<'a>(
+        &'a self,
+        entity_id: EntityId,
+        bundle_encoder: &mut RenderBundleEncoder<'a>,
+    );
+
+    fn release_gpu_resources(&mut self);
+
+    fn remove_entity(&mut self, entity_id: EntityId);
+}
+impl_downcast!(sync ErasedDenseColumn);
+
+impl<T: DenseComponent> ErasedDenseColumn for DenseColumn<T> {
+    fn update(&mut self, device: &Device, queue: &Queue, engine_details: &EngineDetails) {
+        for (entity_id, data) in self.entities.iter().zip(self.data.iter_mut()) {
+            data.update_dense(*entity_id, device, queue, engine_details);
+        }
+    }
+
+    fn render_entity(&self, entity_id: EntityId, render_pass: &mut RenderPass) {
+        if let Some(index) = self.index_of(entity_id) {
+            self.data[index].render(render_pass);
+        }
+    }
+
+    fn render_bundle_entity<'a>(
+        &'a self,
+        entity_id: EntityId,
+        bundle_encoder: &mut RenderBundleEncoder<'a>,
+    ) {
+        if let Some(index) = self.index_of(entity_id) {
+            self.data[index].render_bundle(bundle_encoder);
+        }
+    }
+
+    fn release_gpu_resources(&mut self) {
+        for data in &mut self.data {
+            data.release_gpu_resources();
+        }
+    }
+
+    fn remove_entity(&mut self, entity_id: EntityId) {
+        if let Some(index) = self.indices.remove(&entity_id) {
+            self.entities.swap_remove(index);
+            self.data.swap_remove(index);
+            if let Some(moved_entity) = self.entities.get(index) {
+                self.indices.insert(*moved_entity, index);
+            }
+        }
+    }
+}
+
+/// Opt-in archetype-style storage, one contiguous column per component type.
+#[derive(Debug, Default)]
+pub struct DenseStorage {
+    columns: HashMap<TypeId, Box<dyn ErasedDenseColumn>>,
+}
+
+impl DenseStorage {
+    /// Inserts the component for the given entity, replacing any existing one of the same type
+    pub fn insert<T: DenseComponent>(&mut self, entity_id: EntityId, component: T) {
+        let column = self
+            .columns
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(DenseColumn::<T>::default()))
+            .downcast_mut::<DenseColumn<T>>()
+            .expect("Dense column type mismatch.");
+
+        if let Some(index) = column.index_of(entity_id) {
+            column.data[index] = component;
+        } else {
+            column.indices.insert(entity_id, column.entities.len());
+            column.entities.push(entity_id);
+            column.data.push(component);
+        }
+    }
+
+    pub fn column<T: DenseComponent>(&self) -> Option<&DenseColumn<T>> {
+        self.columns
+            .get(&TypeId::of::<T>())
+            .and_then(|column| column.downcast_ref::<DenseColumn<T>>())
+    }
+
+    pub fn column_mut<T: DenseComponent>(&mut self) -> Option<&mut DenseColumn<T>> {
+        self.columns
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|column| column.downcast_mut::<DenseColumn<T>>())
+    }
+
+    pub fn get<T: DenseComponent>(&self, entity_id: EntityId) -> Option<&T> {
+        let column = self.column::<T>()?;
+        column.index_of(entity_id).map(|index| &column.data[index])
+    }
+
+    pub fn get_mut<T: DenseComponent>(&mut self, entity_id: EntityId) -> Option<&mut T> {
+        let column = self.column_mut::<T>()?;
+        column
+            .index_of(entity_id)
+            .map(|index| &mut column.data[index])
+    }
+
+    pub fn remove_entity(&mut self, entity_id: EntityId) {
+        for column in self.columns.values_mut() {
+            column.remove_entity(entity_id);
+        }
+    }
+
+    pub fn update(&mut self, device: &Device, queue: &Queue, engine_details: &EngineDetails) {
+        for column in self.columns.values_mut() {
+            column.update(device, queue, engine_details);
+        }
+    }
+
+    pub fn render_entity(&self, entity_id: EntityId, render_pass: &mut RenderPass) {
+        for column in self.columns.values() {
+            column.render_entity(entity_id, render_pass);
+        }
+    }
+
+    pub fn render_bundle_entity<'a>(
+        &'a self,
+        entity_id: EntityId,
+        bundle_encoder: &mut RenderBundleEncoder<'a>,
+    ) {
+        for column in self.columns.values() {
+            column.render_bundle_entity(entity_id, bundle_encoder);
+        }
+    }
+
+    pub fn release_gpu_resources(&mut self) {
+        for column in self.columns.values_mut() {
+            column.release_gpu_resources();
+        }
+    }
+}
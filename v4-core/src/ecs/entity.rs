@@ -1,7 +1,107 @@
 use super::component::ComponentId;
 
+/// The low 32 bits are the entity's slot index and the high 32 bits its generation. A slot freed
+/// by a removed entity is reused with the next generation, so ids held for the removed entity never
+/// resolve to the replacement. Index 0 is reserved to mean "no entity".
 pub type EntityId = u64;
 
+pub fn entity_id(index: u32, generation: u32) -> EntityId {
+    ((generation as u64) << 32) | index as u64
+}
+
+pub fn entity_index(id: EntityId) -> u32 {
+    id as u32
+}
+
+pub fn entity_generation(id: EntityId) -> u32 {
+    (id >> 32) as u32
+}
+
+#[derive(Debug, Clone, Copy)]
+struct EntitySlot {
+    generation: u32,
+    is_alive: bool,
+}
+
+/// Hands out generational entity ids. Fresh slots start at generation 0, so the first ids
+/// allocated by a scene are 1, 2, 3 and so on.
+#[derive(Debug, Default)]
+pub struct EntityAllocator {
+    slots: Vec<EntitySlot>,
+    free_indices: Vec<u32>,
+}
+
+impl EntityAllocator {
+    pub fn allocate(&mut self) -> EntityId {
+        if let Some(index) = self.free_indices.pop() {
+            let slot = &mut self.slots[index as usize - 1];
+            slot.is_alive = true;
+            return entity_id(index, slot.generation);
+        }
+        self.slots.push(EntitySlot {
+            generation: 0,
+            is_alive: true,
+        });
+        entity_id(self.slots.len() as u32, 0)
+    }
+
+    /// Marks this exact id as alive, for loading saved entities. Slots skipped over to reach its
+    /// index become free. Returns false if the slot is already taken or the index is 0.
+    pub fn allocate_id(&mut self, id: EntityId) -> bool {
+        let index = entity_index(id);
+        if index == 0 {
+            return false;
+        }
+        while self.slots.len() < index as usize {
+            self.slots.push(EntitySlot {
+                generation: 0,
+                is_alive: false,
+            });
+            self.free_indices.push(self.slots.len() as u32);
+        }
+
+        let slot = &mut self.slots[index as usize - 1];
+        if slot.is_alive {
+            return false;
+        }
+        slot.generation = entity_generation(id);
+        slot.is_alive = true;
+        self.free_indices.retain(|free_index| *free_index != index);
+        true
+    }
+
+    /// Frees the id's slot for reuse. Returns false if the id was already stale
+    pub fn free(&mut self, id: EntityId) -> bool {
+        if !self.is_alive(id) {
+            return false;
+        }
+        let index = entity_index(id);
+        let slot = &mut self.slots[index as usize - 1];
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.is_alive = false;
+        self.free_indices.push(index);
+        true
+    }
+
+    pub fn is_alive(&self, id: EntityId) -> bool {
+        let index = entity_index(id);
+        index != 0
+            && self
+                .slots
+                .get(index as usize - 1)
+                .is_some_and(|slot| slot.is_alive && slot.generation == entity_generation(id))
+    }
+
+    /// The number of entities that are currently alive
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free_indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 #[derive(Debug)]
 pub struct Entity {
     id: EntityId,
@@ -65,4 +165,87 @@ impl Entity {
     pub fn push_child(&mut self, child: EntityId) {
         self.children_ids.push(child);
     }
+
+    pub fn remove_child(&mut self, child: EntityId) {
+        self.children_ids.retain(|id| *id != child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocates_sequential_ids() {
+        let mut allocator = EntityAllocator::default();
+
+        assert_eq!(allocator.allocate(), entity_id(1, 0));
+        assert_eq!(allocator.allocate(), entity_id(2, 0));
+        assert_eq!(allocator.len(), 2);
+    }
+
+    #[test]
+    fn reuses_freed_slot_with_next_generation() {
+        let mut allocator = EntityAllocator::default();
+        let first = allocator.allocate();
+        allocator.allocate();
+
+        assert!(allocator.free(first));
+        let reused = allocator.allocate();
+
+        assert_eq!(entity_index(reused), entity_index(first));
+        assert_eq!(entity_generation(reused), 1);
+        assert_eq!(allocator.len(), 2);
+    }
+
+    #[test]
+    fn stale_ids_are_not_alive() {
+        let mut allocator = EntityAllocator::default();
+        let first = allocator.allocate();
+        assert!(allocator.is_alive(first));
+
+        allocator.free(first);
+        assert!(!allocator.is_alive(first));
+
+        let reused = allocator.allocate();
+        assert!(allocator.is_alive(reused));
+        assert!(!allocator.is_alive(first));
+        assert!(!allocator.is_alive(0));
+        assert!(!allocator.is_alive(entity_id(5, 0)));
+    }
+
+    #[test]
+    fn allocates_exact_ids() {
+        let mut allocator = EntityAllocator::default();
+
+        assert!(allocator.allocate_id(entity_id(3, 2)));
+        assert!(allocator.is_alive(entity_id(3, 2)));
+        assert!(!allocator.allocate_id(entity_id(3, 2)));
+        assert!(!allocator.allocate_id(0));
+        assert_eq!(allocator.len(), 1);
+
+        // The skipped slots are handed out before new ones
+        let mut reused = vec![
+            entity_index(allocator.allocate()),
+            entity_index(allocator.allocate()),
+        ];
+        reused.sort_unstable();
+        assert_eq!(reused, vec![1, 2]);
+        assert_eq!(allocator.allocate(), entity_id(4, 0));
+    }
+
+    #[test]
+    fn double_free_is_rejected() {
+        let mut allocator = EntityAllocator::default();
+        let first = allocator.allocate();
+
+        assert!(allocator.free(first));
+        assert!(!allocator.free(first));
+        assert!(allocator.is_empty());
+
+        // The slot was only queued once, so both allocations get distinct slots
+        let a = allocator.allocate();
+        let b = allocator.allocate();
+        assert_ne!(entity_index(a), entity_index(b));
+    }
 }
@@ -1,19 +1,18 @@
-use std::ops::Range;
-
 use wgpu::{
-    BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, Buffer, CommandEncoder,
-    Device, Queue, Sampler, ShaderStages, util::DeviceExt,
+    BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, Buffer, Device, Queue,
+    RenderBundle, RenderPipeline, Sampler, ShaderStages, TextureFormat, util::DeviceExt,
 };
 
 use crate::{
-    ecs::compute::Compute,
-    engine_management::pipeline::PipelineId,
-    engine_support::texture_support::{TextureBundle, TextureProperties},
+    EngineDetails,
+    engine_management::pipeline::{PipelineId, PipelineTarget},
+    engine_support::texture_support::{SamplerLod, TextureBundle, TextureProperties},
 };
 
 use super::{
     actions::ActionQueue,
     component::{Component, ComponentDetails, ComponentId, ComponentSystem, UpdateParams},
+    dense_storage::DenseStorage,
     entity::EntityId,
 };
 
@@ -81,16 +80,57 @@ pub enum ShaderAttachment {
     Buffer(ShaderBufferAttachment),
 }
 
+/// Runs every frame before the material is drawn, e.g. to write the elapsed time into a uniform
+/// buffer attachment. Growing a buffer past its size recreates the material's bind group.
+pub trait MaterialUpdater: std::fmt::Debug + Send + Sync {
+    fn update(
+        &mut self,
+        attachments: &mut [ShaderAttachment],
+        device: &Device,
+        queue: &Queue,
+        engine_details: &EngineDetails,
+    );
+}
+
+/// The texture a screen-space effect reads at binding 0 of group 0. Binding 4 always holds the
+/// scene as it was before any effect ran, so an effect can combine both, e.g. the composite step
+/// of a bloom chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScreenSpaceInput {
+    /// The output of the previous effect, or the scene for the first one
+    #[default]
+    Previous,
+    /// The scene before any effect ran
+    Original,
+}
+
+#[derive(Debug)]
+enum RenderBundleState {
+    Stale,
+    Ready(RenderBundle),
+    Unsupported,
+}
+
+/// All of a material's attachments share one bind group, at bindings 0, 1, 2 and so on in the
+/// order they were given, followed by the samplers its textures need. The group is at index 1 when
+/// the pipeline uses the camera, which takes index 0, and at index 0 otherwise.
 #[derive(Debug)]
 pub struct Material {
     id: ComponentId,
     pipeline_id: PipelineId,
     entities_attached: Vec<EntityId>,
-    component_ranges: Vec<Range<usize>>,
+    draw_order: Vec<usize>,
     attachments: Vec<ShaderAttachment>,
     bind_group_layout: Option<BindGroupLayout>,
     bind_group: Option<BindGroup>,
     immediate_data: Vec<u8>,
+    sampler_lod: SamplerLod,
+    render_bundle_enabled: bool,
+    render_bundle: RenderBundleState,
+    bundled_components: Vec<ComponentId>,
+    updater: Option<Box<dyn MaterialUpdater>>,
+    screen_space_input: ScreenSpaceInput,
+    screen_space_order: i32,
     is_initialized: bool,
     is_enabled: bool,
 }
@@ -108,16 +148,30 @@ impl Material {
             id,
             attachments,
             entities_attached,
-            component_ranges: Vec::new(),
+            draw_order: Vec::new(),
             pipeline_id,
             bind_group_layout: None,
             bind_group: None,
             immediate_data,
+            sampler_lod: SamplerLod::default(),
+            render_bundle_enabled: false,
+            render_bundle: RenderBundleState::Stale,
+            bundled_components: Vec::new(),
+            updater: None,
+            screen_space_input: ScreenSpaceInput::default(),
+            screen_space_order: 0,
             is_initialized: false,
             is_enabled,
         }
     }
 
+    fn attachment_buffer(attachment: &ShaderAttachment) -> Option<Buffer> {
+        match attachment {
+            ShaderAttachment::Buffer(buf) => Some(buf.buffer.clone()),
+            ShaderAttachment::Texture(_) => None,
+        }
+    }
+
     pub fn create_attachment_bind_group_layout_entry(
         attachment: &ShaderAttachment,
         binding: u32,
@@ -143,7 +197,7 @@ impl Material {
                 binding,
                 visibility: buf.visibility,
                 ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
+                    ty: buf.buffer_type,
                     has_dynamic_offset: false,
                     min_binding_size: None,
                 },
@@ -251,7 +305,13 @@ impl Material {
                         } else {
                             wgpu::FilterMode::Nearest
                         },
-                        mipmap_filter: wgpu::MipmapFilterMode::Nearest,
+                        mipmap_filter: if is_filtering {
+                            wgpu::MipmapFilterMode::Linear
+                        } else {
+                            wgpu::MipmapFilterMode::Nearest
+                        },
+                        lod_min_clamp: self.sampler_lod.lod_min_clamp,
+                        lod_max_clamp: self.sampler_lod.lod_max_clamp,
                         ..Default::default()
                     });
                     Some((sampler, is_filtering, *visibility))
@@ -262,10 +322,71 @@ impl Material {
             .collect()
     }
 
+    /// The attachments at bindings 0, 1, 2 and so on, followed by the samplers
+    fn bind_group_entries<'a>(
+        &'a self,
+        samplers: &'a [(Sampler, bool, ShaderStages)],
+    ) -> (Vec<BindGroupLayoutEntry>, Vec<BindGroupEntry<'a>>) {
+        let (bind_group_layout_entries, bind_group_entries): (
+            Vec<BindGroupLayoutEntry>,
+            Vec<BindGroupEntry>,
+        ) = self
+            .attachments
+            .iter()
+            .enumerate()
+            .map(|(binding, attachment)| {
+                (
+                    Self::create_attachment_bind_group_layout_entry(attachment, binding as u32),
+                    Self::create_attachment_bind_group_entry(attachment, binding as u32),
+                )
+            })
+            .unzip();
+
+        let (samplers_bind_group_layout_entries, samplers_bind_group_entries): (
+            Vec<BindGroupLayoutEntry>,
+            Vec<BindGroupEntry>,
+        ) = samplers
+            .iter()
+            .enumerate()
+            .map(|(i, (sampler, is_filtering, visibility))| {
+                Self::create_sampler_entries(
+                    sampler,
+                    *is_filtering,
+                    *visibility,
+                    (i + bind_group_layout_entries.len()) as u32,
+                )
+            })
+            .unzip();
+
+        (
+            bind_group_layout_entries
+                .into_iter()
+                .chain(samplers_bind_group_layout_entries)
+                .collect(),
+            bind_group_entries
+                .into_iter()
+                .chain(samplers_bind_group_entries)
+                .collect(),
+        )
+    }
+
     pub fn attach_entity(&mut self, entity_id: EntityId) {
         self.entities_attached.push(entity_id);
     }
 
+    pub fn detach_entity(&mut self, entity_id: EntityId) {
+        self.entities_attached.retain(|id| *id != entity_id);
+    }
+
+    pub fn attached_entities(&self) -> &[EntityId] {
+        &self.entities_attached
+    }
+
+    /// Indices into the scene's components in the order they are drawn, as of the last update
+    pub fn draw_order(&self) -> &[usize] {
+        &self.draw_order
+    }
+
     pub fn bind_group_layout(&self) -> Option<&BindGroupLayout> {
         self.bind_group_layout.as_ref()
     }
@@ -278,10 +399,26 @@ impl Material {
         self.attachments.as_ref()
     }
 
+    pub fn set_updater(&mut self, updater: impl MaterialUpdater + 'static) {
+        self.updater = Some(Box::new(updater));
+    }
+
+    pub fn clear_updater(&mut self) {
+        self.updater = None;
+    }
+
     pub fn uses_camera(&self) -> bool {
         self.pipeline_id.uses_camera
     }
 
+    /// Whether any texture attachment is a view of the given texture
+    pub fn samples_texture(&self, texture: &wgpu::Texture) -> bool {
+        self.attachments.iter().any(|attachment| match attachment {
+            ShaderAttachment::Texture(tex) => tex.texture_bundle.view().texture() == texture,
+            ShaderAttachment::Buffer(_) => false,
+        })
+    }
+
     pub fn pipeline_id(&self) -> &PipelineId {
         &self.pipeline_id
     }
@@ -292,54 +429,197 @@ impl Material {
 
     pub fn set_immediate_data(&mut self, immediate_data: &[u8]) {
         self.immediate_data = immediate_data.to_vec();
+        self.invalidate_render_bundle();
+    }
+
+    pub fn sampler_lod(&self) -> SamplerLod {
+        self.sampler_lod
+    }
+
+    /// The samplers are recreated, along with the bind group, the next time the scene initializes
+    /// its materials
+    pub fn set_sampler_lod(&mut self, sampler_lod: SamplerLod) {
+        if self.sampler_lod != sampler_lod {
+            self.sampler_lod = sampler_lod;
+            self.is_initialized = false;
+            self.invalidate_render_bundle();
+        }
+    }
+
+    /// When enabled, the material's draws are recorded once into a render bundle and replayed
+    /// every frame. The bundle is rebuilt when the set of enabled components drawn by the
+    /// material changes, but changes to the components' own buffers require
+    /// `invalidate_render_bundle`.
+    pub fn set_render_bundle_enabled(&mut self, enabled: bool) {
+        self.render_bundle_enabled = enabled;
+        self.invalidate_render_bundle();
+    }
+
+    pub fn render_bundle_enabled(&self) -> bool {
+        self.render_bundle_enabled
+    }
+
+    pub fn screen_space_input(&self) -> ScreenSpaceInput {
+        self.screen_space_input
+    }
+
+    /// Only used by screen-space materials
+    pub fn set_screen_space_input(&mut self, screen_space_input: ScreenSpaceInput) {
+        self.screen_space_input = screen_space_input;
+    }
+
+    pub fn screen_space_order(&self) -> i32 {
+        self.screen_space_order
+    }
+
+    /// Screen-space effects run from the lowest order to the highest, effects with the same order
+    /// run in the order they were created
+    pub fn set_screen_space_order(&mut self, screen_space_order: i32) {
+        self.screen_space_order = screen_space_order;
+    }
+
+    pub fn invalidate_render_bundle(&mut self) {
+        self.render_bundle = RenderBundleState::Stale;
+    }
+
+    pub fn render_bundle(&self) -> Option<&RenderBundle> {
+        match &self.render_bundle {
+            RenderBundleState::Ready(render_bundle) if self.render_bundle_enabled => {
+                Some(render_bundle)
+            }
+            _ => None,
+        }
+    }
+
+    /// Draws the attached components like `render`, binding each entity's dense components, such
+    /// as dense transforms, before drawing the entity's components
+    pub fn render_with_dense_components(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        render_pass: &mut wgpu::RenderPass,
+        other_components: &[&Component],
+        dense_storage: &DenseStorage,
+    ) {
+        let bind_group_offset = if self.uses_camera() { 1 } else { 0 };
+        render_pass.set_bind_group(bind_group_offset, self.bind_group.as_ref().expect("The material bind group was not created. Remember to initialize the material before executing it."), &[]);
+
+        let mut bound_entity = None;
+        for &index in &self.draw_order {
+            let component = other_components[index];
+            if !component.is_enabled() {
+                continue;
+            }
+            let entity_id = component.parent_entity_id();
+            if bound_entity != Some(entity_id) {
+                dense_storage.render_entity(entity_id, render_pass);
+                bound_entity = Some(entity_id);
+            }
+            component.render(device, queue, render_pass, other_components);
+        }
+    }
+
+    pub fn record_render_bundle(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        pipeline: &RenderPipeline,
+        camera_bind_group: Option<&BindGroup>,
+        other_components: &[&Component],
+        dense_storage: &DenseStorage,
+        target: &PipelineTarget,
+        depth_format: TextureFormat,
+    ) {
+        if !self.render_bundle_enabled || !matches!(self.render_bundle, RenderBundleState::Stale) {
+            return;
+        }
+        if self.uses_camera() && camera_bind_group.is_none() {
+            return;
+        }
+
+        self.render_bundle = match self.encode_render_bundle(
+            device,
+            queue,
+            pipeline,
+            camera_bind_group,
+            other_components,
+            dense_storage,
+            target,
+            depth_format,
+        ) {
+            Some(render_bundle) => RenderBundleState::Ready(render_bundle),
+            None => RenderBundleState::Unsupported,
+        };
+    }
+
+    fn encode_render_bundle(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        pipeline: &RenderPipeline,
+        camera_bind_group: Option<&BindGroup>,
+        other_components: &[&Component],
+        dense_storage: &DenseStorage,
+        target: &PipelineTarget,
+        depth_format: TextureFormat,
+    ) -> Option<RenderBundle> {
+        let mut bundle_encoder =
+            device.create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                label: Some(&format!("Material {} | Render bundle encoder", self.id)),
+                color_formats: &target.color_formats(),
+                depth_stencil: Some(wgpu::RenderBundleDepthStencil {
+                    format: depth_format,
+                    depth_read_only: false,
+                    stencil_read_only: !depth_format.has_stencil_aspect(),
+                }),
+                sample_count: target.sample_count,
+                multiview: None,
+            });
+
+        bundle_encoder.set_pipeline(pipeline);
+        if self.uses_camera() {
+            bundle_encoder.set_bind_group(0, camera_bind_group, &[]);
+        }
+        if self.pipeline_id.immediate_size != 0 {
+            bundle_encoder.set_immediates(0, &self.immediate_data);
+        }
+        let bind_group_offset = if self.uses_camera() { 1 } else { 0 };
+        bundle_encoder.set_bind_group(
+            bind_group_offset,
+            self.bind_group.as_ref().expect(
+                "The material bind group was not created. Remember to initialize the material before executing it.",
+            ),
+            &[],
+        );
+
+        let mut bound_entity = None;
+        for &index in &self.draw_order {
+            let component = other_components[index];
+            if !component.is_enabled() {
+                continue;
+            }
+            let entity_id = component.parent_entity_id();
+            if bound_entity != Some(entity_id) {
+                dense_storage.render_bundle_entity(entity_id, &mut bundle_encoder);
+                bound_entity = Some(entity_id);
+            }
+            if !component.render_bundle(device, queue, &mut bundle_encoder, other_components) {
+                return None;
+            }
+        }
+
+        Some(bundle_encoder.finish(&wgpu::RenderBundleDescriptor {
+            label: Some(&format!("Material {} | Render bundle", self.id)),
+        }))
     }
 }
 
 // #[async_trait::async_trait]
 impl ComponentSystem for Material {
     fn initialize(&mut self, device: &Device) -> ActionQueue {
-        let (bind_group_layout_entries, bind_group_entries): (
-            Vec<BindGroupLayoutEntry>,
-            Vec<BindGroupEntry>,
-        ) = self
-            .attachments
-            .iter()
-            .enumerate()
-            .map(|(binding, attachment)| {
-                (
-                    Self::create_attachment_bind_group_layout_entry(attachment, binding as u32),
-                    Self::create_attachment_bind_group_entry(attachment, binding as u32),
-                )
-            })
-            .unzip();
-
         let samplers = self.create_samplers(device);
-
-        let (samplers_bind_group_layout_entries, samplers_bind_group_entries): (
-            Vec<BindGroupLayoutEntry>,
-            Vec<BindGroupEntry>,
-        ) = samplers
-            .iter()
-            .enumerate()
-            .map(|(i, (sampler, is_filtering, visibility))| {
-                Self::create_sampler_entries(
-                    sampler,
-                    *is_filtering,
-                    *visibility,
-                    (i + bind_group_layout_entries.len()) as u32,
-                )
-            })
-            .unzip();
-
-        let all_bind_group_layout_entries: Vec<BindGroupLayoutEntry> = bind_group_layout_entries
-            .into_iter()
-            .chain(samplers_bind_group_layout_entries)
-            .collect();
-
-        let all_bind_group_entries: Vec<BindGroupEntry> = bind_group_entries
-            .into_iter()
-            .chain(samplers_bind_group_entries)
-            .collect();
+        let (all_bind_group_layout_entries, all_bind_group_entries) =
+            self.bind_group_entries(&samplers);
 
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some(&format!("Material {} | Bind group layout", self.id)),
@@ -363,20 +643,57 @@ impl ComponentSystem for Material {
     fn update(
         &mut self,
         UpdateParams {
+            device,
+            queue,
             entity_component_groupings,
+            other_components,
+            engine_details,
             ..
         }: UpdateParams<'_, '_>,
     ) -> crate::ecs::actions::ActionQueue {
-        self.component_ranges = entity_component_groupings
+        if let Some(updater) = self.updater.as_mut() {
+            let buffers_before: Vec<Option<Buffer>> = self
+                .attachments
+                .iter()
+                .map(Self::attachment_buffer)
+                .collect();
+            updater.update(&mut self.attachments, device, queue, engine_details);
+            let buffers_replaced = self
+                .attachments
+                .iter()
+                .map(Self::attachment_buffer)
+                .ne(buffers_before);
+            if buffers_replaced {
+                self.initialize(device);
+                self.invalidate_render_bundle();
+            }
+        }
+
+        // Components from every attached entity are merged into a single list ordered by
+        // rendering order, so draws across entities are deterministic. Ties keep entity order.
+        let mut draw_order: Vec<usize> = entity_component_groupings
             .iter()
-            .flat_map(|(entity_id, range)| {
-                if self.entities_attached.contains(entity_id) {
-                    Some(range.clone())
-                } else {
-                    None
-                }
-            })
+            .filter(|(entity_id, _)| self.entities_attached.contains(entity_id))
+            .flat_map(|(_, range)| range.clone())
             .collect();
+        draw_order.sort_unstable();
+        draw_order.sort_by_key(|&index| other_components[index].rendering_order());
+        self.draw_order = draw_order;
+
+        if self.render_bundle_enabled {
+            let drawn_components: Vec<ComponentId> = self
+                .draw_order
+                .iter()
+                .map(|&index| other_components[index])
+                .filter(|component| component.is_enabled())
+                .map(|component| component.id())
+                .collect();
+            if drawn_components != self.bundled_components {
+                self.bundled_components = drawn_components;
+                self.invalidate_render_bundle();
+            }
+        }
+
         Vec::new()
     }
 
@@ -387,40 +704,21 @@ impl ComponentSystem for Material {
         render_pass: &mut wgpu::RenderPass,
         other_components: &[&Component],
     ) {
-        let bind_group_offset = if self.uses_camera() { 1 } else { 0 };
-        render_pass.set_bind_group(bind_group_offset, self.bind_group.as_ref().expect("The material bind group was not created. Remember to initialize the material before executing it."), &[]);
-
-        for range in &self.component_ranges {
-            for component in &other_components[range.clone()] {
-                if !component.is_enabled() {
-                    continue;
-                }
-                component.render(device, queue, render_pass, other_components);
-            }
-        }
+        self.render_with_dense_components(
+            device,
+            queue,
+            render_pass,
+            other_components,
+            &DenseStorage::default(),
+        );
     }
 
-    fn command_encoder_operations(
-        &self,
-        device: &Device,
-        queue: &Queue,
-        encoder: &mut CommandEncoder,
-        other_components: &[&Component],
-        materials: &[Material],
-        computes: &[Compute],
-    ) {
-        for range in &self.component_ranges {
-            for component in &other_components[range.clone()] {
-                component.command_encoder_operations(
-                    device,
-                    queue,
-                    encoder,
-                    other_components,
-                    materials,
-                    computes,
-                );
-            }
-        }
+    fn release_gpu_resources(&mut self) {
+        self.bind_group = None;
+        self.bind_group_layout = None;
+        self.bundled_components.clear();
+        self.invalidate_render_bundle();
+        self.set_uninitialized();
     }
 }
 
@@ -437,6 +735,10 @@ impl ComponentDetails for Material {
         self.is_initialized = true;
     }
 
+    fn set_uninitialized(&mut self) {
+        self.is_initialized = false;
+    }
+
     fn parent_entity_id(&self) -> EntityId {
         0
     }
@@ -451,3 +753,85 @@ impl ComponentDetails for Material {
         self.is_enabled = enabled_state;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::engine_management::pipeline::{GeometryDetails, PipelineShader};
+
+    use super::*;
+
+    fn texture_attachment(device: &Device) -> ShaderAttachment {
+        let (_, texture_bundle) =
+            TextureBundle::create_texture(device, 4, 4, TextureProperties::default());
+        ShaderAttachment::Texture(ShaderTextureAttachment {
+            texture_bundle,
+            visibility: ShaderStages::FRAGMENT,
+        })
+    }
+
+    fn two_texture_material(device: &Device) -> Material {
+        let pipeline_id = PipelineId {
+            vertex_shader: PipelineShader::Raw("".into()),
+            spirv_vertex_shader: false,
+            fragment_shader: PipelineShader::Raw("".into()),
+            spirv_fragment_shader: false,
+            vertex_entry: "vertex",
+            fragment_entry: "fragment",
+            vertex_layouts: Vec::new(),
+            uses_camera: false,
+            is_screen_space: false,
+            geometry_details: GeometryDetails::default(),
+            immediate_size: 0,
+            render_priority: 0,
+            blend_state: PipelineId::DEFAULT_BLEND_STATE,
+            writes_gbuffer: false,
+        };
+        Material::new(
+            1,
+            pipeline_id,
+            vec![texture_attachment(device), texture_attachment(device)],
+            Vec::new(),
+            Vec::new(),
+            true,
+        )
+    }
+
+    #[test]
+    fn textures_get_sequential_bindings_before_the_sampler() {
+        let (device, _queue) = Device::noop(&wgpu::DeviceDescriptor::default());
+        let material = two_texture_material(&device);
+        let samplers = material.create_samplers(&device);
+
+        let (layout_entries, entries) = material.bind_group_entries(&samplers);
+
+        let bindings: Vec<u32> = layout_entries.iter().map(|entry| entry.binding).collect();
+        assert_eq!(bindings, [0, 1, 2]);
+        assert!(matches!(
+            layout_entries[0].ty,
+            wgpu::BindingType::Texture { .. }
+        ));
+        assert!(matches!(
+            layout_entries[1].ty,
+            wgpu::BindingType::Texture { .. }
+        ));
+        assert!(matches!(
+            layout_entries[2].ty,
+            wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering)
+        ));
+        let bindings: Vec<u32> = entries.iter().map(|entry| entry.binding).collect();
+        assert_eq!(bindings, [0, 1, 2]);
+    }
+
+    #[test]
+    fn two_texture_materials_create_a_valid_bind_group() {
+        let (device, _queue) = Device::noop(&wgpu::DeviceDescriptor::default());
+        let mut material = two_texture_material(&device);
+
+        let error_scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+        material.initialize(&device);
+
+        assert!(pollster::block_on(error_scope.pop()).is_none());
+        assert!(material.bind_group_layout().is_some());
+        assert!(material.bind_group().is_some());
+    }
+}
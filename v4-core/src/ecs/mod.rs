@@ -1,6 +1,11 @@
 pub mod actions;
 pub mod component;
 pub mod compute;
+pub mod dense_storage;
 pub mod entity;
 pub mod material;
+pub mod render_target;
 pub mod scene;
+pub mod scene_descriptor;
+pub mod system;
+pub mod ui_hit_testing;
@@ -0,0 +1,138 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use wgpu::{BindGroup, Buffer, Device, Queue, Texture, TextureFormat, TextureUsages};
+
+use crate::{
+    engine_management::pipeline::PipelineTarget,
+    engine_support::texture_support::{CompleteTexture, TextureBundle, TextureProperties},
+};
+
+pub type RenderTargetId = u64;
+
+static NEXT_RENDER_TARGET_ID: AtomicU64 = AtomicU64::new(1);
+
+/// An off-screen texture that the scene is rendered into before the main pass, from the point of
+/// view of its own camera. The texture can be attached to materials like any other texture, which
+/// are then skipped while rendering into it.
+#[derive(Debug)]
+pub struct RenderTarget {
+    id: RenderTargetId,
+    color_texture: CompleteTexture,
+    depth_texture: CompleteTexture,
+    camera: Option<(Buffer, BindGroup)>,
+    clear_color: wgpu::Color,
+}
+
+impl RenderTarget {
+    /// Render targets are rendered with pipelines built for this format
+    pub const FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+    /// Render targets are never multisampled and have no G-buffer
+    pub fn pipeline_target() -> PipelineTarget {
+        PipelineTarget::new(Self::FORMAT)
+    }
+
+    /// `depth_format` must match the engine's, see `RenderingManager::depth_format`
+    pub fn new(device: &Device, width: u32, height: u32, depth_format: TextureFormat) -> Self {
+        let color_texture = TextureBundle::create_texture(
+            device,
+            width,
+            height,
+            TextureProperties {
+                format: Self::FORMAT,
+                extra_usages: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                ..Default::default()
+            },
+        );
+
+        let depth_texture =
+            TextureBundle::create_sized_depth_texture(device, width, height, depth_format);
+
+        Self {
+            id: NEXT_RENDER_TARGET_ID.fetch_add(1, Ordering::Relaxed),
+            color_texture,
+            depth_texture,
+            camera: None,
+            clear_color: wgpu::Color::BLACK,
+        }
+    }
+
+    pub fn with_clear_color(mut self, clear_color: wgpu::Color) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    pub fn id(&self) -> RenderTargetId {
+        self.id
+    }
+
+    /// The rendered image, for use as a material attachment
+    pub fn texture_bundle(&self) -> &TextureBundle {
+        &self.color_texture.1
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.color_texture.0
+    }
+
+    pub fn depth_texture(&self) -> &TextureBundle {
+        &self.depth_texture.1
+    }
+
+    /// `None` until camera data has been written, in which case the target is not rendered
+    pub fn camera_bind_group(&self) -> Option<&BindGroup> {
+        self.camera.as_ref().map(|(_, bind_group)| bind_group)
+    }
+
+    pub fn update_camera(&mut self, device: &Device, queue: &Queue, camera_data: &[u8]) {
+        if let Some((camera_buffer, _)) = &self.camera {
+            queue.write_buffer(camera_buffer, 0, camera_data);
+            return;
+        }
+
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("Render target {} camera buffer", self.id)),
+            size: camera_data.len() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&camera_buffer, 0, camera_data);
+
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some(&format!(
+                    "Render target {} camera bind group layout",
+                    self.id
+                )),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("Render target {} camera bind group", self.id)),
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        });
+
+        self.camera = Some((camera_buffer, camera_bind_group));
+    }
+
+    pub fn clear_color(&self) -> wgpu::Color {
+        self.clear_color
+    }
+
+    pub fn set_clear_color(&mut self, clear_color: wgpu::Color) {
+        self.clear_color = clear_color;
+    }
+}
@@ -3,19 +3,25 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::Debug,
     future::Future,
+    marker::PhantomData,
     ops::Range,
+    path::Path,
     pin::Pin,
+    time::Duration,
 };
 
 use crossbeam_channel::{Receiver, Sender};
-use wgpu::{BindGroup, Buffer, Device, Queue};
-use winit_input_helper::WinitInputHelper;
+use tokio::sync::oneshot;
+use wgpu::{BindGroup, Buffer, Device, LoadOp, Queue, RenderPipeline, TextureFormat};
 
 use crate::{
     EngineDetails,
     engine_management::{
-        engine_action::EngineAction,
-        pipeline::{PipelineId, PipelineShader},
+        engine_action::{EngineAction, RemoveTextBufferEngineAction},
+        pipeline::{PipelineId, PipelineShader, PipelineTarget},
+    },
+    engine_support::{
+        file_watcher::FileWatcher, gamepad_state::GamepadState, input_state::InputState,
     },
 };
 
@@ -23,8 +29,15 @@ use super::{
     actions::ActionQueue,
     component::{Component, ComponentDetails, ComponentId, ComponentSystem},
     compute::Compute,
-    entity::{Entity, EntityId},
+    dense_storage::{DenseComponent, DenseStorage},
+    entity::{Entity, EntityAllocator, EntityId},
     material::{Material, ShaderAttachment},
+    render_target::{RenderTarget, RenderTargetId},
+    scene_descriptor::{
+        ComponentRegistry, EntityDescriptor, MaterialDescriptor, SceneDescriptor,
+        SceneDescriptorError,
+    },
+    ui_hit_testing::{self, UiHitState, UiRect},
 };
 
 static mut SCENE_COUNT: usize = 0;
@@ -33,21 +46,42 @@ pub struct Scene {
     scene_index: usize,
     components: Vec<Component>,
     entities: HashMap<EntityId, Entity>,
+    entity_names: HashMap<String, EntityId>,
     entity_component_groupings: HashMap<EntityId, Range<usize>>,
     ui_components: Vec<ComponentId>,
+    ui_bounds: HashMap<ComponentId, UiRect>,
+    ui_hit_states: HashMap<ComponentId, UiHitState>,
     materials: Vec<Material>,
     screen_space_materials: Vec<ComponentId>,
     pipeline_to_corresponding_materials: HashMap<PipelineId, Vec<ComponentId>>,
-    total_entities_created: EntityId,
+    entity_allocator: EntityAllocator,
     workload_sender: Option<Sender<WorkloadPacket>>,
-    workload_output_receiver: Option<Receiver<(ComponentId, WorkloadOutput)>>,
+    workload_output_receiver: Option<Receiver<WorkloadOutputPacket>>,
     workload_outputs: WorkloadOutputCollection,
+    sequenced_workload_outputs: SequencedWorkloadOutputCollection,
+    interval_workload_outputs: SequencedWorkloadOutputCollection,
+    /// The next launch count of each component's interval workloads
+    interval_launches: HashMap<ComponentId, u64>,
+    workload_progress_sender: Sender<(ComponentId, f32)>,
+    workload_progress_receiver: Receiver<(ComponentId, f32)>,
+    workload_progress: HashMap<ComponentId, f32>,
+    interval_workloads: Vec<IntervalWorkload>,
+    /// The sum of the frame times this scene was updated with, which drives interval workloads
+    interval_clock: Duration,
     engine_action_sender: Option<Sender<Box<dyn EngineAction>>>,
     pub new_pipelines_needed: bool,
     active_camera: Option<ComponentId>,
+    depth_load_op: LoadOp<f32>,
+    background: Option<Background>,
+    viewport: Option<[f32; 4]>,
+    debug_lines: Vec<DebugLineVertex>,
+    exit_requested: bool,
+    render_targets: Vec<RenderTarget>,
     active_camera_buffer: Option<Buffer>,
     active_camera_bind_group: Option<BindGroup>,
     computes: Vec<Compute>,
+    dense_storage: DenseStorage,
+    ron_source: Option<RonSceneSource>,
 }
 
 impl Debug for Scene {
@@ -60,12 +94,196 @@ impl Debug for Scene {
 
 pub type WorkloadOutput = Box<dyn Any + Send + Sync>;
 pub type WorkloadOutputCollection = HashMap<ComponentId, Vec<WorkloadOutput>>;
+pub type SequencedWorkloadOutputCollection = HashMap<ComponentId, HashMap<u64, WorkloadOutput>>;
 pub type Workload = Pin<Box<dyn Future<Output = WorkloadOutput> + Send>>;
 
+/// The output of a workload that did not finish within its timeout, found with
+/// `output.is::<WorkloadTimeout>()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkloadTimeout {
+    pub timeout: Duration,
+}
+
+/// Where a workload's output is stored once it arrives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorkloadOutputKey {
+    /// Appended to the component's `UpdateParams::workload_outputs`
+    Unsequenced,
+    /// Stored in `UpdateParams::sequenced_workload_outputs` under the given sequence
+    Sequenced(u64),
+    /// Stored in `UpdateParams::interval_workload_outputs` under the launch count
+    Interval(u64),
+}
+
 pub struct WorkloadPacket {
     pub scene_index: usize,
     pub component_id: ComponentId,
+    pub output_key: WorkloadOutputKey,
     pub workload: Workload,
+    /// The executor drops the workload when a value is sent through this
+    pub cancel_receiver: Option<oneshot::Receiver<()>>,
+    /// The executor drops the workload after this long and outputs a `WorkloadTimeout` instead
+    pub timeout: Option<Duration>,
+}
+
+pub struct WorkloadOutputPacket {
+    pub component_id: ComponentId,
+    pub output_key: WorkloadOutputKey,
+    pub output: WorkloadOutput,
+}
+
+pub type WorkloadFactory = Box<dyn Fn() -> Workload + Send + Sync>;
+
+/// A workload paired with a `WorkloadHandle`, attached with `Scene::attach_handled_workload`
+pub struct CancellableWorkload {
+    component_id: ComponentId,
+    sequence: u64,
+    workload: Workload,
+    cancel_receiver: oneshot::Receiver<()>,
+}
+
+impl Debug for CancellableWorkload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellableWorkload")
+            .field("component_id", &self.component_id)
+            .field("sequence", &self.sequence)
+            .field("workload", &"Future")
+            .finish()
+    }
+}
+
+/// Cancels a workload and reads its output, which is stored in the component's sequenced outputs
+/// under the handle's sequence. Dropping the handle without cancelling lets the workload finish.
+#[derive(Debug)]
+pub struct WorkloadHandle<T> {
+    component_id: ComponentId,
+    sequence: u64,
+    cancel_sender: Option<oneshot::Sender<()>>,
+    _output: PhantomData<fn() -> T>,
+}
+
+impl<T: Any + Send + Sync> WorkloadHandle<T> {
+    /// Wraps `workload` so its output is stored under `sequence`. Components can keep the handle
+    /// and attach the workload with `CancellableWorkloadAction`.
+    pub fn new(
+        component_id: ComponentId,
+        sequence: u64,
+        workload: impl Future<Output = T> + Send + 'static,
+    ) -> (Self, CancellableWorkload) {
+        let (cancel_sender, cancel_receiver) = oneshot::channel();
+        (
+            WorkloadHandle {
+                component_id,
+                sequence,
+                cancel_sender: Some(cancel_sender),
+                _output: PhantomData,
+            },
+            CancellableWorkload {
+                component_id,
+                sequence,
+                workload: Box::pin(async move { Box::new(workload.await) as WorkloadOutput }),
+                cancel_receiver,
+            },
+        )
+    }
+
+    pub fn component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Drops the workload if it is still running, so its output never arrives. An output that
+    /// already arrived is kept until it is freed.
+    pub fn cancel(&mut self) {
+        if let Some(cancel_sender) = self.cancel_sender.take() {
+            let _ = cancel_sender.send(());
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_sender.is_none()
+    }
+
+    /// The workload's output once it arrived, e.g. from `UpdateParams::sequenced_workload_outputs`.
+    /// Panics if the output is not a `T`.
+    pub fn output<'a>(
+        &self,
+        sequenced_workload_outputs: &'a SequencedWorkloadOutputCollection,
+    ) -> Option<&'a T> {
+        let output = sequenced_workload_outputs
+            .get(&self.component_id)?
+            .get(&self.sequence)?;
+        match output.downcast_ref() {
+            Some(output) => Some(output),
+            None => self.mismatched_output(),
+        }
+    }
+
+    fn mismatched_output(&self) -> ! {
+        panic!(
+            "The output of workload {} of component {} is not a {}",
+            self.sequence,
+            self.component_id,
+            std::any::type_name::<T>()
+        )
+    }
+}
+
+struct IntervalWorkload {
+    component_id: ComponentId,
+    interval: Duration,
+    /// On the scene's `interval_clock`
+    next_launch: Duration,
+    factory: WorkloadFactory,
+}
+
+/// Lets a running workload report its progress, which the scene exposes to components as
+/// `UpdateParams::workload_progress`
+#[derive(Debug, Clone)]
+pub struct WorkloadProgressSender {
+    component_id: ComponentId,
+    sender: Sender<(ComponentId, f32)>,
+}
+
+impl WorkloadProgressSender {
+    pub fn component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    pub fn send(&self, progress: f32) {
+        let _ = self.sender.send((self.component_id, progress));
+    }
+}
+
+pub type MaterialSource =
+    Box<dyn FnMut(&MaterialDescriptor) -> (PipelineId, Vec<ShaderAttachment>) + Send + Sync>;
+
+/// The file a scene was loaded from with `Scene::reload_from_ron`
+struct RonSceneSource {
+    watcher: FileWatcher,
+    registry: ComponentRegistry,
+    material_source: MaterialSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Background {
+    SolidColor(wgpu::Color),
+    /// Vertical gradient across the screen
+    Gradient {
+        top: wgpu::Color,
+        bottom: wgpu::Color,
+    },
+}
+
+/// A line list vertex in world space, drawn by the rendering manager while debug drawing is on
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DebugLineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
 }
 
 impl Default for Scene {
@@ -75,94 +293,198 @@ impl Default for Scene {
             SCENE_COUNT
         };
 
+        let (workload_progress_sender, workload_progress_receiver) = crossbeam_channel::unbounded();
+
         Scene {
             scene_index,
             components: Vec::new(),
             entities: HashMap::new(),
+            entity_names: HashMap::new(),
             entity_component_groupings: HashMap::new(),
             ui_components: Vec::new(),
+            ui_bounds: HashMap::new(),
+            ui_hit_states: HashMap::new(),
             materials: Vec::new(),
             screen_space_materials: Vec::new(),
             pipeline_to_corresponding_materials: HashMap::new(),
-            total_entities_created: 0,
+            entity_allocator: EntityAllocator::default(),
             workload_sender: None,
             workload_output_receiver: None,
             engine_action_sender: None,
             workload_outputs: HashMap::new(),
+            sequenced_workload_outputs: HashMap::new(),
+            interval_workload_outputs: HashMap::new(),
+            interval_launches: HashMap::new(),
+            workload_progress_sender,
+            workload_progress_receiver,
+            workload_progress: HashMap::new(),
+            interval_workloads: Vec::new(),
+            interval_clock: Duration::ZERO,
             new_pipelines_needed: false,
             active_camera: None,
+            depth_load_op: LoadOp::Clear(1.0),
+            background: None,
+            viewport: None,
+            debug_lines: Vec::new(),
+            exit_requested: false,
+            render_targets: Vec::new(),
             active_camera_buffer: None,
             active_camera_bind_group: None,
             computes: Vec::new(),
+            dense_storage: DenseStorage::default(),
+            ron_source: None,
         }
     }
 }
 
 impl Scene {
-    pub fn initialize(
+    pub async fn initialize(
         &mut self,
         device: &Device,
+        queue: &Queue,
         workload_sender: Sender<WorkloadPacket>,
-        workload_output_receiver: Receiver<(ComponentId, WorkloadOutput)>,
+        workload_output_receiver: Receiver<WorkloadOutputPacket>,
         engine_action_sender: Sender<Box<dyn EngineAction>>,
     ) -> ActionQueue {
         self.workload_sender = Some(workload_sender);
         self.workload_output_receiver = Some(workload_output_receiver);
         self.engine_action_sender = Some(engine_action_sender);
 
-        self.initialize_components(device)
+        self.initialize_components(device, queue).await
     }
 
-    fn initialize_components(&mut self, device: &Device) -> ActionQueue {
-        let comp_action_queue: ActionQueue = self
+    async fn initialize_components(&mut self, device: &Device, queue: &Queue) -> ActionQueue {
+        let mut action_queue: ActionQueue = Vec::new();
+
+        for comp in self
             .components
             .iter_mut()
             .filter(|comp| !comp.is_initialized())
-            .flat_map(|comp| comp.initialize(device))
-            .collect();
+        {
+            action_queue.extend(comp.initialize_async(device, queue).await);
+        }
 
-        let mat_action_queue: ActionQueue = self
+        for mat in self
             .materials
             .iter_mut()
             .filter(|mat| !mat.is_initialized())
-            .flat_map(|mat| mat.initialize(device))
-            .collect();
+        {
+            action_queue.extend(mat.initialize_async(device, queue).await);
+        }
 
-        let compute_action_queue: ActionQueue = self
+        for compute in self
             .computes
             .iter_mut()
             .filter(|compute| !compute.is_initialized())
-            .flat_map(|compute| compute.initialize(device))
-            .collect();
+        {
+            action_queue.extend(compute.initialize_async(device, queue).await);
+        }
 
-        comp_action_queue
-            .into_iter()
-            .chain(mat_action_queue.into_iter())
-            .chain(compute_action_queue.into_iter())
-            .collect()
+        action_queue
     }
 
     pub fn update(
         &mut self,
         device: &Device,
         queue: &Queue,
-        input_manager: &WinitInputHelper,
+        input_manager: &dyn InputState,
+        gamepads: &GamepadState,
         engine_details: &EngineDetails,
     ) -> ActionQueue {
-        while let Ok((component_id, workload_output)) = self
+        self.debug_lines.clear();
+
+        let mut finished_components = Vec::new();
+        while let Ok(WorkloadOutputPacket {
+            component_id,
+            output_key,
+            output: workload_output,
+        }) = self
             .workload_output_receiver
             .as_ref()
             .expect("Failed to initialize workload output receiver.")
             .try_recv()
         {
-            if let Some(outputs) = self.workload_outputs.get_mut(&component_id) {
-                outputs.push(workload_output);
-            } else {
-                self.workload_outputs
-                    .insert(component_id, vec![workload_output]);
+            match output_key {
+                WorkloadOutputKey::Unsequenced => {
+                    finished_components.push(component_id);
+                    if let Some(outputs) = self.workload_outputs.get_mut(&component_id) {
+                        outputs.push(workload_output);
+                    } else {
+                        self.workload_outputs
+                            .insert(component_id, vec![workload_output]);
+                    }
+                }
+                WorkloadOutputKey::Sequenced(sequence) => {
+                    self.sequenced_workload_outputs
+                        .entry(component_id)
+                        .or_default()
+                        .insert(sequence, workload_output);
+                }
+                WorkloadOutputKey::Interval(launch) => {
+                    self.interval_workload_outputs
+                        .entry(component_id)
+                        .or_default()
+                        .insert(launch, workload_output);
+                }
             }
         }
 
+        // Progress is sent before the output, so a finished workload's last report is drained
+        // here and removed along with its entry
+        while let Ok((component_id, progress)) = self.workload_progress_receiver.try_recv() {
+            self.workload_progress.insert(component_id, progress);
+        }
+        for component_id in finished_components {
+            self.workload_progress.remove(&component_id);
+        }
+
+        self.interval_clock += engine_details.delta_time;
+        self.launch_interval_workloads(self.interval_clock);
+
+        self.dense_storage.update(device, queue, engine_details);
+
+        ui_hit_testing::update_hit_states(&self.ui_bounds, &mut self.ui_hit_states, input_manager);
+
+        self.update_enabled_components(
+            device,
+            queue,
+            input_manager,
+            gamepads,
+            engine_details,
+            |component, params| component.update(params),
+        )
+    }
+
+    /// Runs `ComponentSystem::fixed_update` on every enabled component. Called by the engine zero
+    /// or more times per frame, before `update`, when a fixed timestep is set on the builder.
+    pub fn fixed_update(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        input_manager: &dyn InputState,
+        gamepads: &GamepadState,
+        engine_details: &EngineDetails,
+        fixed_delta: Duration,
+    ) -> ActionQueue {
+        self.update_enabled_components(
+            device,
+            queue,
+            input_manager,
+            gamepads,
+            engine_details,
+            |component, params| component.fixed_update(params, fixed_delta),
+        )
+    }
+
+    fn update_enabled_components(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        input_manager: &dyn InputState,
+        gamepads: &GamepadState,
+        engine_details: &EngineDetails,
+        mut update: impl FnMut(&mut Component, super::component::UpdateParams) -> ActionQueue,
+    ) -> ActionQueue {
         let active_camera = self.active_camera();
         let entities = &self.entities;
 
@@ -197,29 +519,45 @@ impl Scene {
                     }
                 }
                 let workload_outputs = &self.workload_outputs;
+                let sequenced_workload_outputs = &self.sequenced_workload_outputs;
+                let interval_workload_outputs = &self.interval_workload_outputs;
+                let workload_progress = &self.workload_progress;
+                let ui_hit_states = &self.ui_hit_states;
+                let dense_storage = &self.dense_storage;
 
-                current_component.update(super::component::UpdateParams {
+                let params = super::component::UpdateParams {
                     device,
                     queue,
                     input_manager,
+                    gamepads,
                     other_components: &mut other_components,
                     computes: &mut self.computes,
                     materials: &mut all_materials,
                     engine_details,
                     workload_outputs,
+                    sequenced_workload_outputs,
+                    interval_workload_outputs,
+                    workload_progress,
+                    ui_hit_states,
                     entities,
                     entity_component_groupings,
                     active_camera,
-                })
+                    dense_storage,
+                };
+                update(current_component, params)
             })
             .collect()
     }
 
+    /// Rebuilds each material's draw order from the current entity groupings and runs its
+    /// updater. Must run after the frame's component updates and actions, so entities created or
+    /// removed this frame are drawn, and before rendering.
     pub fn update_materials(
         &mut self,
         device: &Device,
         queue: &Queue,
-        input_manager: &WinitInputHelper,
+        input_manager: &dyn InputState,
+        gamepads: &GamepadState,
         engine_details: &EngineDetails,
     ) {
         let active_camera = self.active_camera();
@@ -234,6 +572,11 @@ impl Scene {
         let all_materials: &mut Vec<Material> = &mut self.materials;
 
         let workload_outputs = &self.workload_outputs;
+        let sequenced_workload_outputs = &self.sequenced_workload_outputs;
+        let interval_workload_outputs = &self.interval_workload_outputs;
+        let workload_progress = &self.workload_progress;
+        let ui_hit_states = &self.ui_hit_states;
+        let dense_storage = &self.dense_storage;
 
         for i in 0..all_materials.len() {
             let (previous_materials, all_other_materials) = all_materials.split_at_mut(i);
@@ -253,30 +596,228 @@ impl Scene {
                 device,
                 queue,
                 input_manager,
+                gamepads,
                 other_components: &mut all_components,
                 computes: &mut self.computes,
                 materials: &mut other_materials,
                 engine_details,
                 workload_outputs,
+                sequenced_workload_outputs,
+                interval_workload_outputs,
+                workload_progress,
+                ui_hit_states,
                 entities,
                 entity_component_groupings,
                 active_camera,
+                dense_storage,
             });
         }
     }
 
     pub async fn attach_workload(&mut self, component_id: ComponentId, workload: Workload) {
+        self.send_workload(component_id, WorkloadOutputKey::Unsequenced, workload);
+    }
+
+    /// If the workload does not finish within `timeout` it is dropped, and a `WorkloadTimeout`
+    /// is added to the component's outputs instead of its output
+    pub async fn attach_workload_with_timeout(
+        &mut self,
+        component_id: ComponentId,
+        workload: Workload,
+        timeout: Duration,
+    ) {
+        self.send_workload_packet(WorkloadPacket {
+            scene_index: self.scene_index,
+            component_id,
+            output_key: WorkloadOutputKey::Unsequenced,
+            workload,
+            cancel_receiver: None,
+            timeout: Some(timeout),
+        });
+    }
+
+    /// The output is stored under `sequence` instead of being appended to the component's
+    /// outputs, so it can be matched to this workload regardless of completion order
+    pub async fn attach_sequenced_workload(
+        &mut self,
+        component_id: ComponentId,
+        sequence: u64,
+        workload: Workload,
+    ) {
+        self.send_workload(
+            component_id,
+            WorkloadOutputKey::Sequenced(sequence),
+            workload,
+        );
+    }
+
+    fn send_workload(
+        &self,
+        component_id: ComponentId,
+        output_key: WorkloadOutputKey,
+        workload: Workload,
+    ) {
+        self.send_workload_packet(WorkloadPacket {
+            scene_index: self.scene_index,
+            component_id,
+            output_key,
+            workload,
+            cancel_receiver: None,
+            timeout: None,
+        });
+    }
+
+    fn send_workload_packet(&self, workload_packet: WorkloadPacket) {
         if let Some(sender) = &self.workload_sender {
             sender
-                .try_send(WorkloadPacket {
-                    scene_index: self.scene_index,
-                    component_id,
-                    workload,
-                })
+                .try_send(workload_packet)
                 .expect("Failed to send workload");
         }
     }
 
+    /// Attaches `workload` as a sequenced workload that can be cancelled through the returned
+    /// handle, e.g. when the component is disabled before the workload finishes
+    pub fn attach_cancellable_workload<T: Any + Send + Sync>(
+        &mut self,
+        component_id: ComponentId,
+        sequence: u64,
+        workload: impl Future<Output = T> + Send + 'static,
+    ) -> WorkloadHandle<T> {
+        let (handle, workload) = WorkloadHandle::new(component_id, sequence, workload);
+        self.attach_handled_workload(workload);
+        handle
+    }
+
+    /// Attaches a workload created alongside its handle by `WorkloadHandle::new`
+    pub fn attach_handled_workload(&mut self, workload: CancellableWorkload) {
+        self.send_workload_packet(WorkloadPacket {
+            scene_index: self.scene_index,
+            component_id: workload.component_id,
+            output_key: WorkloadOutputKey::Sequenced(workload.sequence),
+            workload: workload.workload,
+            cancel_receiver: Some(workload.cancel_receiver),
+            timeout: None,
+        });
+    }
+
+    /// Launches a workload from `factory` every `interval` of frame time until cancelled with
+    /// `Scene::cancel_interval_workloads`, so simulated frames from `V4::step_frame` drive it too.
+    /// The outputs are found in `UpdateParams::interval_workload_outputs` under the launch count,
+    /// starting from zero. Launch counts are shared by the component's interval workloads.
+    pub fn attach_interval_workload(
+        &mut self,
+        component_id: ComponentId,
+        interval: Duration,
+        factory: impl Fn() -> Workload + Send + Sync + 'static,
+    ) {
+        self.interval_workloads.push(IntervalWorkload {
+            component_id,
+            interval,
+            next_launch: self.interval_clock + interval,
+            factory: Box::new(factory),
+        });
+    }
+
+    pub fn cancel_interval_workloads(&mut self, component_id: ComponentId) {
+        self.interval_workloads
+            .retain(|interval_workload| interval_workload.component_id != component_id);
+    }
+
+    /// A launch that is more than one interval late is not repeated to catch up
+    fn launch_interval_workloads(&mut self, now: Duration) {
+        let mut due_workloads = Vec::new();
+        for interval_workload in &mut self.interval_workloads {
+            if interval_workload.next_launch > now {
+                continue;
+            }
+            let launch = self
+                .interval_launches
+                .entry(interval_workload.component_id)
+                .or_default();
+            due_workloads.push((
+                interval_workload.component_id,
+                *launch,
+                (interval_workload.factory)(),
+            ));
+            *launch += 1;
+            interval_workload.next_launch += interval_workload.interval;
+            if interval_workload.next_launch <= now {
+                interval_workload.next_launch = now + interval_workload.interval;
+            }
+        }
+
+        for (component_id, launch, workload) in due_workloads {
+            self.send_workload(component_id, WorkloadOutputKey::Interval(launch), workload);
+        }
+    }
+
+    pub fn workload_progress_sender(&self, component_id: ComponentId) -> WorkloadProgressSender {
+        WorkloadProgressSender {
+            component_id,
+            sender: self.workload_progress_sender.clone(),
+        }
+    }
+
+    /// `workload` should report its progress through a clone of `progress_sender`, obtained from
+    /// `Scene::workload_progress_sender`. The component's progress is reset to zero, and removed
+    /// once the output of one of its workloads arrives.
+    pub async fn attach_workload_with_progress(
+        &mut self,
+        component_id: ComponentId,
+        workload: Workload,
+        progress_sender: WorkloadProgressSender,
+    ) {
+        assert_eq!(
+            progress_sender.component_id, component_id,
+            "The progress sender belongs to a different component."
+        );
+        self.workload_progress.insert(component_id, 0.0);
+        self.attach_workload(component_id, workload).await;
+    }
+
+    pub fn workload_progress(&self, component_id: ComponentId) -> Option<f32> {
+        self.workload_progress.get(&component_id).copied()
+    }
+
+    pub fn take_output_for(
+        &mut self,
+        component_id: ComponentId,
+        sequence: u64,
+    ) -> Option<WorkloadOutput> {
+        let outputs = self.sequenced_workload_outputs.get_mut(&component_id)?;
+        let output = outputs.remove(&sequence);
+        if outputs.is_empty() {
+            self.sequenced_workload_outputs.remove(&component_id);
+        }
+        output
+    }
+
+    pub fn take_interval_output_for(
+        &mut self,
+        component_id: ComponentId,
+        launch: u64,
+    ) -> Option<WorkloadOutput> {
+        let outputs = self.interval_workload_outputs.get_mut(&component_id)?;
+        let output = outputs.remove(&launch);
+        if outputs.is_empty() {
+            self.interval_workload_outputs.remove(&component_id);
+        }
+        output
+    }
+
+    /// Takes the output of the handle's workload once it arrived. Panics if the output is not a
+    /// `T`.
+    pub fn take_handle_output<T: Any + Send + Sync>(
+        &mut self,
+        handle: &WorkloadHandle<T>,
+    ) -> Option<T> {
+        let output = self.take_output_for(handle.component_id, handle.sequence)?;
+        match output.downcast() {
+            Ok(output) => Some(*output),
+            Err(_) => handle.mismatched_output(),
+        }
+    }
+
     pub async fn free_workload_output(
         &mut self,
         component_id: ComponentId,
@@ -332,6 +873,7 @@ fn main(input: VertexInput) -> VertexOutput {
                 .to_string(),
             ));
             pipeline_id.spirv_vertex_shader = false;
+            pipeline_id.vertex_entry = "main";
             self.screen_space_materials.push(id);
         }
 
@@ -407,26 +949,33 @@ fn main(input: VertexInput) -> VertexOutput {
     pub fn create_entity(
         &mut self,
         parent: Option<EntityId>,
-        mut components: Vec<Component>,
+        components: Vec<Component>,
         computes: Vec<Compute>,
         material: Option<ComponentId>,
         is_enabled: bool,
     ) -> EntityId {
-        let entity = Entity::new(
-            self.total_entities_created + 1,
-            Vec::new(),
-            parent.unwrap_or(0),
-            is_enabled,
-            material,
-        );
-        let id = entity.id();
+        let id = self.entity_allocator.allocate();
+        self.insert_entity(id, parent, components, computes, material, is_enabled);
+        id
+    }
+
+    /// Adds an entity under an id that was already allocated
+    fn insert_entity(
+        &mut self,
+        id: EntityId,
+        parent: Option<EntityId>,
+        mut components: Vec<Component>,
+        computes: Vec<Compute>,
+        material: Option<ComponentId>,
+        is_enabled: bool,
+    ) {
+        let entity = Entity::new(id, Vec::new(), parent.unwrap_or(0), is_enabled, material);
 
         if let Some(parent) = parent {
             self.entities.get_mut(&parent).unwrap().push_child(id);
         }
 
         self.entities.insert(id, entity);
-        self.total_entities_created += 1;
 
         components
             .iter_mut()
@@ -447,9 +996,78 @@ fn main(input: VertexInput) -> VertexOutput {
         }
 
         self.components.append(&mut components);
-        self.computes.extend(computes);
+        for mut compute in computes {
+            compute.set_parent_entity(id);
+            self.computes.push(compute);
+        }
+    }
 
-        id
+    /// Removes the entity along with its components, computes and children. The groupings after
+    /// its components are shifted down, and its id stops being alive. Materials are kept, as they
+    /// can be shared and reused by entities created later. Returns false for unknown entities.
+    pub fn remove_entity(&mut self, entity_id: EntityId) -> bool {
+        let Some(entity) = self.entities.remove(&entity_id) else {
+            return false;
+        };
+
+        for child in entity.children_ids() {
+            self.remove_entity(*child);
+        }
+        if let Some(parent) = self.entities.get_mut(&entity.parent_entity_id()) {
+            parent.remove_child(entity_id);
+        }
+
+        if let Some(range) = self.entity_component_groupings.remove(&entity_id) {
+            let removed_components: Vec<Component> = self.components.drain(range.clone()).collect();
+            for grouping in self.entity_component_groupings.values_mut() {
+                if grouping.start >= range.end {
+                    grouping.start -= range.len();
+                    grouping.end -= range.len();
+                }
+            }
+            for component in removed_components {
+                self.forget_component(component.id());
+            }
+        }
+
+        if let Some(material_id) = entity.active_material()
+            && let Some(material) = self.get_material_mut(material_id)
+        {
+            material.detach_entity(entity_id);
+        }
+        self.computes
+            .retain(|compute| compute.parent_entity_id() != entity_id);
+        self.dense_storage.remove_entity(entity_id);
+        self.entity_names
+            .retain(|_, named_entity| *named_entity != entity_id);
+        self.entity_allocator.free(entity_id);
+        self.invalidate_render_bundles();
+
+        true
+    }
+
+    /// Drops the GPU resources of every component, material and compute, along with the camera
+    /// buffer, while keeping the entities and their data. Meant for scenes that are not active,
+    /// they are initialized again the next time they are made active. Pipelines are shared between
+    /// scenes and stay with the engine, render targets are kept as they can't be recreated.
+    pub fn release_gpu_resources(&mut self) {
+        for component in &mut self.components {
+            component.release_gpu_resources();
+        }
+        for material in &mut self.materials {
+            material.release_gpu_resources();
+        }
+        for compute in &mut self.computes {
+            compute.release_gpu_resources();
+        }
+        self.dense_storage.release_gpu_resources();
+        self.active_camera_buffer = None;
+        self.active_camera_bind_group = None;
+    }
+
+    /// False once the entity has been removed, even if its slot now holds a newer entity
+    pub fn is_entity_alive(&self, entity_id: EntityId) -> bool {
+        self.entity_allocator.is_alive(entity_id)
     }
 
     pub fn get_entity(&self, entity_id: EntityId) -> Option<&Entity> {
@@ -460,6 +1078,52 @@ fn main(input: VertexInput) -> VertexOutput {
         self.entities.get_mut(&entity_id)
     }
 
+    /// Names are unique, naming another entity the same moves the name to it. Named entities are
+    /// matched by name when the scene is reloaded with `Scene::reload_from_ron`.
+    pub fn set_entity_name(&mut self, entity_id: EntityId, name: impl Into<String>) {
+        self.entity_names
+            .retain(|_, named_entity| *named_entity != entity_id);
+        self.entity_names.insert(name.into(), entity_id);
+    }
+
+    pub fn entity_by_name(&self, name: &str) -> Option<EntityId> {
+        self.entity_names.get(name).copied()
+    }
+
+    pub fn entity_name(&self, entity_id: EntityId) -> Option<&str> {
+        self.entity_names
+            .iter()
+            .find(|(_, named_entity)| **named_entity == entity_id)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Every entity with its components, in the order the entities were created
+    pub fn iter_entities(&self) -> impl Iterator<Item = (EntityId, &Entity, &[Component])> {
+        let depth = |mut entity_id: EntityId| {
+            let mut depth = 0;
+            while let Some(entity) = self.entities.get(&entity_id) {
+                entity_id = entity.parent_entity_id();
+                depth += 1;
+            }
+            depth
+        };
+        let mut groupings: Vec<(&EntityId, &Range<usize>)> =
+            self.entity_component_groupings.iter().collect();
+        // Entities without components share their start with the next entity, the depth keeps
+        // parents before their children among those
+        groupings.sort_by_key(|(entity_id, range)| {
+            (range.start, depth(**entity_id), range.end, **entity_id)
+        });
+
+        groupings.into_iter().filter_map(|(entity_id, range)| {
+            Some((
+                *entity_id,
+                self.entities.get(entity_id)?,
+                &self.components[range.clone()],
+            ))
+        })
+    }
+
     pub fn get_component(&self, component_id: ComponentId) -> Option<&Component> {
         self.components
             .iter()
@@ -472,8 +1136,128 @@ fn main(input: VertexInput) -> VertexOutput {
             .find(|comp| comp.id() == component_id)
     }
 
+    /// Removes the component from its entity, shifting the groupings of the components after it
+    /// the same way `Scene::update` does for a component's siblings
+    pub fn remove_component(&mut self, component_id: ComponentId) -> Option<Component> {
+        let index = self
+            .components
+            .iter()
+            .position(|comp| comp.id() == component_id)?;
+        let component = self.components.remove(index);
+
+        for grouping in self.entity_component_groupings.values_mut() {
+            if grouping.start > index {
+                grouping.start -= 1;
+            }
+            if grouping.end > index {
+                grouping.end -= 1;
+            }
+        }
+
+        self.forget_component(component_id);
+        self.invalidate_render_bundles();
+
+        Some(component)
+    }
+
+    /// Drops the scene's references to a removed component, along with its interval workloads
+    /// and workload outputs
+    fn forget_component(&mut self, component_id: ComponentId) {
+        self.cancel_interval_workloads(component_id);
+        self.interval_launches.remove(&component_id);
+        self.workload_outputs.remove(&component_id);
+        self.sequenced_workload_outputs.remove(&component_id);
+        self.interval_workload_outputs.remove(&component_id);
+        self.workload_progress.remove(&component_id);
+        if self.ui_components.contains(&component_id) {
+            self.unregister_ui_component(component_id);
+        }
+        self.ui_bounds.remove(&component_id);
+        self.ui_hit_states.remove(&component_id);
+        if self.active_camera == Some(component_id) {
+            self.active_camera = None;
+        }
+    }
+
+    pub fn set_component_rendering_order(
+        &mut self,
+        component_id: ComponentId,
+        rendering_order: i32,
+    ) {
+        let Some(component) = self.get_component_mut(component_id) else {
+            return;
+        };
+        if component.rendering_order() == rendering_order {
+            return;
+        }
+        component.set_rendering_order(rendering_order);
+        let parent_entity_id = component.parent_entity_id();
+
+        if let Some(range) = self.entity_component_groupings.get(&parent_entity_id) {
+            self.components[range.clone()].sort_by_key(|comp| comp.rendering_order());
+        }
+    }
+
+    /// Stores the component contiguously alongside others of its type instead of in the boxed
+    /// component list. It is updated in a typed pass over its column before the boxed components.
+    pub fn attach_dense_component<T: DenseComponent>(&mut self, entity_id: EntityId, component: T) {
+        self.dense_storage.insert(entity_id, component);
+        // Bundles drawing the entity were recorded without the new component
+        for material in &mut self.materials {
+            if material.attached_entities().contains(&entity_id) {
+                material.invalidate_render_bundle();
+            }
+        }
+    }
+
+    pub fn dense_storage(&self) -> &DenseStorage {
+        &self.dense_storage
+    }
+
+    pub fn dense_storage_mut(&mut self) -> &mut DenseStorage {
+        &mut self.dense_storage
+    }
+
     pub fn get_material(&self, material_id: ComponentId) -> Option<&Material> {
-        self.materials.get(material_id as usize)
+        self.materials.iter().find(|mat| mat.id() == material_id)
+    }
+
+    pub fn get_material_mut(&mut self, material_id: ComponentId) -> Option<&mut Material> {
+        self.materials
+            .iter_mut()
+            .find(|mat| mat.id() == material_id)
+    }
+
+    /// Moves the entity onto the given material. Unknown entity or material ids are reported and
+    /// ignored, leaving the entity on its current material. Returns whether the change was applied
+    pub fn set_entity_active_material(
+        &mut self,
+        entity_id: EntityId,
+        material_id: ComponentId,
+    ) -> bool {
+        if self.get_material(material_id).is_none() {
+            log::warn!(
+                "Ignoring active material change for entity {entity_id}: material {material_id} does not exist"
+            );
+            return false;
+        }
+        let Some(entity) = self.entities.get_mut(&entity_id) else {
+            log::warn!("Ignoring active material change: entity {entity_id} does not exist");
+            return false;
+        };
+        let previous_material = entity.active_material();
+        entity.set_active_material(material_id);
+
+        if let Some(previous_material) = previous_material
+            && let Some(material) = self.get_material_mut(previous_material)
+        {
+            material.detach_entity(entity_id);
+        }
+        self.get_material_mut(material_id)
+            .unwrap()
+            .attach_entity(entity_id);
+
+        true
     }
 
     pub fn enabled_ui_components(&self) -> HashSet<ComponentId> {
@@ -496,7 +1280,7 @@ fn main(input: VertexInput) -> VertexOutput {
         queue: &Queue,
     ) {
         let actions = if action_queue.is_empty() {
-            self.initialize_components(device)
+            self.initialize_components(device, queue).await
         } else {
             action_queue
         };
@@ -505,10 +1289,38 @@ fn main(input: VertexInput) -> VertexOutput {
         }
     }
 
+    /// Makes the component hit-testable, its hover and click state is then given to it through
+    /// `UpdateParams::ui_hit_states`
+    pub fn set_ui_bounds(&mut self, component_id: ComponentId, bounds: UiRect) {
+        self.ui_bounds.insert(component_id, bounds);
+    }
+
+    pub fn clear_ui_bounds(&mut self, component_id: ComponentId) {
+        self.ui_bounds.remove(&component_id);
+    }
+
+    pub fn ui_bounds(&self, component_id: ComponentId) -> Option<UiRect> {
+        self.ui_bounds.get(&component_id).copied()
+    }
+
+    pub fn ui_hit_state(&self, component_id: ComponentId) -> UiHitState {
+        self.ui_hit_states
+            .get(&component_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
     pub fn register_ui_component(&mut self, component_id: ComponentId) {
         self.ui_components.push(component_id);
     }
 
+    /// Also drops the component's text buffer, so it isn't drawn again if the component is
+    /// re-enabled
+    pub fn unregister_ui_component(&mut self, component_id: ComponentId) {
+        self.ui_components.retain(|id| *id != component_id);
+        self.send_engine_action(Box::new(RemoveTextBufferEngineAction(component_id)));
+    }
+
     pub fn send_engine_action(&self, action: Box<dyn EngineAction>) {
         if let Some(engine_action_sender) = &self.engine_action_sender {
             engine_action_sender
@@ -539,12 +1351,148 @@ fn main(input: VertexInput) -> VertexOutput {
 
     pub fn set_active_camera_bind_group(&mut self, active_camera_bind_group: Option<BindGroup>) {
         self.active_camera_bind_group = active_camera_bind_group;
+        self.invalidate_render_bundles();
+    }
+
+    pub fn invalidate_render_bundles(&mut self) {
+        for material in &mut self.materials {
+            material.invalidate_render_bundle();
+        }
+    }
+
+    /// Rebuilds the stale render bundles of the materials that use them. `target` has to match the
+    /// pass the bundles are executed in
+    pub fn record_render_bundles(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        pipelines: &HashMap<PipelineId, RenderPipeline>,
+        target: &PipelineTarget,
+        depth_format: TextureFormat,
+    ) {
+        let components: Vec<&Component> = self.components.iter().collect();
+        let camera_bind_group = self.active_camera_bind_group.as_ref();
+
+        for material in self
+            .materials
+            .iter_mut()
+            .filter(|material| material.render_bundle_enabled() && material.is_enabled())
+        {
+            let Some(pipeline) = pipelines.get(material.pipeline_id()) else {
+                continue;
+            };
+            material.record_render_bundle(
+                device,
+                queue,
+                pipeline,
+                camera_bind_group,
+                &components,
+                &self.dense_storage,
+                target,
+                depth_format,
+            );
+        }
     }
 
     pub fn scene_index(&self) -> usize {
         self.scene_index
     }
 
+    pub fn depth_load_op(&self) -> LoadOp<f32> {
+        self.depth_load_op
+    }
+
+    /// `LoadOp::Load` keeps the depth written by the previous frame. The engine still clears the
+    /// depth on the first frame and after the window is resized, as there is nothing to load yet.
+    pub fn set_depth_load_op(&mut self, depth_load_op: LoadOp<f32>) {
+        self.depth_load_op = depth_load_op;
+    }
+
+    pub fn background(&self) -> Option<Background> {
+        self.background
+    }
+
+    /// Drawn behind all geometry, replacing the engine clear color for this scene
+    pub fn set_background(&mut self, background: Background) {
+        self.background = Some(background);
+    }
+
+    pub fn clear_background(&mut self) {
+        self.background = None;
+    }
+
+    /// The color the main pass clears to while this scene is active, the bottom of a gradient
+    /// background. `None` uses the engine clear color.
+    pub fn clear_color(&self) -> Option<wgpu::Color> {
+        match self.background? {
+            Background::SolidColor(color) => Some(color),
+            Background::Gradient { bottom, .. } => Some(bottom),
+        }
+    }
+
+    /// A solid color background, `None` falls back to the engine clear color
+    pub fn set_clear_color(&mut self, clear_color: Option<wgpu::Color>) {
+        self.background = clear_color.map(Background::SolidColor);
+    }
+
+    pub fn viewport(&self) -> Option<[f32; 4]> {
+        self.viewport
+    }
+
+    /// Restricts the main pass to `[x, y, width, height]`, in pixels of the render size measured
+    /// from the top left, e.g. for a minimap. The rest of the frame keeps the clear color. Cameras
+    /// keep their aspect ratio, so it should be set to the viewport's.
+    pub fn set_viewport(&mut self, viewport: Option<[f32; 4]>) {
+        self.viewport = viewport;
+    }
+
+    /// Every pair of vertices is one line segment. Lines only last for the current frame, so they
+    /// have to be pushed again on every update
+    pub fn push_debug_lines(&mut self, vertices: &[DebugLineVertex]) {
+        self.debug_lines.extend_from_slice(vertices);
+    }
+
+    pub fn debug_lines(&self) -> &[DebugLineVertex] {
+        &self.debug_lines
+    }
+
+    /// Makes the engine shut down its workload executor and exit the event loop after the current
+    /// frame
+    pub fn request_exit(&mut self) {
+        self.exit_requested = true;
+    }
+
+    pub fn exit_requested(&self) -> bool {
+        self.exit_requested
+    }
+
+    /// The target is rendered every frame once its camera data has been written, see
+    /// `RenderTarget::update_camera`
+    pub fn add_render_target(&mut self, render_target: RenderTarget) -> RenderTargetId {
+        let id = render_target.id();
+        self.render_targets.push(render_target);
+        self.new_pipelines_needed = true;
+        id
+    }
+
+    pub fn remove_render_target(&mut self, id: RenderTargetId) -> Option<RenderTarget> {
+        let index = self
+            .render_targets
+            .iter()
+            .position(|target| target.id() == id)?;
+        Some(self.render_targets.remove(index))
+    }
+
+    pub fn render_targets(&self) -> &[RenderTarget] {
+        &self.render_targets
+    }
+
+    pub fn get_render_target_mut(&mut self, id: RenderTargetId) -> Option<&mut RenderTarget> {
+        self.render_targets
+            .iter_mut()
+            .find(|target| target.id() == id)
+    }
+
     pub fn screen_space_materials(&self) -> &[ComponentId] {
         &self.screen_space_materials
     }
@@ -569,6 +1517,248 @@ fn main(input: VertexInput) -> VertexOutput {
         &self.materials
     }
 
+    /// Saves the entities, their components whose type is in `registry` and the materials.
+    /// Computes, render targets, dense components and workloads are not saved.
+    pub fn to_descriptor(&self, registry: &ComponentRegistry) -> SceneDescriptor {
+        // In creation order, so parents are listed before their children
+        let entities = self
+            .iter_entities()
+            .map(|(entity_id, entity, components)| {
+                let components = components
+                    .iter()
+                    .filter_map(|component| match registry.describe(component) {
+                        Some(Ok(descriptor)) => Some(descriptor),
+                        Some(Err(error)) => {
+                            log::warn!("Failed to save component {}: {error}", component.id());
+                            None
+                        }
+                        None => {
+                            log::warn!(
+                                "Skipped component {}, its type is not registered",
+                                component.id()
+                            );
+                            None
+                        }
+                    })
+                    .collect();
+
+                EntityDescriptor {
+                    id: entity_id,
+                    name: self.entity_name(entity_id).map(String::from),
+                    parent: Some(entity.parent_entity_id()).filter(|parent| *parent != 0),
+                    is_enabled: entity.is_enabled(),
+                    active_material: entity.active_material(),
+                    components,
+                }
+            })
+            .collect();
+
+        SceneDescriptor {
+            active_camera: self.active_camera,
+            entities,
+            materials: self
+                .materials
+                .iter()
+                .map(MaterialDescriptor::from_material)
+                .collect(),
+        }
+    }
+
+    /// Builds a scene from `descriptor`, asking `material_source` for the pipeline and
+    /// attachments of every saved material. Entities and components keep their saved ids, so
+    /// parameters that refer to them stay valid. Materials get new ids.
+    pub fn from_descriptor(
+        descriptor: &SceneDescriptor,
+        registry: &ComponentRegistry,
+        mut material_source: impl FnMut(&MaterialDescriptor) -> (PipelineId, Vec<ShaderAttachment>),
+    ) -> Result<Scene, SceneDescriptorError> {
+        let mut scene = Scene::default();
+
+        let material_ids: HashMap<ComponentId, ComponentId> = descriptor
+            .materials
+            .iter()
+            .map(|material| {
+                let (pipeline_id, attachments) = material_source(material);
+                let id = scene.create_material(
+                    pipeline_id,
+                    attachments,
+                    Vec::new(),
+                    material.immediate_data.clone(),
+                    material.is_enabled,
+                );
+                (material.id, id)
+            })
+            .collect();
+
+        for entity in &descriptor.entities {
+            if let Some(parent) = entity.parent
+                && !scene.entities.contains_key(&parent)
+            {
+                return Err(SceneDescriptorError::UnknownParent(parent));
+            }
+            let material = entity
+                .active_material
+                .map(|material| {
+                    material_ids
+                        .get(&material)
+                        .copied()
+                        .ok_or(SceneDescriptorError::UnknownMaterial(material))
+                })
+                .transpose()?;
+            let components = entity
+                .components
+                .iter()
+                .map(|component| registry.build(component))
+                .collect::<Result<Vec<Component>, SceneDescriptorError>>()?;
+
+            if !scene.entity_allocator.allocate_id(entity.id) {
+                return Err(SceneDescriptorError::DuplicateEntity(entity.id));
+            }
+            scene.insert_entity(
+                entity.id,
+                entity.parent,
+                components,
+                Vec::new(),
+                material,
+                entity.is_enabled,
+            );
+            if let Some(name) = &entity.name {
+                scene.set_entity_name(entity.id, name);
+            }
+        }
+
+        scene.set_active_camera(descriptor.active_camera);
+
+        Ok(scene)
+    }
+
+    /// Loads the RON scene at `path` in place of this scene's entities and materials, then loads
+    /// it again every time the file changes, see `Scene::reload_changed_ron`. Named entities keep
+    /// their id across reloads, so ids held for them by systems stay valid.
+    pub fn reload_from_ron(
+        &mut self,
+        path: impl AsRef<Path>,
+        registry: ComponentRegistry,
+        material_source: impl FnMut(&MaterialDescriptor) -> (PipelineId, Vec<ShaderAttachment>)
+        + Send
+        + Sync
+        + 'static,
+    ) -> Result<(), SceneDescriptorError> {
+        let mut source = RonSceneSource {
+            watcher: FileWatcher::new(path),
+            registry,
+            material_source: Box::new(material_source),
+        };
+        self.load_ron(&mut source)?;
+        self.ron_source = Some(source);
+        Ok(())
+    }
+
+    /// Loads the file given to `Scene::reload_from_ron` again if it changed. Called by the engine
+    /// at the start of every frame, which initializes the new components and materials when this
+    /// returns true. A file that fails to load is reported and the scene is left as it was.
+    pub fn reload_changed_ron(&mut self) -> bool {
+        let Some(mut source) = self.ron_source.take() else {
+            return false;
+        };
+        let reloaded = source.watcher.has_changed()
+            && match self.load_ron(&mut source) {
+                Ok(()) => true,
+                Err(error) => {
+                    log::warn!(
+                        "Failed to reload {}: {error}",
+                        source.watcher.path().display()
+                    );
+                    false
+                }
+            };
+        self.ron_source = Some(source);
+        reloaded
+    }
+
+    fn load_ron(&mut self, source: &mut RonSceneSource) -> Result<(), SceneDescriptorError> {
+        let ron =
+            std::fs::read_to_string(source.watcher.path()).map_err(SceneDescriptorError::Io)?;
+        let descriptor =
+            SceneDescriptor::from_ron(&ron).map_err(SceneDescriptorError::InvalidRon)?;
+        let loaded = Scene::from_descriptor(&descriptor, &source.registry, |material| {
+            (source.material_source)(material)
+        })?;
+        self.replace_entities(loaded);
+        Ok(())
+    }
+
+    /// Swaps this scene's entities and materials for those of `loaded`. Entities named like one
+    /// in this scene take over its id, the others keep their loaded id unless it is taken.
+    fn replace_entities(&mut self, mut loaded: Scene) {
+        let matched_ids: HashMap<EntityId, EntityId> = loaded
+            .entity_names
+            .iter()
+            .filter_map(|(name, loaded_id)| Some((*loaded_id, self.entity_by_name(name)?)))
+            .collect();
+
+        let top_level_entities: Vec<EntityId> = self
+            .entities
+            .values()
+            .filter(|entity| entity.parent_entity_id() == 0)
+            .map(|entity| entity.id())
+            .collect();
+        for entity_id in top_level_entities {
+            self.remove_entity(entity_id);
+        }
+        self.materials = std::mem::take(&mut loaded.materials);
+        self.screen_space_materials = std::mem::take(&mut loaded.screen_space_materials);
+        self.pipeline_to_corresponding_materials =
+            std::mem::take(&mut loaded.pipeline_to_corresponding_materials);
+
+        let order: Vec<(EntityId, usize)> = loaded
+            .iter_entities()
+            .map(|(entity_id, _, components)| (entity_id, components.len()))
+            .collect();
+        // Kept ids are reserved before any new id is handed out, so a new id never takes one
+        let mut new_ids: HashMap<EntityId, EntityId> = HashMap::new();
+        for (loaded_id, matched_id) in &matched_ids {
+            if self.entity_allocator.allocate_id(*matched_id) {
+                new_ids.insert(*loaded_id, *matched_id);
+            }
+        }
+        for (loaded_id, _) in &order {
+            if !new_ids.contains_key(loaded_id) && self.entity_allocator.allocate_id(*loaded_id) {
+                new_ids.insert(*loaded_id, *loaded_id);
+            }
+        }
+
+        let mut components = std::mem::take(&mut loaded.components).into_iter();
+        for (loaded_id, component_count) in order {
+            let entity = &loaded.entities[&loaded_id];
+            let id = *new_ids
+                .entry(loaded_id)
+                .or_insert_with(|| self.entity_allocator.allocate());
+            let material = entity.active_material();
+            if let Some(material) = material {
+                self.materials[material as usize].detach_entity(loaded_id);
+            }
+
+            self.insert_entity(
+                id,
+                Some(entity.parent_entity_id())
+                    .filter(|parent| *parent != 0)
+                    .map(|parent| new_ids[&parent]),
+                components.by_ref().take(component_count).collect(),
+                Vec::new(),
+                material,
+                entity.is_enabled(),
+            );
+            if let Some(name) = loaded.entity_name(loaded_id) {
+                self.set_entity_name(id, name);
+            }
+        }
+
+        self.active_camera = loaded.active_camera;
+        self.new_pipelines_needed = true;
+        self.invalidate_render_bundles();
+    }
+
     pub fn is_entity_enabled(&self, entity: EntityId) -> bool {
         let mut predecessor_entity_id = entity;
         while predecessor_entity_id != 0 {
@@ -0,0 +1,238 @@
+use std::{any::TypeId, collections::HashMap};
+
+use downcast_rs::Downcast;
+use ron::{error::SpannedError, value::RawValue};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::engine_management::pipeline::PipelineShader;
+
+use super::{
+    component::{Component, ComponentDetails, ComponentId, ComponentSystem, reserve_component_id},
+    entity::EntityId,
+    material::Material,
+};
+
+/// A component that can be saved in a `SceneDescriptor`. The parameters hold everything needed to
+/// build the component again, its GPU resources are created when the loaded scene initializes.
+pub trait SerializableComponent: ComponentSystem + Sized {
+    type Parameters: Serialize + DeserializeOwned;
+
+    fn to_parameters(&self) -> Self::Parameters;
+
+    /// Builds the component with the saved `id`, so components that refer to it by id keep
+    /// working after loading
+    fn from_parameters(parameters: Self::Parameters, id: ComponentId) -> Self;
+}
+
+/// A scene as saved by `Scene::to_descriptor` and loaded by `Scene::from_descriptor`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SceneDescriptor {
+    pub active_camera: Option<ComponentId>,
+    /// In the order the entities were created, so parents are listed before their children
+    pub entities: Vec<EntityDescriptor>,
+    pub materials: Vec<MaterialDescriptor>,
+}
+
+impl SceneDescriptor {
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, SpannedError> {
+        ron::from_str(source)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntityDescriptor {
+    pub id: EntityId,
+    /// Set with `Scene::set_entity_name`, used to match entities when a scene is reloaded
+    #[serde(default)]
+    pub name: Option<String>,
+    /// `None` for top-level entities
+    pub parent: Option<EntityId>,
+    pub is_enabled: bool,
+    pub active_material: Option<ComponentId>,
+    pub components: Vec<ComponentDescriptor>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComponentDescriptor {
+    /// The name the component's type is registered under in the `ComponentRegistry`
+    pub type_name: String,
+    pub id: ComponentId,
+    pub is_enabled: bool,
+    pub rendering_order: i32,
+    pub parameters: Box<RawValue>,
+}
+
+/// Pipelines and attachments hold GPU resources and `'static` data, so they are not saved. Instead
+/// `Scene::from_descriptor` asks for them by material, the shader paths help tell materials apart.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MaterialDescriptor {
+    pub id: ComponentId,
+    /// `None` when the shader was given as source
+    pub vertex_shader_path: Option<String>,
+    pub fragment_shader_path: Option<String>,
+    pub is_screen_space: bool,
+    pub immediate_data: Vec<u8>,
+    pub is_enabled: bool,
+}
+
+impl MaterialDescriptor {
+    pub fn from_material(material: &Material) -> Self {
+        let shader_path = |shader: &PipelineShader| match shader {
+            PipelineShader::Path(path) => Some(path.to_string()),
+            PipelineShader::Raw(_) => None,
+        };
+        let pipeline_id = material.pipeline_id();
+        MaterialDescriptor {
+            id: material.id(),
+            vertex_shader_path: shader_path(&pipeline_id.vertex_shader),
+            fragment_shader_path: shader_path(&pipeline_id.fragment_shader),
+            is_screen_space: pipeline_id.is_screen_space,
+            immediate_data: material.get_immediate_data().to_vec(),
+            is_enabled: material.is_enabled(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneDescriptorError {
+    UnregisteredComponent(String),
+    InvalidParameters {
+        type_name: String,
+        error: SpannedError,
+    },
+    /// An entity uses a material that is not in the descriptor
+    UnknownMaterial(ComponentId),
+    /// An entity's parent is not listed before it
+    UnknownParent(EntityId),
+    /// An entity id is listed more than once, or is 0
+    DuplicateEntity(EntityId),
+    /// The scene file given to `Scene::reload_from_ron` could not be read
+    Io(std::io::Error),
+    InvalidRon(SpannedError),
+}
+
+impl std::fmt::Display for SceneDescriptorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneDescriptorError::UnregisteredComponent(type_name) => {
+                write!(f, "No component is registered as \"{type_name}\"")
+            }
+            SceneDescriptorError::InvalidParameters { type_name, error } => {
+                write!(f, "Invalid parameters for \"{type_name}\": {error}")
+            }
+            SceneDescriptorError::UnknownMaterial(material_id) => {
+                write!(
+                    f,
+                    "The material {material_id} is not in the scene descriptor"
+                )
+            }
+            SceneDescriptorError::UnknownParent(entity_id) => {
+                write!(
+                    f,
+                    "The parent entity {entity_id} is not listed before its children"
+                )
+            }
+            SceneDescriptorError::DuplicateEntity(entity_id) => {
+                write!(f, "The entity id {entity_id} is listed more than once")
+            }
+            SceneDescriptorError::Io(error) => write!(f, "Failed to read the scene: {error}"),
+            SceneDescriptorError::InvalidRon(error) => {
+                write!(f, "Failed to parse the scene: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneDescriptorError {}
+
+type SerializeFn = fn(&Component) -> Result<Box<RawValue>, ron::Error>;
+type DeserializeFn = fn(&RawValue, ComponentId) -> Result<Component, SpannedError>;
+
+/// Maps component types to the names they are saved under. Only components whose type is
+/// registered are saved by `Scene::to_descriptor`.
+#[derive(Debug, Default)]
+pub struct ComponentRegistry {
+    type_names: HashMap<TypeId, String>,
+    constructors: HashMap<String, (SerializeFn, DeserializeFn)>,
+}
+
+impl ComponentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registering another type under the same name replaces the previous type when loading
+    pub fn register<T: SerializableComponent>(&mut self, type_name: &str) {
+        self.type_names
+            .insert(TypeId::of::<T>(), type_name.to_string());
+        self.constructors.insert(
+            type_name.to_string(),
+            (serialize_parameters::<T>, deserialize_parameters::<T>),
+        );
+    }
+
+    pub fn type_name(&self, component: &Component) -> Option<&str> {
+        self.type_names
+            .get(&(**component).as_any().type_id())
+            .map(String::as_str)
+    }
+
+    /// Returns `None` if the component's type is not registered
+    pub fn describe(
+        &self,
+        component: &Component,
+    ) -> Option<Result<ComponentDescriptor, ron::Error>> {
+        let type_name = self.type_name(component)?;
+        let (serialize, _) = self.constructors[type_name];
+        Some(serialize(component).map(|parameters| ComponentDescriptor {
+            type_name: type_name.to_string(),
+            id: component.id(),
+            is_enabled: component.is_enabled(),
+            rendering_order: component.rendering_order(),
+            parameters,
+        }))
+    }
+
+    pub fn build(
+        &self,
+        descriptor: &ComponentDescriptor,
+    ) -> Result<Component, SceneDescriptorError> {
+        let (_, deserialize) = self
+            .constructors
+            .get(&descriptor.type_name)
+            .ok_or_else(|| {
+                SceneDescriptorError::UnregisteredComponent(descriptor.type_name.clone())
+            })?;
+        let mut component =
+            deserialize(&descriptor.parameters, descriptor.id).map_err(|error| {
+                SceneDescriptorError::InvalidParameters {
+                    type_name: descriptor.type_name.clone(),
+                    error,
+                }
+            })?;
+        component.set_enabled_state(descriptor.is_enabled);
+        component.set_rendering_order(descriptor.rendering_order);
+        reserve_component_id(descriptor.id);
+        Ok(component)
+    }
+}
+
+fn serialize_parameters<T: SerializableComponent>(
+    component: &Component,
+) -> Result<Box<RawValue>, ron::Error> {
+    let component = component
+        .downcast_ref::<T>()
+        .expect("The component's type does not match its registered name");
+    RawValue::from_rust(&component.to_parameters())
+}
+
+fn deserialize_parameters<T: SerializableComponent>(
+    parameters: &RawValue,
+    id: ComponentId,
+) -> Result<Component, SpannedError> {
+    Ok(Box::new(T::from_parameters(parameters.into_rust()?, id)))
+}
@@ -0,0 +1,11 @@
+use std::fmt::Debug;
+
+use crate::EngineDetails;
+
+use super::scene::Scene;
+
+/// Scene-wide logic that doesn't belong to any entity. Systems run once per frame, before the
+/// active scene's components are updated.
+pub trait System: Debug + Send {
+    fn run(&mut self, scene: &mut Scene, engine_details: &EngineDetails);
+}
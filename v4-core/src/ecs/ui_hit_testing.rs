@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use winit::event::MouseButton;
+
+use crate::engine_support::input_state::InputState;
+
+use super::component::ComponentId;
+
+/// A rectangle in pixels, measured from the top left corner of the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl UiRect {
+    pub fn contains(&self, point: (f32, f32)) -> bool {
+        point.0 >= self.x
+            && point.0 < self.x + self.width
+            && point.1 >= self.y
+            && point.1 < self.y + self.height
+    }
+}
+
+/// The cursor's interaction with a component's UI bounds during the current frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UiHitState {
+    pub is_hovered: bool,
+    /// The left button was pressed inside the bounds and is still held
+    pub is_pressed: bool,
+    /// The left button was released inside the bounds after being pressed inside them
+    pub was_clicked: bool,
+}
+
+/// Recomputes the hit state of every registered rectangle from the cursor and the left button.
+/// Overlapping rectangles are all hovered.
+pub(crate) fn update_hit_states(
+    bounds: &HashMap<ComponentId, UiRect>,
+    hit_states: &mut HashMap<ComponentId, UiHitState>,
+    input_manager: &dyn InputState,
+) {
+    hit_states.retain(|component_id, _| bounds.contains_key(component_id));
+    let cursor = input_manager.cursor();
+    let pressed = input_manager.mouse_pressed(MouseButton::Left);
+    let released = input_manager.mouse_released(MouseButton::Left);
+
+    for (component_id, rect) in bounds {
+        let state = hit_states.entry(*component_id).or_default();
+        let is_hovered = cursor.is_some_and(|cursor| rect.contains(cursor));
+
+        state.was_clicked = state.is_pressed && released && is_hovered;
+        if pressed && is_hovered {
+            state.is_pressed = true;
+        } else if released || !input_manager.mouse_held(MouseButton::Left) {
+            state.is_pressed = false;
+        }
+        state.is_hovered = is_hovered;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_support::input_state::MockInput;
+
+    const BUTTON: ComponentId = 1;
+
+    fn button_bounds() -> HashMap<ComponentId, UiRect> {
+        HashMap::from([(
+            BUTTON,
+            UiRect {
+                x: 10.0,
+                y: 20.0,
+                width: 100.0,
+                height: 40.0,
+            },
+        )])
+    }
+
+    fn step(
+        bounds: &HashMap<ComponentId, UiRect>,
+        hit_states: &mut HashMap<ComponentId, UiHitState>,
+        input: &mut MockInput,
+    ) -> UiHitState {
+        update_hit_states(bounds, hit_states, input);
+        input.end_step();
+        hit_states.get(&BUTTON).copied().unwrap_or_default()
+    }
+
+    #[test]
+    fn a_cursor_inside_the_bounds_hovers_them() {
+        let bounds = button_bounds();
+        let mut hit_states = HashMap::new();
+        let mut input = MockInput::default();
+
+        input.set_cursor(Some((50.0, 30.0)));
+        assert!(step(&bounds, &mut hit_states, &mut input).is_hovered);
+
+        input.set_cursor(Some((110.0, 30.0)));
+        assert!(!step(&bounds, &mut hit_states, &mut input).is_hovered);
+
+        input.set_cursor(None);
+        assert!(!step(&bounds, &mut hit_states, &mut input).is_hovered);
+    }
+
+    #[test]
+    fn clicks_need_a_press_and_release_inside() {
+        let bounds = button_bounds();
+        let mut hit_states = HashMap::new();
+        let mut input = MockInput::default();
+        input.set_cursor(Some((50.0, 30.0)));
+
+        input.press_mouse(MouseButton::Left);
+        let state = step(&bounds, &mut hit_states, &mut input);
+        assert!(state.is_pressed && !state.was_clicked);
+
+        input.release_mouse(MouseButton::Left);
+        let state = step(&bounds, &mut hit_states, &mut input);
+        assert!(!state.is_pressed && state.was_clicked);
+        assert!(!step(&bounds, &mut hit_states, &mut input).was_clicked);
+
+        // Dragging off the bounds before releasing cancels the click
+        input.press_mouse(MouseButton::Left);
+        step(&bounds, &mut hit_states, &mut input);
+        input.set_cursor(Some((200.0, 30.0)));
+        input.release_mouse(MouseButton::Left);
+        let state = step(&bounds, &mut hit_states, &mut input);
+        assert!(!state.is_pressed && !state.was_clicked);
+    }
+
+    #[test]
+    fn unregistered_bounds_lose_their_state() {
+        let mut hit_states = HashMap::new();
+        let mut input = MockInput::default();
+        input.set_cursor(Some((50.0, 30.0)));
+        step(&button_bounds(), &mut hit_states, &mut input);
+
+        step(&HashMap::new(), &mut hit_states, &mut input);
+
+        assert!(hit_states.is_empty());
+    }
+}
@@ -1,14 +1,18 @@
-use winit::window::Window;
+use winit::{monitor::Fullscreen, window::Window};
 
 use crate::ecs::component::ComponentId;
 
-use super::font_management::{FontState, TextAttributes, TextComponentProperties, TextDisplayInfo};
+use super::{
+    font_management::{FontState, TextAttributes, TextComponentProperties, TextDisplayInfo},
+    rendering_management::RenderingManager,
+};
 
 pub struct V4Mutable<'a> {
     pub window: &'a dyn Window,
     pub active_scene: &'a mut usize,
     pub initialized_scene: &'a mut bool,
     pub font_state: &'a mut FontState,
+    pub rendering_manager: &'a mut RenderingManager,
 }
 
 pub trait EngineAction: Send + Sync {
@@ -38,6 +42,8 @@ pub struct UpdateTextBufferEngineAction {
     pub text_attributes: Option<TextAttributes>,
     pub text_metrics: Option<glyphon::Metrics>,
     pub text_display_info: Option<TextDisplayInfo>,
+    /// Changes only the alignment, `text_display_info` takes precedence when both are given
+    pub alignment: Option<glyphon::cosmic_text::Align>,
 }
 
 impl EngineAction for UpdateTextBufferEngineAction {
@@ -48,10 +54,19 @@ impl EngineAction for UpdateTextBufferEngineAction {
             self.text_attributes,
             self.text_metrics,
             self.text_display_info,
+            self.alignment,
         );
     }
 }
 
+pub struct RemoveTextBufferEngineAction(pub ComponentId);
+
+impl EngineAction for RemoveTextBufferEngineAction {
+    fn execute(self: Box<Self>, engine: V4Mutable) {
+        engine.font_state.remove_text_buffer(self.0);
+    }
+}
+
 pub struct SetCursorLockEngineAction(pub bool);
 
 impl EngineAction for SetCursorLockEngineAction {
@@ -78,3 +93,65 @@ impl EngineAction for SetCursorPositionEngineAction
             .unwrap_or_else(|_| panic!("Error setting cursor to position {:?}", self.0));
     }
 }
+
+pub struct SetCursorVisibleEngineAction(pub bool);
+
+impl EngineAction for SetCursorVisibleEngineAction {
+    fn execute(self: Box<Self>, engine: V4Mutable) {
+        engine.window.set_cursor_visible(self.0);
+    }
+}
+
+pub struct SetWindowTitleEngineAction(pub String);
+
+impl EngineAction for SetWindowTitleEngineAction {
+    fn execute(self: Box<Self>, engine: V4Mutable) {
+        engine.window.set_title(&self.0);
+    }
+}
+
+/// `None` leaves fullscreen. The window size changes, so the surface is resized by the usual
+/// `WindowEvent::SurfaceResized` handling once the window reports its new size.
+pub struct SetFullscreenEngineAction(pub Option<Fullscreen>);
+
+impl EngineAction for SetFullscreenEngineAction {
+    fn execute(self: Box<Self>, engine: V4Mutable) {
+        engine.window.set_fullscreen(self.0);
+    }
+}
+
+pub struct ToggleFullscreenEngineAction;
+
+impl EngineAction for ToggleFullscreenEngineAction {
+    fn execute(self: Box<Self>, engine: V4Mutable) {
+        let fullscreen = engine.window.fullscreen().is_some();
+        engine
+            .window
+            .set_fullscreen((!fullscreen).then_some(Fullscreen::Borderless(None)));
+    }
+}
+
+pub struct SetAntialiasingEngineAction(pub bool);
+
+impl EngineAction for SetAntialiasingEngineAction {
+    fn execute(self: Box<Self>, engine: V4Mutable) {
+        engine.rendering_manager.set_antialiasing(self.0);
+    }
+}
+
+pub struct SetDebugDrawEngineAction(pub bool);
+
+impl EngineAction for SetDebugDrawEngineAction {
+    fn execute(self: Box<Self>, engine: V4Mutable) {
+        engine.rendering_manager.set_debug_draw_enabled(self.0);
+    }
+}
+
+pub struct ToggleDebugDrawEngineAction;
+
+impl EngineAction for ToggleDebugDrawEngineAction {
+    fn execute(self: Box<Self>, engine: V4Mutable) {
+        let enabled = engine.rendering_manager.debug_draw_enabled();
+        engine.rendering_manager.set_debug_draw_enabled(!enabled);
+    }
+}
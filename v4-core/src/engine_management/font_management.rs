@@ -1,7 +1,11 @@
 use crate::ecs::component::ComponentId;
 
-use glyphon::{FontSystem, SwashCache, TextAtlas, TextRenderer};
-use std::{collections::HashMap, fmt::Debug};
+use glyphon::{FontSystem, SwashCache, TextAtlas, TextRenderer, cosmic_text::fontdb};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    sync::Arc,
+};
 
 pub struct FontState {
     pub font_system: FontSystem,
@@ -24,6 +28,59 @@ impl std::fmt::Debug for FontState {
 }
 
 impl FontState {
+    /// Registers the fonts in the file and returns the family names they can be used by in
+    /// `FontFamily::Name`
+    pub fn load_font_from_path(&mut self, path: &str) -> std::io::Result<Vec<String>> {
+        let bytes = std::fs::read(path)?;
+        Ok(self.load_font_bytes(bytes))
+    }
+
+    /// Registers the fonts in a TrueType or OpenType file or collection and returns their family
+    /// names, empty when the bytes hold no font
+    pub fn load_font_bytes(&mut self, bytes: Vec<u8>) -> Vec<String> {
+        let database = self.font_system.db_mut();
+        let face_ids = database.load_font_source(fontdb::Source::Binary(Arc::new(bytes)));
+
+        let mut family_names: Vec<String> = Vec::new();
+        for face in face_ids.iter().filter_map(|id| database.face(*id)) {
+            for (family_name, _) in &face.families {
+                if !family_names.contains(family_name) {
+                    family_names.push(family_name.clone());
+                }
+            }
+        }
+        family_names
+    }
+
+    /// The width of the longest line and the total height of the text laid out without a width
+    /// limit, in the same units as the metrics. Nothing is kept for rendering.
+    pub fn measure_text(
+        &mut self,
+        text: &str,
+        attrs: &TextAttributes,
+        metrics: glyphon::Metrics,
+    ) -> (f32, f32) {
+        let font_system = &mut self.font_system;
+        let mut buffer = glyphon::Buffer::new(font_system, metrics);
+        buffer.set_size(font_system, None, None);
+        buffer.set_text(
+            font_system,
+            text,
+            &attrs.into_glyphon_attrs(),
+            glyphon::Shaping::Advanced,
+            None,
+        );
+
+        buffer
+            .layout_runs()
+            .fold((0.0, 0.0), |(width, height), run| {
+                (
+                    f32::max(width, run.line_w),
+                    f32::max(height, run.line_top + run.line_height),
+                )
+            })
+    }
+
     pub fn create_text_buffer(
         &mut self,
         component_id: ComponentId,
@@ -36,15 +93,16 @@ impl FontState {
         let mut text_buffer = glyphon::Buffer::new(font_system, text_metrics);
         text_buffer.set_size(
             font_system,
-            Some(text_display_info.on_screen_height),
+            Some(text_display_info.on_screen_width),
             Some(text_display_info.on_screen_height),
         );
+        text_buffer.set_wrap(font_system, text_display_info.wrap);
         text_buffer.set_text(
             font_system,
             text,
             &text_attributes.into_glyphon_attrs(),
             glyphon::Shaping::Advanced,
-            None,
+            text_display_info.alignment,
         );
 
         self.text_buffers.insert(
@@ -62,10 +120,36 @@ impl FontState {
                 },
                 scale: text_display_info.scale,
                 attributes: text_attributes,
+                alignment: text_display_info.alignment,
             },
         );
     }
 
+    /// The component's text stops being drawn, returns `None` if it had no text buffer
+    pub fn remove_text_buffer(&mut self, component_id: ComponentId) -> Option<TextRenderInfo> {
+        self.text_buffers.remove(&component_id)
+    }
+
+    /// The text areas drawn for the buffers of enabled UI components. Takes the buffers rather than
+    /// `self` so the font system stays free to be borrowed mutably while preparing them.
+    pub fn text_areas<'a>(
+        text_buffers: &'a HashMap<ComponentId, TextRenderInfo>,
+        enabled_ui_components: &'a HashSet<ComponentId>,
+    ) -> impl Iterator<Item = glyphon::TextArea<'a>> {
+        text_buffers
+            .iter()
+            .filter(|(id, _)| enabled_ui_components.contains(id))
+            .map(|(_, data)| glyphon::TextArea {
+                buffer: &data.buffer,
+                left: data.top_left_pos[0],
+                top: data.top_left_pos[1],
+                scale: data.scale,
+                bounds: data.bounds,
+                default_color: data.attributes.color,
+                custom_glyphs: &[],
+            })
+    }
+
     pub fn update_text_buffer(
         &mut self,
         component_id: ComponentId,
@@ -73,9 +157,20 @@ impl FontState {
         text_attributes: Option<TextAttributes>,
         text_metrics: Option<glyphon::Metrics>,
         text_display_info: Option<TextDisplayInfo>,
+        alignment: Option<glyphon::cosmic_text::Align>,
     ) {
         let font_system = &mut self.font_system;
         if let Some(text_buffer) = self.text_buffers.get_mut(&component_id) {
+            if let Some(new_alignment) = text_display_info
+                .as_ref()
+                .map(|info| info.alignment)
+                .or(alignment.map(Some))
+            {
+                text_buffer.alignment = new_alignment;
+                for line in text_buffer.buffer.lines.iter_mut() {
+                    line.set_align(new_alignment);
+                }
+            }
             if let Some(new_text) = text {
                 let attrs = text_attributes.as_ref().unwrap_or(&text_buffer.attributes);
                 text_buffer.buffer.set_text(
@@ -83,7 +178,7 @@ impl FontState {
                     &new_text,
                     &attrs.into_glyphon_attrs(),
                     glyphon::Shaping::Advanced,
-                    None,
+                    text_buffer.alignment,
                 );
                 text_buffer.attributes = attrs.clone();
             }
@@ -93,6 +188,14 @@ impl FontState {
                     .set_metrics(font_system, new_text_metrics);
             }
             if let Some(new_text_display_info) = text_display_info {
+                text_buffer.buffer.set_size(
+                    font_system,
+                    Some(new_text_display_info.on_screen_width),
+                    Some(new_text_display_info.on_screen_height),
+                );
+                text_buffer
+                    .buffer
+                    .set_wrap(font_system, new_text_display_info.wrap);
                 text_buffer.bounds = glyphon::TextBounds {
                     left: new_text_display_info.top_left_pos[0] as i32,
                     top: new_text_display_info.top_left_pos[1] as i32,
@@ -103,6 +206,8 @@ impl FontState {
                         as i32,
                 };
             }
+            // Changing the alignment drops the layout of the lines
+            text_buffer.buffer.shape_until_scroll(font_system, false);
         }
     }
 }
@@ -113,6 +218,11 @@ pub struct TextDisplayInfo {
     pub on_screen_height: f32,
     pub top_left_pos: [f32; 2],
     pub scale: f32,
+    /// Horizontal alignment of every line within `on_screen_width`, `None` aligns to the start of
+    /// the text's direction
+    pub alignment: Option<glyphon::cosmic_text::Align>,
+    /// How lines longer than `on_screen_width` are broken, `Wrap::None` never breaks them
+    pub wrap: glyphon::Wrap,
 }
 
 #[derive(Debug)]
@@ -122,6 +232,7 @@ pub struct TextRenderInfo {
     pub scale: f32,
     pub bounds: glyphon::TextBounds,
     pub attributes: TextAttributes,
+    pub alignment: Option<glyphon::cosmic_text::Align>,
 }
 
 #[derive(Debug, Clone)]
@@ -201,3 +312,136 @@ pub struct TextComponentProperties {
     pub text_metrics: glyphon::Metrics,
     pub text_display_info: TextDisplayInfo,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE_HEIGHT: f32 = 25.0;
+    const METRICS: glyphon::Metrics = glyphon::Metrics {
+        font_size: 20.0,
+        line_height: LINE_HEIGHT,
+    };
+
+    fn font_state() -> FontState {
+        let (device, queue) = wgpu::Device::noop(&wgpu::DeviceDescriptor::default());
+        let cache = glyphon::Cache::new(&device);
+        let viewport = glyphon::Viewport::new(&device, &cache);
+        let mut atlas =
+            TextAtlas::new(&device, &queue, &cache, wgpu::TextureFormat::Rgba8UnormSrgb);
+        let text_renderer =
+            TextRenderer::new(&mut atlas, &device, wgpu::MultisampleState::default(), None);
+
+        // Only the bundled font, so the measurements don't depend on the machine's fonts
+        let mut font_state = FontState {
+            font_system: FontSystem::new_with_locale_and_db(
+                "en-US".to_string(),
+                fontdb::Database::new(),
+            ),
+            swash_cache: SwashCache::new(),
+            viewport,
+            atlas,
+            text_renderer,
+            text_buffers: HashMap::new(),
+        };
+        let family_names = font_state
+            .load_font_bytes(include_bytes!("../../../examples/assets/fonts/inter.ttf").to_vec());
+        assert_eq!(family_names, vec!["Inter".to_string()]);
+        font_state
+    }
+
+    fn inter() -> TextAttributes {
+        glyphon::Attrs::new()
+            .family(glyphon::Family::Name("Inter"))
+            .into()
+    }
+
+    #[test]
+    fn longer_text_is_wider() {
+        let mut font_state = font_state();
+        let (short_width, short_height) = font_state.measure_text("ab", &inter(), METRICS);
+        let (long_width, long_height) = font_state.measure_text("abab abab", &inter(), METRICS);
+
+        assert!(short_width > 0.0);
+        assert!(long_width > short_width * 2.0);
+        assert_eq!(short_height, LINE_HEIGHT);
+        assert_eq!(long_height, LINE_HEIGHT);
+    }
+
+    #[test]
+    fn lines_stack_and_the_widest_one_counts() {
+        let mut font_state = font_state();
+        let (widest, _) = font_state.measure_text("a much longer line", &inter(), METRICS);
+        let (width, height) =
+            font_state.measure_text("short\na much longer line\nmid line", &inter(), METRICS);
+
+        assert_eq!(width, widest);
+        assert_eq!(height, LINE_HEIGHT * 3.0);
+    }
+
+    #[test]
+    fn long_text_does_not_wrap() {
+        let mut font_state = font_state();
+        let text = "word ".repeat(200);
+        let (width, height) = font_state.measure_text(&text, &inter(), METRICS);
+
+        assert!(width > 1000.0);
+        assert_eq!(height, LINE_HEIGHT);
+    }
+
+    fn create_text_buffer(font_state: &mut FontState, component_id: ComponentId, left: f32) {
+        font_state.create_text_buffer(
+            component_id,
+            "Text",
+            inter(),
+            METRICS,
+            TextDisplayInfo {
+                on_screen_width: 100.0,
+                on_screen_height: LINE_HEIGHT,
+                top_left_pos: [left, 0.0],
+                scale: 1.0,
+                alignment: None,
+                wrap: glyphon::Wrap::None,
+            },
+        );
+    }
+
+    fn text_area_lefts(font_state: &FontState, enabled: &HashSet<ComponentId>) -> Vec<f32> {
+        let mut lefts: Vec<f32> = FontState::text_areas(&font_state.text_buffers, enabled)
+            .map(|text_area| text_area.left)
+            .collect();
+        lefts.sort_by(f32::total_cmp);
+        lefts
+    }
+
+    #[test]
+    fn removed_text_buffers_are_not_drawn() {
+        let mut font_state = font_state();
+        create_text_buffer(&mut font_state, 1, 10.0);
+        create_text_buffer(&mut font_state, 2, 20.0);
+        let enabled = HashSet::from([1, 2]);
+        assert_eq!(text_area_lefts(&font_state, &enabled), [10.0, 20.0]);
+
+        assert!(font_state.remove_text_buffer(1).is_some());
+
+        assert_eq!(text_area_lefts(&font_state, &enabled), [20.0]);
+        assert!(font_state.remove_text_buffer(1).is_none());
+    }
+
+    #[test]
+    fn text_of_disabled_components_is_not_drawn() {
+        let mut font_state = font_state();
+        create_text_buffer(&mut font_state, 1, 10.0);
+        create_text_buffer(&mut font_state, 2, 20.0);
+
+        assert_eq!(text_area_lefts(&font_state, &HashSet::from([2])), [20.0]);
+    }
+
+    #[test]
+    fn measuring_keeps_no_buffers() {
+        let mut font_state = font_state();
+        font_state.measure_text("Hello", &inter(), METRICS);
+
+        assert!(font_state.text_buffers.is_empty());
+    }
+}
@@ -1,4 +1,6 @@
 pub mod engine_action;
 pub mod font_management;
 pub mod pipeline;
+pub mod pipeline_cache;
+pub mod render_graph;
 pub mod rendering_management;
@@ -1,11 +1,16 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    time::Duration,
+};
 
 use wgpu::{
     util::make_spirv, BindGroupLayout, Device, RenderPipeline, ShaderStages, TextureFormat,
     VertexBufferLayout,
 };
 
-use crate::engine_support::texture_support::TextureBundle;
+use crate::engine_support::file_watcher::FileWatcher;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PipelineAttachments {
@@ -19,15 +24,27 @@ pub struct PipelineId {
     pub spirv_vertex_shader: bool,
     pub fragment_shader: PipelineShader,
     pub spirv_fragment_shader: bool,
+    /// Name of the vertex shader's entry point, usually `"main"`
+    pub vertex_entry: &'static str,
+    pub fragment_entry: &'static str,
     pub vertex_layouts: Vec<wgpu::VertexBufferLayout<'static>>,
     pub uses_camera: bool,
     pub is_screen_space: bool,
     pub geometry_details: GeometryDetails,
     pub immediate_size: u32,
     pub render_priority: i32,
+    /// How the fragment output is combined with the color target, `None` replaces it. Usually
+    /// `PipelineId::DEFAULT_BLEND_STATE`.
+    pub blend_state: Option<wgpu::BlendState>,
+    /// Whether the fragment shader also writes the G-buffer from `V4Builder::gbuffer_formats`, at
+    /// locations 1 and up. Pipelines that don't leave it untouched.
+    pub writes_gbuffer: bool,
 }
 
 impl PipelineId {
+    pub const DEFAULT_BLEND_STATE: Option<wgpu::BlendState> =
+        Some(wgpu::BlendState::ALPHA_BLENDING);
+
     pub fn vertex_layouts<'a>(&'a self) -> &'a [VertexBufferLayout<'a>] {
         &self.vertex_layouts
     }
@@ -35,6 +52,77 @@ impl PipelineId {
     pub fn geometry_details(&self) -> &GeometryDetails {
         &self.geometry_details
     }
+
+    /// The files of the pipeline's `PipelineShader::Path` shaders
+    pub fn shader_paths(&self) -> impl Iterator<Item = &'static str> {
+        [&self.vertex_shader, &self.fragment_shader]
+            .into_iter()
+            .filter_map(|shader| match shader {
+                PipelineShader::Path(path) => Some(*path),
+                PipelineShader::Raw(_) => None,
+            })
+    }
+
+    /// Whether the pipeline draws in the depth pre-pass, which is the case for every depth tested
+    /// pipeline that isn't a screen-space effect
+    pub fn uses_depth_prepass(&self) -> bool {
+        !self.is_screen_space && self.geometry_details.depth_test
+    }
+}
+
+/// The attachments of the passes a pipeline is compiled for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PipelineTarget {
+    pub render_format: TextureFormat,
+    /// The G-buffer drawn into alongside the color target. Screen-space pipelines don't draw into
+    /// it but read it, see `screen_space_bind_group_layout_entries`.
+    pub gbuffer_formats: Vec<TextureFormat>,
+    pub sample_count: u32,
+}
+
+impl PipelineTarget {
+    /// A single-sampled target without a G-buffer
+    pub fn new(render_format: TextureFormat) -> Self {
+        PipelineTarget {
+            render_format,
+            gbuffer_formats: Vec::new(),
+            sample_count: 1,
+        }
+    }
+
+    /// The formats of the color attachments, the G-buffer follows the render format
+    pub fn color_formats(&self) -> Vec<Option<TextureFormat>> {
+        std::iter::once(self.render_format)
+            .chain(self.gbuffer_formats.iter().copied())
+            .map(Some)
+            .collect()
+    }
+
+    /// The color targets of a pipeline drawing into this target. The G-buffer targets are only
+    /// written when `writes_gbuffer` is set.
+    pub fn color_targets(
+        &self,
+        blend_state: Option<wgpu::BlendState>,
+        writes_gbuffer: bool,
+    ) -> Vec<Option<wgpu::ColorTargetState>> {
+        std::iter::once(Some(wgpu::ColorTargetState {
+            format: self.render_format,
+            blend: blend_state,
+            write_mask: wgpu::ColorWrites::ALL,
+        }))
+        .chain(self.gbuffer_formats.iter().map(|format| {
+            Some(wgpu::ColorTargetState {
+                format: *format,
+                blend: None,
+                write_mask: if writes_gbuffer {
+                    wgpu::ColorWrites::ALL
+                } else {
+                    wgpu::ColorWrites::empty()
+                },
+            })
+        }))
+        .collect()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -50,6 +138,9 @@ pub struct GeometryDetails {
     pub front_face: wgpu::FrontFace,
     pub cull_mode: Option<wgpu::Face>,
     pub polygon_mode: wgpu::PolygonMode,
+    /// When false the pipeline neither tests nor writes depth, so its geometry is drawn over
+    /// everything rendered before it, which makes it suited to overlays like gizmos
+    pub depth_test: bool,
 }
 
 impl Default for GeometryDetails {
@@ -60,18 +151,91 @@ impl Default for GeometryDetails {
             front_face: wgpu::FrontFace::Ccw,
             cull_mode: None,
             polygon_mode: wgpu::PolygonMode::Fill,
+            depth_test: true,
         }
     }
 }
 
-pub fn create_render_pipeline(
+/// Group 0 of every screen-space effect: the color input texture at binding 0, its sampler at
+/// binding 1, the scene depth (`texture_depth_2d`) at binding 2 and a uniform at binding 3 laid out
+/// as `struct { time: f32, resolution: vec2<f32>, mouse: vec2<f32>, viewport: vec4<f32> }`, with
+/// the time in seconds and the mouse position in pixels. The viewport is the `[x, y, width, height]`
+/// the scene was drawn into, the whole frame unless `Scene::set_viewport` restricted it. Effects
+/// still cover the whole frame. Binding 4 is the scene before any effect ran, see
+/// `ScreenSpaceInput`. The G-buffer textures follow from binding 5, as unfilterable
+/// `texture_2d<f32>`s. Effect attachments start at group 1.
+pub fn screen_space_bind_group_layout_entries(
+    gbuffer_count: usize,
+) -> Vec<wgpu::BindGroupLayoutEntry> {
+    let gbuffer_entries = (0..gbuffer_count).map(|index| wgpu::BindGroupLayoutEntry {
+        binding: 5 + index as u32,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    });
+    vec![
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 2,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Depth,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 3,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 4,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        },
+    ]
+    .into_iter()
+    .chain(gbuffer_entries)
+    .collect()
+}
+
+fn create_pipeline_layout(
     device: &Device,
     id: &PipelineId,
     attachment_bind_group_layout: Option<&BindGroupLayout>,
-    render_format: TextureFormat,
-    is_vert_spirv: bool,
-    is_frag_spirv: bool,
-) -> RenderPipeline {
+    gbuffer_count: usize,
+) -> wgpu::PipelineLayout {
     let camera_layout = if id.uses_camera {
         Some(
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -96,24 +260,7 @@ pub fn create_render_pipeline(
         Some(
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some(&format!("{id:?} Pipeline Screen Space Bind Group Layout")),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            multisampled: false,
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
+                entries: &screen_space_bind_group_layout_entries(gbuffer_count),
             }),
         )
     } else {
@@ -136,38 +283,51 @@ pub fn create_render_pipeline(
         };
 
 
-    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some(&format!("{id:?} Pipeline Layout")),
         bind_group_layouts: &bind_group_layouts,
         immediate_size: id.immediate_size,
-    });
+    })
+}
 
-    let vertex_shader_module =
-        load_shader_module_descriptor(device, &id.vertex_shader, is_vert_spirv);
-    if let Err(error) = vertex_shader_module {
-        panic!(
-            "Vertex shader error for shader {:?}: {error}",
-            id.vertex_shader
-        );
-    }
-    let vertex_shader_module = vertex_shader_module.unwrap();
+/// With `depth_prepass` the pipeline only draws where the depth pre-pass left the same depth,
+/// see `create_depth_prepass_pipeline`. The target has to match the attachments of the passes the
+/// pipeline is drawn in.
+pub fn create_render_pipeline(
+    device: &Device,
+    id: &PipelineId,
+    attachment_bind_group_layout: Option<&BindGroupLayout>,
+    target: &PipelineTarget,
+    depth_format: TextureFormat,
+    depth_prepass: bool,
+    is_vert_spirv: bool,
+    is_frag_spirv: bool,
+) -> Result<RenderPipeline, PipelineError> {
+    let pipeline_layout = create_pipeline_layout(
+        device,
+        id,
+        attachment_bind_group_layout,
+        target.gbuffer_formats.len(),
+    );
+    // Screen-space effects draw into the frame, they only read the G-buffer
+    let color_targets = if id.is_screen_space {
+        PipelineTarget::new(target.render_format).color_targets(id.blend_state, false)
+    } else {
+        target.color_targets(id.blend_state, id.writes_gbuffer)
+    };
 
+    let vertex_shader_module =
+        load_validated_shader_module(device, &id.vertex_shader, is_vert_spirv)?;
     let fragment_shader_module =
-        load_shader_module_descriptor(device, &id.fragment_shader, is_frag_spirv);
-    if let Err(error) = fragment_shader_module {
-        panic!(
-            "Fragment shader error for shader {:?}: {error}",
-            id.fragment_shader
-        );
-    }
-    let fragment_shader_module = fragment_shader_module.unwrap();
+        load_validated_shader_module(device, &id.fragment_shader, is_frag_spirv)?;
 
-    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    let error_scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: Some(&format!("{id:?} Pipeline")),
         layout: Some(&pipeline_layout),
         vertex: wgpu::VertexState {
             module: &vertex_shader_module,
-            entry_point: Some("main"),
+            entry_point: Some(id.vertex_entry),
             compilation_options: Default::default(),
             buffers: &id.vertex_layouts,
         },
@@ -183,31 +343,366 @@ pub fn create_render_pipeline(
         depth_stencil: if id.is_screen_space {
             None
         } else {
+            let depth_test = id.geometry_details.depth_test;
             Some(wgpu::DepthStencilState {
-                format: TextureBundle::DEPTH_FORMAT,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::LessEqual,
+                format: depth_format,
+                depth_write_enabled: depth_test && !depth_prepass,
+                depth_compare: if depth_test && depth_prepass {
+                    wgpu::CompareFunction::Equal
+                } else if depth_test {
+                    wgpu::CompareFunction::LessEqual
+                } else {
+                    wgpu::CompareFunction::Always
+                },
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             })
         },
         multisample: wgpu::MultisampleState {
-            count: 1,
+            count: target.sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
         fragment: Some(wgpu::FragmentState {
             module: &fragment_shader_module,
-            entry_point: Some("main"),
+            entry_point: Some(id.fragment_entry),
             compilation_options: Default::default(),
-            targets: &[Some(wgpu::ColorTargetState {
-                format: render_format,
-                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                write_mask: wgpu::ColorWrites::ALL,
-            })],
+            targets: &color_targets,
         }),
         multiview_mask: None,
         cache: None,
+    });
+
+    match pollster::block_on(error_scope.pop()) {
+        Some(error) => Err(PipelineError {
+            shader: None,
+            message: error.to_string(),
+        }),
+        None => Ok(pipeline),
+    }
+}
+
+/// The depth only variant of the pipeline drawn in the depth pre-pass. It shares the pipeline's
+/// layout and vertex shader, so materials bind the same groups for both.
+pub fn create_depth_prepass_pipeline(
+    device: &Device,
+    id: &PipelineId,
+    attachment_bind_group_layout: Option<&BindGroupLayout>,
+    depth_format: TextureFormat,
+    sample_count: u32,
+) -> Result<RenderPipeline, PipelineError> {
+    let pipeline_layout = create_pipeline_layout(device, id, attachment_bind_group_layout, 0);
+
+    let vertex_shader_module =
+        load_validated_shader_module(device, &id.vertex_shader, id.spirv_vertex_shader)?;
+
+    let error_scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(&format!("{id:?} Depth Pre-pass Pipeline")),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &vertex_shader_module,
+            entry_point: Some(id.vertex_entry),
+            compilation_options: Default::default(),
+            buffers: &id.vertex_layouts,
+        },
+        primitive: wgpu::PrimitiveState {
+            topology: id.geometry_details.topology,
+            strip_index_format: id.geometry_details.strip_index_format,
+            front_face: id.geometry_details.front_face,
+            cull_mode: id.geometry_details.cull_mode,
+            unclipped_depth: false,
+            polygon_mode: id.geometry_details.polygon_mode,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: depth_format,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::LessEqual,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        fragment: None,
+        multiview_mask: None,
+        cache: None,
+    });
+
+    match pollster::block_on(error_scope.pop()) {
+        Some(error) => Err(PipelineError {
+            shader: None,
+            message: error.to_string(),
+        }),
+        None => Ok(pipeline),
+    }
+}
+
+/// Why a pipeline could not be created
+#[derive(Debug, Clone)]
+pub struct PipelineError {
+    /// The shader that failed to load or compile, `None` when the shaders compiled but the
+    /// pipeline itself is invalid, e.g. when its layout doesn't match the shaders' bindings
+    pub shader: Option<PipelineShader>,
+    pub message: String,
+}
+
+impl std::fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.shader {
+            Some(PipelineShader::Path(path)) => {
+                write!(f, "Shader error in {path}: {}", self.message)
+            }
+            Some(PipelineShader::Raw(_)) => {
+                write!(f, "Shader error in raw shader: {}", self.message)
+            }
+            None => write!(f, "Pipeline error: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+/// A finished compilation with the target the pipeline was compiled for
+pub type CompiledPipeline = (
+    PipelineId,
+    PipelineTarget,
+    Result<RenderPipeline, PipelineError>,
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineCompileState {
+    Compiling,
+    Ready,
+    Failed,
+}
+
+/// Compiles render pipelines on background threads so frames keep rendering while shaders
+/// compile. Finished pipelines are collected with `poll`.
+#[derive(Debug, Default)]
+pub struct PipelineCompiler {
+    pending: HashMap<(PipelineId, PipelineTarget), Receiver<CompilationResult>>,
+    states: HashMap<PipelineId, PipelineCompileState>,
+    depth_prepass_pipelines: HashMap<PipelineId, RenderPipeline>,
+    multisampled_depth_prepass_pipelines: HashMap<PipelineId, RenderPipeline>,
+}
+
+type CompilationResult = Result<(RenderPipeline, Option<RenderPipeline>), PipelineError>;
+
+impl PipelineCompiler {
+    /// Does nothing if the pipeline is already being compiled for `render_format` and
+    /// `sample_count`. Screen-space pipelines are always single-sampled, since they draw into the
+    /// resolved frame.
+    pub fn compile(
+        &mut self,
+        device: &Device,
+        id: &PipelineId,
+        attachment_bind_group_layout: Option<&BindGroupLayout>,
+        target: &PipelineTarget,
+        depth_format: TextureFormat,
+        depth_prepass: bool,
+    ) {
+        let key = (id.clone(), target.clone());
+        if self.pending.contains_key(&key) {
+            return;
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let device = device.clone();
+        let id = id.clone();
+        let attachment_bind_group_layout = attachment_bind_group_layout.cloned();
+        let mut target = target.clone();
+        if id.is_screen_space {
+            target.sample_count = 1;
+        }
+        std::thread::spawn(move || {
+            let depth_prepass = depth_prepass && id.uses_depth_prepass();
+            let result = create_render_pipeline(
+                &device,
+                &id,
+                attachment_bind_group_layout.as_ref(),
+                &target,
+                depth_format,
+                depth_prepass,
+                id.spirv_vertex_shader,
+                id.spirv_fragment_shader,
+            )
+            .and_then(|pipeline| {
+                let depth_prepass_pipeline = depth_prepass
+                    .then(|| {
+                        create_depth_prepass_pipeline(
+                            &device,
+                            &id,
+                            attachment_bind_group_layout.as_ref(),
+                            depth_format,
+                            target.sample_count,
+                        )
+                    })
+                    .transpose()?;
+                Ok((pipeline, depth_prepass_pipeline))
+            });
+            let _ = sender.send(result);
+        });
+
+        self.states
+            .insert(key.0.clone(), PipelineCompileState::Compiling);
+        self.pending.insert(key, receiver);
+    }
+
+    /// Returns the pipelines that finished compiling since the last call, with the target they
+    /// were compiled for
+    pub fn poll(&mut self) -> Vec<CompiledPipeline> {
+        let mut finished = Vec::new();
+        self.pending.retain(|(id, target), receiver| {
+            let result = match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => Err(PipelineError {
+                    shader: None,
+                    message: String::from("The pipeline compilation thread panicked"),
+                }),
+            };
+            let result = result.map(|(pipeline, depth_prepass_pipeline)| {
+                if let Some(depth_prepass_pipeline) = depth_prepass_pipeline {
+                    let depth_prepass_pipelines = if target.sample_count > 1 {
+                        &mut self.multisampled_depth_prepass_pipelines
+                    } else {
+                        &mut self.depth_prepass_pipelines
+                    };
+                    depth_prepass_pipelines.insert(id.clone(), depth_prepass_pipeline);
+                }
+                pipeline
+            });
+            finished.push((id.clone(), target.clone(), result));
+            false
+        });
+
+        for (id, _, result) in &finished {
+            let still_compiling = self.pending.keys().any(|(pending_id, _)| pending_id == id);
+            let state = if result.is_err() {
+                PipelineCompileState::Failed
+            } else if still_compiling {
+                PipelineCompileState::Compiling
+            } else {
+                PipelineCompileState::Ready
+            };
+            self.states.insert(id.clone(), state);
+        }
+
+        finished
+    }
+
+    pub fn is_compiling(&self, id: &PipelineId, target: &PipelineTarget) -> bool {
+        self.pending.contains_key(&(id.clone(), target.clone()))
+    }
+
+    pub fn state(&self, id: &PipelineId) -> Option<PipelineCompileState> {
+        self.states.get(id).copied()
+    }
+
+    /// The depth only variants of the compiled pipelines, only filled when the depth pre-pass is
+    /// enabled. The multisampled ones are drawn into the MSAA depth texture.
+    pub fn depth_prepass_pipelines(
+        &self,
+        multisampled: bool,
+    ) -> &HashMap<PipelineId, RenderPipeline> {
+        if multisampled {
+            &self.multisampled_depth_prepass_pipelines
+        } else {
+            &self.depth_prepass_pipelines
+        }
+    }
+
+    /// The number of compilations that have not finished yet
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+/// Polls the files of `PipelineShader::Path` shaders on a background thread, so pipelines can be
+/// recompiled when their shaders are edited. The thread stops when the reloader is dropped.
+#[derive(Debug)]
+pub struct ShaderHotReloader {
+    watch_sender: Sender<&'static str>,
+    changed_receiver: Receiver<&'static str>,
+}
+
+impl ShaderHotReloader {
+    const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    pub fn new() -> Self {
+        let (watch_sender, watch_receiver) = mpsc::channel::<&'static str>();
+        let (changed_sender, changed_receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut watchers: HashMap<&'static str, FileWatcher> = HashMap::new();
+            loop {
+                loop {
+                    match watch_receiver.try_recv() {
+                        Ok(path) => {
+                            watchers
+                                .entry(path)
+                                .or_insert_with(|| FileWatcher::new(path));
+                        }
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Disconnected) => return,
+                    }
+                }
+                for (path, watcher) in &mut watchers {
+                    if watcher.has_changed() && changed_sender.send(*path).is_err() {
+                        return;
+                    }
+                }
+                std::thread::sleep(Self::POLL_INTERVAL);
+            }
+        });
+
+        Self {
+            watch_sender,
+            changed_receiver,
+        }
+    }
+
+    /// Starts watching the file, watching it more than once has no effect
+    pub fn watch(&self, path: &'static str) {
+        let _ = self.watch_sender.send(path);
+    }
+
+    /// The watched files that were modified since the last call
+    pub fn changed_paths(&self) -> Vec<&'static str> {
+        let mut changed: Vec<&'static str> = self.changed_receiver.try_iter().collect();
+        changed.sort_unstable();
+        changed.dedup();
+        changed
+    }
+}
+
+impl Default for ShaderHotReloader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Loads the shader like `load_shader_module_descriptor`, but also returns its compilation errors
+/// instead of handing them to the device's uncaptured error handler
+pub fn load_validated_shader_module(
+    device: &Device,
+    shader: &PipelineShader,
+    spirv: bool,
+) -> Result<wgpu::ShaderModule, PipelineError> {
+    let error_scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let module = load_shader_module_descriptor(device, shader, spirv);
+    let validation_error = pollster::block_on(error_scope.pop());
+
+    let message = match (module, validation_error) {
+        (Ok(module), None) => return Ok(module),
+        (Err(error), _) => error.to_string(),
+        (Ok(_), Some(error)) => error.to_string(),
+    };
+    Err(PipelineError {
+        shader: Some(shader.clone()),
+        message,
     })
 }
 
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use wgpu::{BindGroupLayout, RenderPipeline};
+
+use super::{
+    pipeline::{PipelineId, PipelineTarget},
+    rendering_management::RenderingManager,
+};
+
+/// The pipelines compiled for one `PipelineTarget`, keyed by `PipelineId`. Pipelines are compiled
+/// on a background thread by the `RenderingManager`, so a missing pipeline is only available once
+/// its compilation has been collected with `PipelineCache::insert`.
+#[derive(Debug)]
+pub struct PipelineCache {
+    target: PipelineTarget,
+    pipelines: HashMap<PipelineId, RenderPipeline>,
+}
+
+impl PipelineCache {
+    pub fn new(target: PipelineTarget) -> Self {
+        PipelineCache {
+            target,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    pub fn target(&self) -> &PipelineTarget {
+        &self.target
+    }
+
+    /// Drops every pipeline when the target changes, as they can't be drawn into the new one.
+    /// Returns whether the pipelines were dropped
+    pub fn set_target(&mut self, target: PipelineTarget) -> bool {
+        if self.target == target {
+            return false;
+        }
+        self.target = target;
+        self.pipelines.clear();
+        true
+    }
+
+    pub fn get(&self, id: &PipelineId) -> Option<&RenderPipeline> {
+        self.pipelines.get(id)
+    }
+
+    pub fn contains(&self, id: &PipelineId) -> bool {
+        self.pipelines.contains_key(id)
+    }
+
+    pub fn pipelines(&self) -> &HashMap<PipelineId, RenderPipeline> {
+        &self.pipelines
+    }
+
+    /// Returns the pipeline it replaces, e.g. after its shaders were reloaded
+    pub fn insert(&mut self, id: PipelineId, pipeline: RenderPipeline) -> Option<RenderPipeline> {
+        self.pipelines.insert(id, pipeline)
+    }
+
+    /// Returns the cached pipeline, or starts compiling it for the cache's target and returns
+    /// `None` until it is inserted. A pipeline that is already compiling is not compiled again.
+    pub fn get_or_create(
+        &self,
+        rendering_manager: &mut RenderingManager,
+        id: &PipelineId,
+        attachment_bind_group_layout: Option<&BindGroupLayout>,
+    ) -> Option<&RenderPipeline> {
+        if let Some(pipeline) = self.pipelines.get(id) {
+            return Some(pipeline);
+        }
+        if !rendering_manager.is_compiling_pipeline(id, &self.target) {
+            rendering_manager.compile_pipeline(id, attachment_bind_group_layout, &self.target);
+        }
+        None
+    }
+
+    /// Compiles the cached pipeline again, it stays in use until the new one is inserted
+    pub fn recompile(
+        &self,
+        rendering_manager: &mut RenderingManager,
+        id: &PipelineId,
+        attachment_bind_group_layout: Option<&BindGroupLayout>,
+    ) {
+        if self.pipelines.contains_key(id) {
+            rendering_manager.compile_pipeline(id, attachment_bind_group_layout, &self.target);
+        }
+    }
+}
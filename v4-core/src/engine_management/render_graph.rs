@@ -0,0 +1,194 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
+
+use wgpu::{CommandEncoder, Device, Queue, TextureFormat, TextureView};
+
+use crate::ecs::scene::Scene;
+
+/// A named texture that passes read from and write to. The built-in resources below are backed by
+/// the engine's textures, any other name only serves to order custom passes.
+pub type RenderResource = &'static str;
+
+/// The color target that scene geometry is drawn into, before antialiasing and screen-space effects
+pub const SCENE_COLOR: RenderResource = "scene_color";
+
+pub const SCENE_DEPTH: RenderResource = "scene_depth";
+
+/// The surface texture that is presented at the end of the frame
+pub const FRAME_OUTPUT: RenderResource = "frame_output";
+
+pub struct RenderPassContext<'a> {
+    pub device: &'a Device,
+    pub queue: &'a Queue,
+    pub encoder: &'a mut CommandEncoder,
+    pub scene: &'a Scene,
+    /// Views of the built-in resources that are available when the pass runs
+    pub resources: HashMap<RenderResource, &'a TextureView>,
+    pub format: TextureFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A pass registered with `RenderingManager::add_pass`.
+///
+/// Passes are ordered by the resources they declare. For every resource, the passes that only
+/// write it run first, then the passes that both read and write it in registration order, then
+/// the passes that only read it. The built-in geometry pass reads and writes `SCENE_COLOR` and
+/// `SCENE_DEPTH`, and loads them instead of clearing if another pass only writes them.
+pub trait RenderGraphPass: Debug {
+    fn name(&self) -> &str;
+
+    fn inputs(&self) -> Vec<RenderResource> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<RenderResource>;
+
+    fn record(&mut self, context: RenderPassContext);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BuiltinPass {
+    Geometry,
+    ScreenSpaceEffects,
+    Ui,
+}
+
+#[derive(Debug)]
+pub(crate) enum RenderGraphNode {
+    Builtin(BuiltinPass),
+    Custom(Box<dyn RenderGraphPass>),
+}
+
+impl RenderGraphNode {
+    fn name(&self) -> &str {
+        match self {
+            RenderGraphNode::Builtin(BuiltinPass::Geometry) => "Geometry",
+            RenderGraphNode::Builtin(BuiltinPass::ScreenSpaceEffects) => "Screen-space effects",
+            RenderGraphNode::Builtin(BuiltinPass::Ui) => "UI",
+            RenderGraphNode::Custom(pass) => pass.name(),
+        }
+    }
+
+    fn inputs(&self) -> Vec<RenderResource> {
+        match self {
+            RenderGraphNode::Builtin(BuiltinPass::Geometry) => vec![SCENE_COLOR, SCENE_DEPTH],
+            RenderGraphNode::Builtin(BuiltinPass::ScreenSpaceEffects) => vec![SCENE_COLOR],
+            RenderGraphNode::Builtin(BuiltinPass::Ui) => vec![FRAME_OUTPUT],
+            RenderGraphNode::Custom(pass) => pass.inputs(),
+        }
+    }
+
+    fn outputs(&self) -> Vec<RenderResource> {
+        match self {
+            RenderGraphNode::Builtin(BuiltinPass::Geometry) => vec![SCENE_COLOR, SCENE_DEPTH],
+            RenderGraphNode::Builtin(BuiltinPass::ScreenSpaceEffects) => vec![FRAME_OUTPUT],
+            RenderGraphNode::Builtin(BuiltinPass::Ui) => vec![FRAME_OUTPUT],
+            RenderGraphNode::Custom(pass) => pass.outputs(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RenderGraph {
+    nodes: Vec<RenderGraphNode>,
+    execution_order: Option<Vec<usize>>,
+}
+
+impl Default for RenderGraph {
+    fn default() -> Self {
+        Self {
+            nodes: vec![
+                RenderGraphNode::Builtin(BuiltinPass::Geometry),
+                RenderGraphNode::Builtin(BuiltinPass::ScreenSpaceEffects),
+                RenderGraphNode::Builtin(BuiltinPass::Ui),
+            ],
+            execution_order: None,
+        }
+    }
+}
+
+impl RenderGraph {
+    pub(crate) fn add_pass(&mut self, pass: Box<dyn RenderGraphPass>) {
+        self.nodes.push(RenderGraphNode::Custom(pass));
+        self.execution_order = None;
+    }
+
+    pub(crate) fn node_mut(&mut self, index: usize) -> &mut RenderGraphNode {
+        &mut self.nodes[index]
+    }
+
+    /// Whether a pass writes the resource without reading it, so the resource already holds this
+    /// frame's contents when its readers run
+    pub(crate) fn is_produced(&self, resource: RenderResource) -> bool {
+        self.nodes
+            .iter()
+            .any(|node| node.outputs().contains(&resource) && !node.inputs().contains(&resource))
+    }
+
+    pub(crate) fn execution_order(&mut self) -> Vec<usize> {
+        if self.execution_order.is_none() {
+            self.execution_order = Some(self.resolve_execution_order());
+        }
+        self.execution_order.clone().unwrap()
+    }
+
+    fn resolve_execution_order(&self) -> Vec<usize> {
+        let inputs: Vec<Vec<RenderResource>> =
+            self.nodes.iter().map(|node| node.inputs()).collect();
+        let outputs: Vec<Vec<RenderResource>> =
+            self.nodes.iter().map(|node| node.outputs()).collect();
+
+        let resources: HashSet<RenderResource> =
+            inputs.iter().chain(&outputs).flatten().copied().collect();
+
+        let mut dependencies: Vec<HashSet<usize>> = vec![HashSet::new(); self.nodes.len()];
+        for resource in resources {
+            let reads = |index: usize| inputs[index].contains(&resource);
+            let writes = |index: usize| outputs[index].contains(&resource);
+
+            let producers: Vec<usize> = (0..self.nodes.len())
+                .filter(|&index| writes(index) && !reads(index))
+                .collect();
+            let modifiers: Vec<usize> = (0..self.nodes.len())
+                .filter(|&index| writes(index) && reads(index))
+                .collect();
+            let consumers: Vec<usize> = (0..self.nodes.len())
+                .filter(|&index| reads(index) && !writes(index))
+                .collect();
+
+            for (position, &modifier) in modifiers.iter().enumerate() {
+                dependencies[modifier].extend(&producers);
+                dependencies[modifier].extend(&modifiers[..position]);
+            }
+            for &consumer in &consumers {
+                dependencies[consumer].extend(&producers);
+                dependencies[consumer].extend(&modifiers);
+            }
+        }
+
+        // Passes without an ordering constraint between them keep their registration order
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut scheduled = vec![false; self.nodes.len()];
+        while order.len() < self.nodes.len() {
+            let Some(next) = (0..self.nodes.len()).find(|&index| {
+                !scheduled[index]
+                    && dependencies[index]
+                        .iter()
+                        .all(|dependency| scheduled[*dependency])
+            }) else {
+                let remaining: Vec<&str> = (0..self.nodes.len())
+                    .filter(|&index| !scheduled[index])
+                    .map(|index| self.nodes[index].name())
+                    .collect();
+                panic!("The render graph has a cycle between the passes {remaining:?}.");
+            };
+            scheduled[next] = true;
+            order.push(next);
+        }
+
+        order
+    }
+}
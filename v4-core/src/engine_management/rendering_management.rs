@@ -1,25 +1,39 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::{collections::HashMap, fmt::Debug, sync::Mutex};
 
 use egui::TexturesDelta;
 use egui_wgpu_backend::{RenderPass as EguiRenderPass, ScreenDescriptor};
 use egui_winit_platform::Platform;
+use futures::channel::oneshot;
+use image::RgbaImage;
 use smaa::SmaaTarget;
 use wgpu::{
-    Adapter, BindGroup, Buffer, CommandEncoder, Device, Instance, Queue, RenderPipeline, Texture,
-    TextureFormat, TextureUsages, TextureView,
+    Adapter, BindGroup, BindGroupLayout, Buffer, CommandEncoder, Device, Instance, Queue,
+    RenderPipeline, Sampler, Texture, TextureFormat, TextureUsages, TextureView,
     rwh::{HasDisplayHandle, HasWindowHandle},
     util::DeviceExt,
 };
 use winit::{dpi::PhysicalSize, window::Window};
 
 use crate::{
+    EngineDetails,
     ecs::{
         component::{Component, ComponentDetails, ComponentSystem},
         compute::Compute,
-        scene::Scene,
+        material::{Material, ScreenSpaceInput},
+        scene::{Background, DebugLineVertex, Scene},
     },
-    engine_management::pipeline::{PipelineId, create_render_pipeline},
-    engine_support::texture_support,
+    engine_management::{
+        pipeline::{
+            CompiledPipeline, PipelineCompileState, PipelineCompiler, PipelineError, PipelineId,
+            PipelineTarget, ShaderHotReloader, create_render_pipeline,
+            screen_space_bind_group_layout_entries,
+        },
+        render_graph::{
+            BuiltinPass, FRAME_OUTPUT, RenderGraph, RenderGraphNode, RenderGraphPass,
+            RenderPassContext, SCENE_COLOR, SCENE_DEPTH,
+        },
+    },
+    engine_support::{buffer_pool::BufferPool, misc_utils, texture_support},
 };
 
 use super::font_management::FontState;
@@ -30,20 +44,49 @@ pub struct RenderingManager {
     adapter: Adapter,
     device: Device,
     queue: Queue,
+    adapter_info: wgpu::AdapterInfo,
+    features: wgpu::Features,
+    limits: wgpu::Limits,
     width: u32,
     height: u32,
     clear_color: wgpu::Color,
     antialiasing_enabled: bool,
+    msaa_samples: u32,
+    gbuffer_formats: Vec<TextureFormat>,
+    depth_format: TextureFormat,
+    screen_space_filter_mode: wgpu::FilterMode,
+    surface_view_formats: Vec<TextureFormat>,
+    design_resolution: Option<(u32, u32)>,
+    present_mode: wgpu::PresentMode,
+    depth_prepass: bool,
+    shader_hot_reloader: Option<ShaderHotReloader>,
+    buffer_pool: BufferPool,
+    shader_error: Option<String>,
+    debug_draw_enabled: bool,
+    pipeline_compiler: PipelineCompiler,
+    pipeline_errors: HashMap<PipelineId, PipelineError>,
+    render_graph: RenderGraph,
+    frame_captures: Mutex<Vec<oneshot::Sender<RgbaImage>>>,
     surface_data: Option<SurfaceData>,
 }
 
 pub struct SurfaceData {
     surface: wgpu::Surface<'static>,
     format: wgpu::TextureFormat,
+    ui_format: wgpu::TextureFormat,
     config: wgpu::SurfaceConfiguration,
     smaa_target: SmaaTarget,
     depth_texture: texture_support::CompleteTexture,
+    /// Set while the depth texture holds nothing to load, when it was just created or resized.
+    /// The main pass then clears it even if the scene's depth load op is `LoadOp::Load`.
+    depth_needs_clear: bool,
+    /// Only created when MSAA is enabled
+    multisampled_targets: Option<MultisampledTargets>,
+    /// Drawn alongside the scene color by the main pass, one for each of the G-buffer formats
+    gbuffer_views: Vec<TextureView>,
     screen_space_attachments: ScreenSpaceAttachments,
+    background_renderer: BackgroundRenderer,
+    debug_line_renderer: DebugLineRenderer,
     egui_render_pass: EguiRenderPass,
     egui_screen_descriptor: ScreenDescriptor,
 }
@@ -53,10 +96,15 @@ impl Debug for SurfaceData {
         f.debug_struct("SurfaceData")
             .field("surface", &self.surface)
             .field("format", &self.format)
+            .field("ui_format", &self.ui_format)
             .field("config", &self.config)
             .field("smaa_target", &"smaa_target")
             .field("depth_texture", &self.depth_texture)
+            .field("multisampled_targets", &self.multisampled_targets)
+            .field("gbuffer_views", &self.gbuffer_views)
             .field("screen_space_attachments", &self.screen_space_attachments)
+            .field("background_renderer", &self.background_renderer)
+            .field("debug_line_renderer", &self.debug_line_renderer)
             .finish()
     }
 }
@@ -64,10 +112,19 @@ impl Debug for SurfaceData {
 #[derive(Debug, Clone)]
 pub struct RenderingManagerDetails {
     pub antialiasing_enabled: bool,
+    pub msaa_samples: u32,
+    pub gbuffer_formats: Vec<TextureFormat>,
     pub clear_color: wgpu::Color,
     pub features: wgpu::Features,
     pub limits: wgpu::Limits,
     pub backends: wgpu::Backends,
+    pub depth_format: TextureFormat,
+    pub screen_space_filter_mode: wgpu::FilterMode,
+    pub surface_view_formats: Vec<TextureFormat>,
+    pub design_resolution: Option<(u32, u32)>,
+    pub present_mode: wgpu::PresentMode,
+    pub depth_prepass: bool,
+    pub shader_hot_reload: bool,
 }
 
 impl RenderingManager {
@@ -75,14 +132,51 @@ impl RenderingManager {
         window_size: PhysicalSize<u32>,
         RenderingManagerDetails {
             antialiasing_enabled,
+            msaa_samples,
+            gbuffer_formats,
             clear_color,
             features,
             limits,
             backends,
+            depth_format,
+            screen_space_filter_mode,
+            surface_view_formats,
+            design_resolution,
+            present_mode,
+            depth_prepass,
+            shader_hot_reload,
         }: RenderingManagerDetails,
     ) -> Self {
+        assert!(
+            depth_format.has_depth_aspect(),
+            "The depth format {depth_format:?} has no depth aspect."
+        );
+        assert!(
+            matches!(msaa_samples, 1 | 2 | 4 | 8),
+            "The MSAA sample count must be 1, 2, 4 or 8, got {msaa_samples}."
+        );
+        assert!(
+            !(antialiasing_enabled && msaa_samples > 1),
+            "MSAA and SMAA are mutually exclusive, enable only one of them."
+        );
+        assert!(
+            gbuffer_formats.len() <= 3,
+            "At most 3 G-buffer formats are supported, got {}.",
+            gbuffer_formats.len()
+        );
+        assert!(
+            gbuffer_formats.is_empty() || msaa_samples == 1,
+            "The G-buffer can't be combined with MSAA."
+        );
+
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
             backends,
+            backend_options: wgpu::BackendOptions {
+                noop: wgpu::NoopBackendOptions {
+                    enable: backends == wgpu::Backends::NOOP,
+                },
+                ..Default::default()
+            },
             ..Default::default()
         });
 
@@ -107,15 +201,38 @@ impl RenderingManager {
             .unwrap();
         let (width, height): (u32, u32) = window_size.into();
 
+        let adapter_info = adapter.get_info();
+        let features = device.features();
+        let limits = device.limits();
+
         RenderingManager {
             instance,
             adapter,
+            adapter_info,
+            features,
+            limits,
             width,
             height,
             device,
             queue,
             clear_color,
             antialiasing_enabled,
+            msaa_samples,
+            gbuffer_formats,
+            depth_format,
+            screen_space_filter_mode,
+            surface_view_formats,
+            design_resolution,
+            present_mode,
+            depth_prepass,
+            shader_hot_reloader: shader_hot_reload.then(ShaderHotReloader::new),
+            buffer_pool: BufferPool::default(),
+            shader_error: None,
+            debug_draw_enabled: false,
+            pipeline_compiler: PipelineCompiler::default(),
+            pipeline_errors: HashMap::new(),
+            render_graph: RenderGraph::default(),
+            frame_captures: Mutex::new(Vec::new()),
             surface_data: None,
         }
     }
@@ -139,39 +256,124 @@ impl RenderingManager {
             .find(|f| f.is_srgb())
             .unwrap_or(surface_caps.formats[0]);
 
+        // Surfaces can only be viewed as their sRGB or linear twin
+        let view_formats: Vec<TextureFormat> = self
+            .surface_view_formats
+            .iter()
+            .copied()
+            .filter(|view_format| {
+                let compatible = *view_format != format
+                    && view_format.remove_srgb_suffix() == format.remove_srgb_suffix();
+                if !compatible {
+                    log::warn!(
+                        "Ignoring surface view format {view_format:?}, it is not compatible with the surface format {format:?}."
+                    );
+                }
+                compatible
+            })
+            .collect();
+
+        // egui outputs gamma-encoded colors, so it draws through the linear twin when one is available
+        let ui_format = view_formats
+            .iter()
+            .copied()
+            .find(|view_format| format.is_srgb() && !view_format.is_srgb())
+            .unwrap_or(format);
+
+        // The automatic modes are always supported, wgpu picks a concrete mode for them
+        let present_mode = match self.present_mode {
+            wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync => self.present_mode,
+            present_mode if surface_caps.present_modes.contains(&present_mode) => present_mode,
+            present_mode => {
+                log::warn!(
+                    "Present mode {present_mode:?} is not supported by the surface, falling back to AutoVsync."
+                );
+                wgpu::PresentMode::AutoVsync
+            }
+        };
+
+        // Frame captures copy out of the surface texture, which not every surface allows
+        let usage =
+            TextureUsages::RENDER_ATTACHMENT | (surface_caps.usages & TextureUsages::COPY_SRC);
+
         let config = wgpu::SurfaceConfiguration {
-            usage: TextureUsages::RENDER_ATTACHMENT,
+            usage,
             format,
             width: self.width,
             height: self.height,
-            present_mode: wgpu::PresentMode::AutoNoVsync,
+            present_mode,
             desired_maximum_frame_latency: 2,
             alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
+            view_formats,
         };
 
         surface.configure(&self.device, &config);
 
-        let depth_texture =
-            texture_support::TextureBundle::create_depth_texture(&self.device, &config);
+        // Sample counts other than 4 depend on the adapter and the format
+        if self.msaa_samples > 1
+            && ![format, self.depth_format].iter().all(|texture_format| {
+                self.msaa_samples == 4
+                    || (self
+                        .features
+                        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+                        && self
+                            .adapter
+                            .get_texture_format_features(*texture_format)
+                            .flags
+                            .sample_count_supported(self.msaa_samples))
+            })
+        {
+            log::warn!(
+                "{}x MSAA is not supported for the surface and depth formats, falling back to 4x.",
+                self.msaa_samples
+            );
+            self.msaa_samples = 4;
+        }
 
-        let smaa_target = SmaaTarget::new(
+        let (render_width, render_height) = self.render_size();
+        let depth_texture = texture_support::TextureBundle::create_sized_depth_texture(
             &self.device,
-            &self.queue,
-            self.width,
-            self.height,
-            format,
-            if self.antialiasing_enabled {
-                smaa::SmaaMode::Smaa1X
-            } else {
-                smaa::SmaaMode::Disabled
-            },
+            render_width,
+            render_height,
+            self.depth_format,
+        );
+        let multisampled_targets = (self.msaa_samples > 1).then(|| {
+            MultisampledTargets::new(
+                &self.device,
+                render_width,
+                render_height,
+                format,
+                self.depth_format,
+                self.msaa_samples,
+            )
+        });
+
+        let gbuffer_views = Self::create_gbuffer_views(
+            &self.device,
+            &self.gbuffer_formats,
+            render_width,
+            render_height,
+        );
+
+        let smaa_target = self.create_smaa_target(format);
+
+        let pipeline_target = self.pipeline_target(format);
+        let screen_space_attachments = ScreenSpaceAttachments::new(
+            &self.device,
+            render_width,
+            render_height,
+            &pipeline_target,
+            gbuffer_views.clone(),
+            &depth_texture.0,
+            self.screen_space_filter_mode,
         );
 
-        let screen_space_attachments =
-            ScreenSpaceAttachments::new(&self.device, self.width, self.height, format);
+        let background_renderer =
+            BackgroundRenderer::new(&self.device, &pipeline_target, self.depth_format);
+        let debug_line_renderer =
+            DebugLineRenderer::new(&self.device, &pipeline_target, self.depth_format);
 
-        let egui_render_pass = EguiRenderPass::new(&self.device, format, 1);
+        let egui_render_pass = EguiRenderPass::new(&self.device, ui_format, 1);
         let egui_screen_descriptor = ScreenDescriptor {
             physical_width: self.width,
             physical_height: self.height,
@@ -181,10 +383,16 @@ impl RenderingManager {
         self.surface_data = Some(SurfaceData {
             surface,
             format,
+            ui_format,
             config,
             smaa_target,
             depth_texture,
+            depth_needs_clear: true,
+            multisampled_targets,
+            gbuffer_views,
             screen_space_attachments,
+            background_renderer,
+            debug_line_renderer,
             egui_render_pass,
             egui_screen_descriptor,
         });
@@ -198,11 +406,21 @@ impl RenderingManager {
         egui_platform: &mut Platform,
         window: Option<&dyn Window>,
         egui_clear_color: Option<wgpu::Color>,
+        engine_details: &EngineDetails,
+        render_target_pipelines: &HashMap<PipelineId, RenderPipeline>,
     ) {
-        let screen_space_materials = scene.screen_space_materials();
+        let has_screen_space_effects = !scene.screen_space_materials().is_empty();
+        // A fixed design resolution is rendered offscreen and composited like screen-space effects
+        let needs_composite = has_screen_space_effects || self.design_resolution.is_some();
+        let (render_width, render_height) = self.render_size();
+        let letterbox_viewport = self.letterbox_viewport();
+        let clear_color = self.scene_clear_color(scene);
+        let scene_viewport = scene
+            .viewport()
+            .map(|viewport| Self::clamp_viewport(viewport, (render_width, render_height)));
         let surface_data = self.surface_data.as_mut().unwrap();
         let output = surface_data.surface.get_current_texture().unwrap();
-        let raw_render_tex = if screen_space_materials.is_empty() {
+        let raw_render_tex = if !needs_composite {
             &output.texture
         } else {
             &self.device.create_texture(&wgpu::TextureDescriptor {
@@ -211,8 +429,8 @@ impl RenderingManager {
                     scene.scene_index()
                 )),
                 size: wgpu::Extent3d {
-                    width: self.width,
-                    height: self.height,
+                    width: render_width,
+                    height: render_height,
                     depth_or_array_layers: 1,
                 },
                 mip_level_count: 1,
@@ -228,187 +446,465 @@ impl RenderingManager {
 
         let view = raw_render_tex.create_view(&wgpu::TextureViewDescriptor::default());
 
+        let output_view = if !needs_composite {
+            view.clone()
+        } else {
+            output
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        let ui_view = if surface_data.ui_format == surface_data.format {
+            output_view.clone()
+        } else {
+            output.texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("UI output view"),
+                format: Some(surface_data.ui_format),
+                ..Default::default()
+            })
+        };
+
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render encoder"),
             });
 
-        let smaa_frame = surface_data
-            .smaa_target
-            .start_frame(&self.device, &self.queue, &view);
+        let mut smaa_frame = Some(surface_data.smaa_target.start_frame(
+            &self.device,
+            &self.queue,
+            &view,
+        ));
 
-        let all_components = scene.all_components();
+        let pipeline_target = PipelineTarget {
+            render_format: surface_data.format,
+            gbuffer_formats: self.gbuffer_formats.clone(),
+            sample_count: self.msaa_samples,
+        };
+        scene.record_render_bundles(
+            &self.device,
+            &self.queue,
+            pipelines,
+            &pipeline_target,
+            self.depth_format,
+        );
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Main render pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &smaa_frame,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(self.clear_color),
-                        store: wgpu::StoreOp::Store,
-                    },
-                    depth_slice: None,
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: surface_data.depth_texture.1.view(),
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
-                }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-                multiview_mask: None,
-            });
+        let scene_color_produced = self.render_graph.is_produced(SCENE_COLOR);
+        let scene_depth_produced = self.render_graph.is_produced(SCENE_DEPTH);
 
-            let mut sorted_pipelines: Vec<(&PipelineId, &RenderPipeline)> =
-                Vec::from_iter(pipelines);
-            sorted_pipelines.sort_by(|(a, _), (b, _)| a.render_priority.cmp(&b.render_priority));
+        let mut tdelta_option = None;
 
-            for (pipeline_id, pipeline) in sorted_pipelines {
-                if pipeline_id.is_screen_space {
-                    continue;
-                }
-                render_pass.set_pipeline(pipeline);
-                let materials_for_pipeline = scene.get_pipeline_materials(pipeline_id);
-                for material in materials_for_pipeline
-                    .iter()
-                    .filter(|mat| scene.is_component_enabled(**mat))
-                {
-                    if material.uses_camera() {
-                        render_pass.set_bind_group(
-                            0,
-                            scene
-                                .active_camera_bind_group()
-                                .expect("No active camera buffer set"),
-                            &[],
+        for pass_index in self.render_graph.execution_order() {
+            match self.render_graph.node_mut(pass_index) {
+                RenderGraphNode::Builtin(BuiltinPass::Geometry) => {
+                    let all_components = scene.all_components();
+
+                    for render_target in scene.render_targets() {
+                        let Some(camera_bind_group) = render_target.camera_bind_group() else {
+                            continue;
+                        };
+                        assert_eq!(
+                            render_target.depth_texture().properties().format,
+                            self.depth_format,
+                            "Render targets must use the engine's depth format."
+                        );
+
+                        if self.depth_prepass {
+                            let mut depth_prepass = Self::begin_depth_prepass(
+                                &mut encoder,
+                                render_target.depth_texture().view(),
+                                wgpu::LoadOp::Clear(1.0),
+                                self.depth_format,
+                            );
+                            Self::draw_scene_geometry(
+                                &self.device,
+                                &self.queue,
+                                &mut depth_prepass,
+                                scene,
+                                self.pipeline_compiler.depth_prepass_pipelines(false),
+                                Some(camera_bind_group),
+                                Some(render_target.texture()),
+                                &all_components,
+                                false,
+                            );
+                        }
+
+                        let mut render_pass =
+                            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                                label: Some(&format!("Render target {} pass", render_target.id())),
+                                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                                    view: render_target.texture_bundle().view(),
+                                    resolve_target: None,
+                                    ops: wgpu::Operations {
+                                        load: wgpu::LoadOp::Clear(render_target.clear_color()),
+                                        store: wgpu::StoreOp::Store,
+                                    },
+                                    depth_slice: None,
+                                })],
+                                depth_stencil_attachment: Some(Self::depth_stencil_attachment(
+                                    render_target.depth_texture().view(),
+                                    if self.depth_prepass {
+                                        wgpu::LoadOp::Load
+                                    } else {
+                                        wgpu::LoadOp::Clear(1.0)
+                                    },
+                                    self.depth_format,
+                                )),
+                                timestamp_writes: None,
+                                occlusion_query_set: None,
+                                multiview_mask: None,
+                            });
+
+                        Self::draw_scene_geometry(
+                            &self.device,
+                            &self.queue,
+                            &mut render_pass,
+                            scene,
+                            render_target_pipelines,
+                            Some(camera_bind_group),
+                            Some(render_target.texture()),
+                            &all_components,
+                            true,
                         );
                     }
 
-                    if pipeline_id.immediate_size != 0 {
-                        render_pass.set_immediates(0, material.get_immediate_data());
+                    // With MSAA the geometry is drawn into the multisampled targets and resolved
+                    // into the scene color. They only hold this pass, so they can't load what
+                    // other passes rendered.
+                    let multisampled_targets = surface_data.multisampled_targets.as_ref();
+                    let depth_view = match multisampled_targets {
+                        Some(targets) => &targets.depth_view,
+                        None => surface_data.depth_texture.1.view(),
+                    };
+                    let mut depth_load_op = Self::geometry_depth_load_op(
+                        scene.depth_load_op(),
+                        scene_depth_produced && multisampled_targets.is_none(),
+                        &mut surface_data.depth_needs_clear,
+                    );
+                    if self.depth_prepass {
+                        let mut depth_prepass = Self::begin_depth_prepass(
+                            &mut encoder,
+                            depth_view,
+                            depth_load_op,
+                            self.depth_format,
+                        );
+                        // The pre-pass depth only matches the main pass with the same viewport
+                        if let Some(viewport) = scene_viewport {
+                            Self::set_pass_viewport(&mut depth_prepass, viewport);
+                        }
+                        Self::draw_scene_geometry(
+                            &self.device,
+                            &self.queue,
+                            &mut depth_prepass,
+                            scene,
+                            self.pipeline_compiler
+                                .depth_prepass_pipelines(multisampled_targets.is_some()),
+                            scene.active_camera_bind_group(),
+                            None,
+                            &all_components,
+                            false,
+                        );
+                        depth_load_op = wgpu::LoadOp::Load;
+                    }
+                    let background = scene.background();
+                    if let Some(Background::Gradient { top, bottom }) = background {
+                        surface_data
+                            .background_renderer
+                            .update_gradient(&self.queue, top, bottom);
                     }
 
-                    material.render(&self.device, &self.queue, &mut render_pass, &all_components);
-                }
-            }
-        }
+                    {
+                        let scene_color = smaa_frame
+                            .as_deref()
+                            .expect("The scene color was already resolved.");
+                        let color_attachments: Vec<_> =
+                            std::iter::once(Some(Self::scene_color_attachment(
+                                multisampled_targets,
+                                scene_color,
+                                scene_color_produced,
+                                clear_color,
+                            )))
+                            .chain(surface_data.gbuffer_views.iter().map(|gbuffer_view| {
+                                Some(wgpu::RenderPassColorAttachment {
+                                    view: gbuffer_view,
+                                    resolve_target: None,
+                                    ops: wgpu::Operations {
+                                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                                        store: wgpu::StoreOp::Store,
+                                    },
+                                    depth_slice: None,
+                                })
+                            }))
+                            .collect();
+                        let mut render_pass =
+                            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                                label: Some("Main render pass"),
+                                color_attachments: &color_attachments,
+                                depth_stencil_attachment: Some(Self::depth_stencil_attachment(
+                                    depth_view,
+                                    depth_load_op,
+                                    self.depth_format,
+                                )),
+                                timestamp_writes: None,
+                                occlusion_query_set: None,
+                                multiview_mask: None,
+                            });
+
+                        if let Some(viewport) = scene_viewport {
+                            Self::set_pass_viewport(&mut render_pass, viewport);
+                        }
+
+                        if let Some(Background::Gradient { .. }) = background {
+                            surface_data
+                                .background_renderer
+                                .render_gradient(&mut render_pass);
+                        }
+
+                        Self::draw_scene_geometry(
+                            &self.device,
+                            &self.queue,
+                            &mut render_pass,
+                            scene,
+                            pipelines,
+                            scene.active_camera_bind_group(),
+                            None,
+                            &all_components,
+                            true,
+                        );
 
-        for material in scene.materials().iter().filter(|mat| mat.is_enabled()) {
-            material.command_encoder_operations(
-                &self.device,
-                &self.queue,
-                &mut encoder,
-                &all_components,
-                scene.materials(),
-                scene.computes(),
-            );
-        }
+                        if let (true, Some(camera_bind_group)) =
+                            (self.debug_draw_enabled, scene.active_camera_bind_group())
+                        {
+                            surface_data.debug_line_renderer.render(
+                                &self.device,
+                                &self.queue,
+                                &mut render_pass,
+                                scene.debug_lines(),
+                                camera_bind_group,
+                            );
+                        }
+                    }
 
-        smaa_frame.resolve();
+                    for component in all_components
+                        .iter()
+                        .filter(|comp| scene.is_component_enabled(comp.as_ref()))
+                    {
+                        component.command_encoder_operations(
+                            &self.device,
+                            &self.queue,
+                            &mut encoder,
+                            &all_components,
+                            scene.materials(),
+                            scene.computes(),
+                        );
+                    }
+                }
+                RenderGraphNode::Builtin(BuiltinPass::ScreenSpaceEffects) => {
+                    if let Some(smaa_frame) = smaa_frame.take() {
+                        smaa_frame.resolve();
+                    }
 
-        let output_view = if screen_space_materials.is_empty() {
-            view
-        } else {
-            output
-                .texture
-                .create_view(&wgpu::TextureViewDescriptor::default())
-        };
+                    if needs_composite {
+                        surface_data.screen_space_attachments.update_uniforms(
+                            &self.queue,
+                            ScreenSpaceUniforms {
+                                time: engine_details.initialization_time.elapsed().as_secs_f32(),
+                                _padding: 0.0,
+                                resolution: [render_width as f32, render_height as f32],
+                                mouse: [
+                                    engine_details.cursor_position.0 as f32,
+                                    engine_details.cursor_position.1 as f32,
+                                ],
+                                _end_padding: [0.0; 2],
+                                viewport: scene_viewport.unwrap_or([
+                                    0.0,
+                                    0.0,
+                                    render_width as f32,
+                                    render_height as f32,
+                                ]),
+                            },
+                        );
+                        Self::render_screen_space_effects(
+                            scene,
+                            render_width,
+                            render_height,
+                            letterbox_viewport,
+                            pipelines,
+                            &output_view,
+                            &self.device,
+                            raw_render_tex,
+                            surface_data.format,
+                            &surface_data.screen_space_attachments,
+                            &mut encoder,
+                        );
+                    }
+                }
+                RenderGraphNode::Builtin(BuiltinPass::Ui) => {
+                    let shader_error_buffer = self.shader_error.as_ref().map(|error| {
+                        let mut buffer = glyphon::Buffer::new(
+                            &mut font_state.font_system,
+                            glyphon::Metrics::new(16.0, 20.0),
+                        );
+                        buffer.set_size(
+                            &mut font_state.font_system,
+                            Some(self.width as f32),
+                            Some(self.height as f32),
+                        );
+                        buffer.set_text(
+                            &mut font_state.font_system,
+                            &format!("Shader error:\n{error}"),
+                            &glyphon::Attrs::new().family(glyphon::Family::Monospace),
+                            glyphon::Shaping::Advanced,
+                            None,
+                        );
+                        buffer
+                    });
+
+                    let pending_pipelines = self.pipeline_compiler.pending_count();
+                    let loading_buffer = (pending_pipelines > 0).then(|| {
+                        let mut buffer = glyphon::Buffer::new(
+                            &mut font_state.font_system,
+                            glyphon::Metrics::new(16.0, 20.0),
+                        );
+                        buffer.set_size(
+                            &mut font_state.font_system,
+                            Some(self.width as f32),
+                            Some(20.0),
+                        );
+                        buffer.set_text(
+                            &mut font_state.font_system,
+                            &format!("Compiling shaders ({pending_pipelines} remaining)"),
+                            &glyphon::Attrs::new(),
+                            glyphon::Shaping::Advanced,
+                            None,
+                        );
+                        buffer
+                    });
+
+                    let enabled_ui_components = scene.enabled_ui_components();
+                    let text_areas =
+                        FontState::text_areas(&font_state.text_buffers, &enabled_ui_components)
+                            .chain(
+                                shader_error_buffer
+                                    .as_ref()
+                                    .map(|buffer| glyphon::TextArea {
+                                        buffer,
+                                        left: 8.0,
+                                        top: 8.0,
+                                        scale: 1.0,
+                                        bounds: glyphon::TextBounds::default(),
+                                        default_color: glyphon::Color::rgb(255, 80, 80),
+                                        custom_glyphs: &[],
+                                    }),
+                            )
+                            .chain(loading_buffer.as_ref().map(|buffer| glyphon::TextArea {
+                                buffer,
+                                left: 8.0,
+                                top: self.height as f32 - 28.0,
+                                scale: 1.0,
+                                bounds: glyphon::TextBounds::default(),
+                                default_color: glyphon::Color::rgb(255, 255, 255),
+                                custom_glyphs: &[],
+                            }))
+                            .collect::<Vec<_>>();
+
+                    font_state
+                        .text_renderer
+                        .prepare(
+                            &self.device,
+                            &self.queue,
+                            &mut font_state.font_system,
+                            &mut font_state.atlas,
+                            &font_state.viewport,
+                            text_areas,
+                            &mut font_state.swash_cache,
+                        )
+                        .expect("Failed to prepare text for rendering.");
+
+                    {
+                        let mut ui_render_pass =
+                            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                                label: Some("UI Render Pass"),
+                                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                                    view: &output_view,
+                                    resolve_target: None,
+                                    ops: wgpu::Operations {
+                                        load: wgpu::LoadOp::Load,
+                                        store: wgpu::StoreOp::Store,
+                                    },
+                                    depth_slice: None,
+                                })],
+                                depth_stencil_attachment: None,
+                                timestamp_writes: None,
+                                occlusion_query_set: None,
+                                multiview_mask: None,
+                            });
+                        font_state
+                            .text_renderer
+                            .render(&font_state.atlas, &font_state.viewport, &mut ui_render_pass)
+                            .expect("Failed to render text.");
+                    }
+                    if !enabled_ui_components.is_empty() {
+                        let all_comps = scene.all_components_mut();
+                        let ui_components: Vec<&mut Component> = all_comps
+                            .into_iter()
+                            .filter(|comp| enabled_ui_components.contains(&comp.id()))
+                            .collect();
+                        tdelta_option = Some(Self::egui_render(
+                            ui_components,
+                            egui_platform,
+                            &mut surface_data.egui_render_pass,
+                            &surface_data.egui_screen_descriptor,
+                            window.as_deref(),
+                            &self.device,
+                            &self.queue,
+                            &mut encoder,
+                            &ui_view,
+                            egui_clear_color,
+                        ));
+                    }
+                }
+                RenderGraphNode::Custom(pass) => {
+                    let mut resources = HashMap::from([
+                        (SCENE_DEPTH, surface_data.depth_texture.1.view()),
+                        (FRAME_OUTPUT, &output_view),
+                    ]);
+                    if let Some(scene_color) = smaa_frame.as_deref() {
+                        resources.insert(SCENE_COLOR, scene_color);
+                    }
 
-        if !screen_space_materials.is_empty() {
-            Self::render_screen_space_effects(
-                scene,
-                self.width,
-                self.height,
-                pipelines,
-                &output_view,
-                &self.device,
-                raw_render_tex,
-                surface_data,
-                &mut encoder,
-            );
+                    pass.record(RenderPassContext {
+                        device: &self.device,
+                        queue: &self.queue,
+                        encoder: &mut encoder,
+                        scene,
+                        resources,
+                        format: surface_data.format,
+                        width: self.width,
+                        height: self.height,
+                    });
+                }
+            }
         }
 
-        let enabled_ui_components = scene.enabled_ui_components();
-        let text_areas = font_state
-            .text_buffers
-            .iter()
-            .filter(|(id, _)| enabled_ui_components.contains(id))
-            .map(|(_, data)| glyphon::TextArea {
-                buffer: &data.buffer,
-                left: data.top_left_pos[0],
-                top: data.top_left_pos[1],
-                scale: data.scale,
-                bounds: data.bounds,
-                default_color: data.attributes.color,
-                custom_glyphs: &[],
-            })
-            .collect::<Vec<_>>();
+        if let Some(smaa_frame) = smaa_frame.take() {
+            smaa_frame.resolve();
+        }
 
-        font_state
-            .text_renderer
-            .prepare(
-                &self.device,
-                &self.queue,
-                &mut font_state.font_system,
-                &mut font_state.atlas,
-                &font_state.viewport,
-                text_areas,
-                &mut font_state.swash_cache,
-            )
-            .expect("Failed to prepare text for rendering.");
+        self.queue.submit(Some(encoder.finish()));
 
-        {
-            let mut ui_render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("UI Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &output_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: wgpu::StoreOp::Store,
-                    },
-                    depth_slice: None,
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-                multiview_mask: None,
-            });
-            font_state
-                .text_renderer
-                .render(&font_state.atlas, &font_state.viewport, &mut ui_render_pass)
-                .expect("Failed to render text.");
-        }
-        let tdelta_option = if !enabled_ui_components.is_empty() {
-            let all_comps = scene.all_components_mut();
-            let ui_components: Vec<&mut Component> = all_comps
-                .into_iter()
-                .filter(|comp| enabled_ui_components.contains(&comp.id()))
-                .collect();
-            Some(Self::egui_render(
-                ui_components,
-                egui_platform,
-                &mut surface_data.egui_render_pass,
-                &surface_data.egui_screen_descriptor,
-                window.as_deref(),
-                &self.device,
-                &self.queue,
-                &mut encoder,
-                &output_view,
-                egui_clear_color
-            ))
-        } else {
-            None
-        };
+        let frame_captures = std::mem::take(&mut *self.frame_captures.lock().unwrap());
+        if !frame_captures.is_empty() {
+            let frame = Self::read_frame(&self.device, &self.queue, &output.texture);
+            for capture in frame_captures {
+                let _ = capture.send(frame.clone());
+            }
+        }
 
-        self.queue.submit(Some(encoder.finish()));
         output.present();
 
+        self.buffer_pool.reset();
+
         if let Some(tdelta) = tdelta_option {
             surface_data
                 .egui_render_pass
@@ -421,24 +917,31 @@ impl RenderingManager {
         scene: &Scene,
         width: u32,
         height: u32,
+        letterbox_viewport: Option<[f32; 4]>,
         pipelines: &HashMap<PipelineId, RenderPipeline>,
         output_view: &TextureView,
         device: &Device,
         raw_render_tex: &Texture,
-        surface_data: &SurfaceData,
+        format: TextureFormat,
+        screen_space_attachments: &ScreenSpaceAttachments,
         encoder: &mut CommandEncoder,
     ) {
+        let extent = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
         encoder.copy_texture_to_texture(
             raw_render_tex.as_image_copy(),
-            surface_data
-                .screen_space_attachments
+            screen_space_attachments
                 .screen_space_input_texture
                 .as_image_copy(),
-            wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
+            extent,
+        );
+        encoder.copy_texture_to_texture(
+            raw_render_tex.as_image_copy(),
+            screen_space_attachments.original_texture.as_image_copy(),
+            extent,
         );
         let screen_space_output = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Screen space effect output texture"),
@@ -450,7 +953,7 @@ impl RenderingManager {
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: surface_data.format,
+            format,
             usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
             view_formats: &[],
         });
@@ -458,37 +961,42 @@ impl RenderingManager {
         let screen_space_output_view =
             screen_space_output.create_view(&wgpu::TextureViewDescriptor::default());
 
-        for material_id in scene.screen_space_materials() {
-            let material = scene
-                .get_material(*material_id)
-                .expect("Invalid material ID");
+        let mut materials: Vec<&Material> = scene
+            .screen_space_materials()
+            .iter()
+            .map(|material_id| {
+                scene
+                    .get_material(*material_id)
+                    .expect("Invalid material ID")
+            })
+            .collect();
+        // The sort is stable, so effects with the same order keep their creation order
+        materials.sort_by_key(|material| material.screen_space_order());
+
+        for material in materials {
             if let Some(pipeline) = pipelines.get(material.pipeline_id()) {
-                surface_data
-                    .screen_space_attachments
-                    .execute_effect_render_pass(
-                        encoder,
-                        &screen_space_output_view,
-                        pipeline,
-                        material.bind_group().unwrap(),
-                    );
+                screen_space_attachments.execute_effect_render_pass(
+                    encoder,
+                    &screen_space_output_view,
+                    pipeline,
+                    material.bind_group().unwrap(),
+                    material.screen_space_input(),
+                );
             }
             encoder.copy_texture_to_texture(
                 screen_space_output.as_image_copy(),
-                surface_data
-                    .screen_space_attachments
+                screen_space_attachments
                     .screen_space_input_texture
                     .as_image_copy(),
-                wgpu::Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
+                extent,
             );
         }
 
-        surface_data
-            .screen_space_attachments
-            .execute_output_render_pass(encoder, &output_view);
+        screen_space_attachments.execute_output_render_pass(
+            encoder,
+            &output_view,
+            letterbox_viewport,
+        );
     }
 
     fn egui_render(
@@ -526,6 +1034,188 @@ impl RenderingManager {
         tdelta
     }
 
+    /// Draws every enabled non-screen-space material. When rendering into a render target, the
+    /// materials sampling that target are skipped and render bundles are not used, since they were
+    /// recorded with the active camera.
+    /// Render bundles are recorded against the color target, so `use_render_bundles` has to be
+    /// false for passes without one
+    fn draw_scene_geometry(
+        device: &Device,
+        queue: &Queue,
+        render_pass: &mut wgpu::RenderPass,
+        scene: &Scene,
+        pipelines: &HashMap<PipelineId, RenderPipeline>,
+        camera_bind_group: Option<&BindGroup>,
+        render_target: Option<&Texture>,
+        all_components: &[&Component],
+        use_render_bundles: bool,
+    ) {
+        let mut sorted_pipelines: Vec<(&PipelineId, &RenderPipeline)> = Vec::from_iter(pipelines);
+        sorted_pipelines.sort_by(|(a, _), (b, _)| a.render_priority.cmp(&b.render_priority));
+
+        for (pipeline_id, pipeline) in sorted_pipelines {
+            if pipeline_id.is_screen_space {
+                continue;
+            }
+            render_pass.set_pipeline(pipeline);
+            let materials_for_pipeline = scene.get_pipeline_materials(pipeline_id);
+            for material in materials_for_pipeline
+                .iter()
+                .filter(|mat| scene.is_component_enabled(**mat))
+            {
+                if let Some(render_target) = render_target {
+                    if material.samples_texture(render_target) {
+                        continue;
+                    }
+                } else if let (true, Some(render_bundle)) =
+                    (use_render_bundles, material.render_bundle())
+                {
+                    render_pass.execute_bundles(std::iter::once(render_bundle));
+                    // Executing a bundle resets the pass state
+                    render_pass.set_pipeline(pipeline);
+                    continue;
+                }
+
+                if material.uses_camera() {
+                    render_pass.set_bind_group(
+                        0,
+                        camera_bind_group.expect("No active camera buffer set"),
+                        &[],
+                    );
+                }
+
+                if pipeline_id.immediate_size != 0 {
+                    render_pass.set_immediates(0, material.get_immediate_data());
+                }
+
+                material.render_with_dense_components(
+                    device,
+                    queue,
+                    render_pass,
+                    all_components,
+                    scene.dense_storage(),
+                );
+            }
+        }
+    }
+
+    /// Keeps the viewport inside the render size, with whole pixels so it can be used as the
+    /// scissor rect as well
+    fn clamp_viewport(
+        [x, y, width, height]: [f32; 4],
+        (render_width, render_height): (u32, u32),
+    ) -> [f32; 4] {
+        let (render_width, render_height) = (render_width as f32, render_height as f32);
+        let x = x.clamp(0.0, render_width - 1.0).floor();
+        let y = y.clamp(0.0, render_height - 1.0).floor();
+        [
+            x,
+            y,
+            width.clamp(1.0, render_width - x).floor(),
+            height.clamp(1.0, render_height - y).floor(),
+        ]
+    }
+
+    fn set_pass_viewport(render_pass: &mut wgpu::RenderPass, [x, y, width, height]: [f32; 4]) {
+        render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+        render_pass.set_scissor_rect(x as u32, y as u32, width as u32, height as u32);
+    }
+
+    /// A pass that only writes `depth_view`, drawn with the pipelines from
+    /// `PipelineCompiler::depth_prepass_pipelines`
+    fn begin_depth_prepass<'a>(
+        encoder: &'a mut CommandEncoder,
+        depth_view: &TextureView,
+        depth_load_op: wgpu::LoadOp<f32>,
+        depth_format: TextureFormat,
+    ) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Depth pre-pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(Self::depth_stencil_attachment(
+                depth_view,
+                depth_load_op,
+                depth_format,
+            )),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        })
+    }
+
+    /// How the geometry pass loads the depth target. The scene's `Load` clears instead while the
+    /// target holds nothing yet, on the first frame and after it was recreated by a resize.
+    fn geometry_depth_load_op(
+        scene_depth_load_op: wgpu::LoadOp<f32>,
+        depth_produced_this_frame: bool,
+        depth_needs_clear: &mut bool,
+    ) -> wgpu::LoadOp<f32> {
+        let depth_load_op = if depth_produced_this_frame {
+            wgpu::LoadOp::Load
+        } else {
+            match scene_depth_load_op {
+                wgpu::LoadOp::Load if *depth_needs_clear => wgpu::LoadOp::Clear(1.0),
+                depth_load_op => depth_load_op,
+            }
+        };
+        *depth_needs_clear = false;
+        depth_load_op
+    }
+
+    /// The geometry pass's color attachment. With MSAA the multisampled color is drawn into and
+    /// resolved into `scene_color`, so the single-sampled texture behind it is what screen-space
+    /// effects copy. The multisampled color only holds this pass, so it can't load earlier passes.
+    fn scene_color_attachment<'a>(
+        multisampled_targets: Option<&'a MultisampledTargets>,
+        scene_color: &'a TextureView,
+        scene_color_produced: bool,
+        clear_color: wgpu::Color,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        let (view, resolve_target, store) = match multisampled_targets {
+            Some(targets) => (
+                &targets.color_view,
+                Some(scene_color),
+                wgpu::StoreOp::Discard,
+            ),
+            None => (scene_color, None, wgpu::StoreOp::Store),
+        };
+        wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target,
+            ops: wgpu::Operations {
+                load: if scene_color_produced && resolve_target.is_none() {
+                    wgpu::LoadOp::Load
+                } else {
+                    wgpu::LoadOp::Clear(clear_color)
+                },
+                store,
+            },
+            depth_slice: None,
+        }
+    }
+
+    /// The stencil of formats that have one is cleared along with the depth, passes with a depth
+    /// attachment of such a format must say what happens to it
+    fn depth_stencil_attachment(
+        depth_view: &TextureView,
+        depth_load_op: wgpu::LoadOp<f32>,
+        depth_format: TextureFormat,
+    ) -> wgpu::RenderPassDepthStencilAttachment<'_> {
+        wgpu::RenderPassDepthStencilAttachment {
+            view: depth_view,
+            depth_ops: Some(wgpu::Operations {
+                load: depth_load_op,
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: depth_format
+                .has_stencil_aspect()
+                .then_some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: wgpu::StoreOp::Store,
+                }),
+        }
+    }
+
     pub fn resize(&mut self, physical_width: u32, physical_height: u32, scale_factor: f32,) {
         self.width = physical_width;
         self.height = physical_height;
@@ -536,11 +1226,41 @@ impl RenderingManager {
         surface_data
             .surface
             .configure(&self.device, &surface_data.config);
-        surface_data.depth_texture = texture_support::TextureBundle::create_depth_texture(
-            &self.device,
-            &surface_data.config,
-        );
-        surface_data.smaa_target.resize(&self.device, physical_width, physical_height);
+        // The offscreen targets keep the design resolution, only the letterbox changes
+        if self.design_resolution.is_none() {
+            surface_data.depth_texture = texture_support::TextureBundle::create_depth_texture(
+                &self.device,
+                &surface_data.config,
+                self.depth_format,
+            );
+            surface_data.depth_needs_clear = true;
+            if let Some(multisampled_targets) = surface_data.multisampled_targets.as_mut() {
+                *multisampled_targets = MultisampledTargets::new(
+                    &self.device,
+                    physical_width,
+                    physical_height,
+                    surface_data.format,
+                    self.depth_format,
+                    self.msaa_samples,
+                );
+            }
+            surface_data.gbuffer_views = Self::create_gbuffer_views(
+                &self.device,
+                &self.gbuffer_formats,
+                physical_width,
+                physical_height,
+            );
+            surface_data
+                .smaa_target
+                .resize(&self.device, physical_width, physical_height);
+            surface_data.screen_space_attachments.resize(
+                &self.device,
+                physical_width,
+                physical_height,
+                surface_data.gbuffer_views.clone(),
+                &surface_data.depth_texture.0,
+            );
+        }
         surface_data.egui_screen_descriptor = ScreenDescriptor {
             physical_width,
             physical_height,
@@ -556,38 +1276,412 @@ impl RenderingManager {
         &self.queue
     }
 
-    pub fn format(&self) -> Option<wgpu::TextureFormat> {
-        self.surface_data
-            .as_ref()
-            .map(|surface_data| surface_data.format)
+    pub fn adapter_info(&self) -> wgpu::AdapterInfo {
+        self.adapter_info.clone()
     }
 
-    pub fn surface(&self) -> Option<&wgpu::Surface<'static>> {
+    /// The features enabled on the device, which may be a superset of the requested ones
+    pub fn features(&self) -> wgpu::Features {
+        self.features
+    }
+
+    pub fn limits(&self) -> wgpu::Limits {
+        self.limits.clone()
+    }
+
+    pub fn format(&self) -> Option<wgpu::TextureFormat> {
         self.surface_data
             .as_ref()
-            .map(|surface_data| &surface_data.surface)
+            .map(|surface_data| surface_data.format)
     }
 
-    pub fn smaa_target_mut(&mut self) -> Option<&mut SmaaTarget> {
-        self.surface_data
-            .as_mut()
-            .map(|surface_data| &mut surface_data.smaa_target)
+    /// The next rendered frame as it appears on screen, including the UI. The future resolves once
+    /// that frame has been rendered.
+    pub fn capture_frame(&self) -> impl Future<Output = RgbaImage> + use<> {
+        let (sender, receiver) = oneshot::channel();
+        self.frame_captures.lock().unwrap().push(sender);
+        async move {
+            receiver
+                .await
+                .expect("The rendering manager was dropped before the frame was captured.")
+        }
     }
 
-    pub fn individual_compute_execution(&self, computes: &[Compute]) {
-        let mut encoder =
-            self.device
-                .create_command_encoder(&wgpu::wgt::CommandEncoderDescriptor {
-                    label: Some("Individual compute encoder"),
-                });
+    /// Copies `texture` into a mappable buffer and waits for it. The texels are stored already
+    /// encoded for sRGB surfaces, so only the channel order has to be fixed up.
+    fn read_frame(device: &Device, queue: &Queue, texture: &Texture) -> RgbaImage {
+        let (width, height) = (texture.width(), texture.height());
+        let format = texture.format();
+        let is_bgra = match format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+            _ => {
+                log::warn!("Frames can not be captured from a {format:?} surface.");
+                return RgbaImage::new(width, height);
+            }
+        };
+        if !texture.usage().contains(TextureUsages::COPY_SRC) {
+            log::warn!("Frames can not be captured, the surface does not support copying from it.");
+            return RgbaImage::new(width, height);
+        }
 
-        {
-            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+        let unpadded_row_size = width * 4;
+        let padded_row_size =
+            unpadded_row_size.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame capture staging buffer"),
+            size: padded_row_size as u64 * height as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Frame capture encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &staging_buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+        queue.submit([encoder.finish()]);
+
+        let buffer_slice = staging_buffer.slice(..);
+        buffer_slice.map_async(wgpu::MapMode::Read, |result| {
+            result.expect("Failed to map the frame capture buffer.");
+        });
+        device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .expect("Failed to wait for the frame capture.");
+
+        let mut pixels = Vec::with_capacity((unpadded_row_size * height) as usize);
+        for row in buffer_slice
+            .get_mapped_range()
+            .chunks(padded_row_size as usize)
+        {
+            pixels.extend_from_slice(&row[..unpadded_row_size as usize]);
+        }
+        staging_buffer.unmap();
+        if is_bgra {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        RgbaImage::from_raw(width, height, pixels).expect("The frame capture has the wrong size.")
+    }
+
+    pub fn depth_prepass_enabled(&self) -> bool {
+        self.depth_prepass
+    }
+
+    pub fn design_resolution(&self) -> Option<(u32, u32)> {
+        self.design_resolution
+    }
+
+    /// The size the scene is rendered at, the design resolution if there is one
+    pub fn render_size(&self) -> (u32, u32) {
+        self.design_resolution.unwrap_or((self.width, self.height))
+    }
+
+    /// The color the main pass clears to while the scene is active, the scene's own clear color
+    /// if it has one and the engine clear color otherwise
+    pub fn scene_clear_color(&self, scene: &Scene) -> wgpu::Color {
+        scene.clear_color().unwrap_or(self.clear_color)
+    }
+
+    /// The `[x, y, width, height]` of the window, in pixels, that the design resolution is scaled
+    /// into while keeping its aspect ratio. The bars around it are left black.
+    pub fn letterbox_viewport(&self) -> Option<[f32; 4]> {
+        let design_resolution = self.design_resolution?;
+        Some(Self::letterbox(
+            design_resolution,
+            (self.width, self.height),
+        ))
+    }
+
+    /// Maps a position in window pixels to design resolution pixels, positions on the bars end up
+    /// outside of the design resolution
+    pub fn window_to_design(&self, position: (f32, f32)) -> (f32, f32) {
+        match self.design_resolution {
+            Some(design_resolution) => {
+                Self::letterboxed_to_design(position, design_resolution, (self.width, self.height))
+            }
+            None => position,
+        }
+    }
+
+    fn letterbox(design_resolution: (u32, u32), window_size: (u32, u32)) -> [f32; 4] {
+        let (design_width, design_height) =
+            (design_resolution.0 as f32, design_resolution.1 as f32);
+        let (window_width, window_height) = (window_size.0 as f32, window_size.1 as f32);
+        let scale = (window_width / design_width).min(window_height / design_height);
+        let (width, height) = (design_width * scale, design_height * scale);
+        [
+            (window_width - width) / 2.0,
+            (window_height - height) / 2.0,
+            width,
+            height,
+        ]
+    }
+
+    fn letterboxed_to_design(
+        position: (f32, f32),
+        design_resolution: (u32, u32),
+        window_size: (u32, u32),
+    ) -> (f32, f32) {
+        let [x, y, width, height] = Self::letterbox(design_resolution, window_size);
+        (
+            (position.0 - x) / width * design_resolution.0 as f32,
+            (position.1 - y) / height * design_resolution.1 as f32,
+        )
+    }
+
+    /// The extra formats the surface texture can be viewed as
+    pub fn surface_view_formats(&self) -> &[TextureFormat] {
+        self.surface_data
+            .as_ref()
+            .map(|surface_data| surface_data.config.view_formats.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn buffer_pool(&self) -> &BufferPool {
+        &self.buffer_pool
+    }
+
+    pub fn buffer_pool_mut(&mut self) -> &mut BufferPool {
+        &mut self.buffer_pool
+    }
+
+    pub fn depth_format(&self) -> TextureFormat {
+        self.depth_format
+    }
+
+    pub fn screen_space_filter_mode(&self) -> wgpu::FilterMode {
+        self.screen_space_filter_mode
+    }
+
+    /// `Nearest` keeps upscaled screen-space effects crisp, e.g. for pixel art
+    pub fn set_screen_space_filter_mode(&mut self, filter_mode: wgpu::FilterMode) {
+        self.screen_space_filter_mode = filter_mode;
+        if let Some(surface_data) = self.surface_data.as_mut() {
+            surface_data
+                .screen_space_attachments
+                .set_filter_mode(&self.device, filter_mode);
+        }
+    }
+
+    pub fn shader_error(&self) -> Option<&str> {
+        self.shader_error.as_deref()
+    }
+
+    pub fn shader_error_overlay_active(&self) -> bool {
+        self.shader_error.is_some()
+    }
+
+    /// Draws the error over every frame until cleared. Pipelines that failed to build are skipped
+    /// while the ones that compiled keep rendering.
+    pub fn show_shader_error(&mut self, error: String) {
+        self.shader_error = Some(error);
+    }
+
+    pub fn clear_shader_error(&mut self) {
+        self.shader_error = None;
+    }
+
+    fn create_smaa_target(&self, format: TextureFormat) -> SmaaTarget {
+        let (render_width, render_height) = self.render_size();
+        SmaaTarget::new(
+            &self.device,
+            &self.queue,
+            render_width,
+            render_height,
+            format,
+            if self.antialiasing_enabled {
+                smaa::SmaaMode::Smaa1X
+            } else {
+                smaa::SmaaMode::Disabled
+            },
+        )
+    }
+
+    pub fn antialiasing_enabled(&self) -> bool {
+        self.antialiasing_enabled
+    }
+
+    /// Recreates the SMAA target in place, so it takes effect from the next frame and
+    /// `smaa_target_mut` returns the new target. SMAA can't be enabled while MSAA is.
+    pub fn set_antialiasing(&mut self, enabled: bool) {
+        if self.antialiasing_enabled == enabled {
+            return;
+        }
+        if enabled && self.msaa_samples > 1 {
+            log::warn!("SMAA can't be enabled while MSAA is, they are mutually exclusive.");
+            return;
+        }
+        self.antialiasing_enabled = enabled;
+        if let Some(format) = self.format() {
+            let smaa_target = self.create_smaa_target(format);
+            self.surface_data.as_mut().unwrap().smaa_target = smaa_target;
+        }
+    }
+
+    /// The number of samples per pixel of the main pass, 1 when MSAA is disabled
+    pub fn msaa_samples(&self) -> u32 {
+        self.msaa_samples
+    }
+
+    pub fn gbuffer_formats(&self) -> &[TextureFormat] {
+        &self.gbuffer_formats
+    }
+
+    /// The target the pipelines of the main pass are compiled for
+    pub fn pipeline_target(&self, render_format: TextureFormat) -> PipelineTarget {
+        PipelineTarget {
+            render_format,
+            gbuffer_formats: self.gbuffer_formats.clone(),
+            sample_count: self.msaa_samples,
+        }
+    }
+
+    fn create_gbuffer_views(
+        device: &Device,
+        gbuffer_formats: &[TextureFormat],
+        width: u32,
+        height: u32,
+    ) -> Vec<TextureView> {
+        gbuffer_formats
+            .iter()
+            .enumerate()
+            .map(|(index, format)| {
+                device
+                    .create_texture(&wgpu::TextureDescriptor {
+                        label: Some(&format!("G-buffer texture {index}")),
+                        size: wgpu::Extent3d {
+                            width,
+                            height,
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: *format,
+                        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                        view_formats: &[],
+                    })
+                    .create_view(&wgpu::TextureViewDescriptor::default())
+            })
+            .collect()
+    }
+
+    pub fn debug_draw_enabled(&self) -> bool {
+        self.debug_draw_enabled
+    }
+
+    /// While enabled, the lines pushed with `Scene::push_debug_lines` are drawn over the scene
+    pub fn set_debug_draw_enabled(&mut self, enabled: bool) {
+        self.debug_draw_enabled = enabled;
+    }
+
+    /// Starts compiling the pipeline on a background thread. Until it is collected with
+    /// `poll_compiled_pipelines`, a loading message is shown and materials using it are not drawn.
+    /// Pipelines for the main pass use `RenderingManager::pipeline_target`, the ones for render
+    /// targets `RenderTarget::pipeline_target`.
+    pub fn compile_pipeline(
+        &mut self,
+        id: &PipelineId,
+        attachment_bind_group_layout: Option<&BindGroupLayout>,
+        target: &PipelineTarget,
+    ) {
+        if let Some(shader_hot_reloader) = &self.shader_hot_reloader {
+            for path in id.shader_paths() {
+                shader_hot_reloader.watch(path);
+            }
+        }
+        self.pipeline_compiler.compile(
+            &self.device,
+            id,
+            attachment_bind_group_layout,
+            target,
+            self.depth_format,
+            self.depth_prepass,
+        );
+    }
+
+    /// The shader files edited since the last call, always empty unless shader hot reloading was
+    /// enabled on the builder
+    pub fn changed_shader_paths(&self) -> Vec<&'static str> {
+        self.shader_hot_reloader
+            .as_ref()
+            .map(ShaderHotReloader::changed_paths)
+            .unwrap_or_default()
+    }
+
+    /// Also remembers the errors of the pipelines that failed, see `RenderingManager::pipeline_error`
+    pub fn poll_compiled_pipelines(&mut self) -> Vec<CompiledPipeline> {
+        let compiled = self.pipeline_compiler.poll();
+        for (id, _, result) in &compiled {
+            match result {
+                Ok(_) => self.pipeline_errors.remove(id),
+                Err(error) => self.pipeline_errors.insert(id.clone(), error.clone()),
+            };
+        }
+        compiled
+    }
+
+    /// The error of the pipeline's last failed compilation, cleared once it compiles
+    pub fn pipeline_error(&self, id: &PipelineId) -> Option<&PipelineError> {
+        self.pipeline_errors.get(id)
+    }
+
+    pub fn is_compiling_pipeline(&self, id: &PipelineId, target: &PipelineTarget) -> bool {
+        self.pipeline_compiler.is_compiling(id, target)
+    }
+
+    pub fn pipeline_compile_state(&self, id: &PipelineId) -> Option<PipelineCompileState> {
+        self.pipeline_compiler.state(id)
+    }
+
+    /// Registers a pass that runs every frame alongside the built-in geometry, screen-space effect
+    /// and UI passes, ordered by the resources it declares
+    pub fn add_pass(&mut self, pass: Box<dyn RenderGraphPass>) {
+        self.render_graph.add_pass(pass);
+    }
+
+    pub fn surface(&self) -> Option<&wgpu::Surface<'static>> {
+        self.surface_data
+            .as_ref()
+            .map(|surface_data| &surface_data.surface)
+    }
+
+    pub fn smaa_target_mut(&mut self) -> Option<&mut SmaaTarget> {
+        self.surface_data
+            .as_mut()
+            .map(|surface_data| &mut surface_data.smaa_target)
+    }
+
+    pub fn individual_compute_execution(&self, computes: &[Compute]) {
+        let mut encoder =
+            self.device
+                .create_command_encoder(&wgpu::wgt::CommandEncoderDescriptor {
+                    label: Some("Individual compute encoder"),
+                });
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
                 label: Some("Compute pass"),
                 timestamp_writes: None,
             });
 
-            for compute in computes {
+            for compute in computes
+                .iter()
+                .filter(|compute| compute.pipeline_error().is_none())
+            {
                 for _ in 0..compute.iterate_count() {
                     compute.calculate(&mut compute_pass);
                 }
@@ -598,85 +1692,372 @@ impl RenderingManager {
     }
 }
 
+/// The targets the main pass draws into with MSAA, the color is resolved into the scene color
+#[derive(Debug)]
+struct MultisampledTargets {
+    color_view: TextureView,
+    depth_view: TextureView,
+}
+
+impl MultisampledTargets {
+    fn new(
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        depth_format: TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let create_view = |label, format| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        MultisampledTargets {
+            color_view: create_view("Multisampled color texture", format),
+            depth_view: create_view("Multisampled depth texture", depth_format),
+        }
+    }
+}
+
+/// Draws scene backgrounds as a full-screen triangle that neither writes nor tests depth
+#[derive(Debug)]
+struct BackgroundRenderer {
+    gradient_pipeline: RenderPipeline,
+    gradient_buffer: Buffer,
+    gradient_bind_group: BindGroup,
+}
+
+impl BackgroundRenderer {
+    fn new(device: &Device, target: &PipelineTarget, depth_format: TextureFormat) -> Self {
+        let gradient_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Background gradient bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let gradient_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Background gradient buffer"),
+            size: std::mem::size_of::<[[f32; 4]; 2]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let gradient_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Background gradient bind group"),
+            layout: &gradient_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: gradient_buffer.as_entire_binding(),
+            }],
+        });
+
+        let gradient_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Background gradient pipeline layout"),
+                bind_group_layouts: &[&gradient_bind_group_layout],
+                immediate_size: 0,
+            });
+
+        let gradient_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Background gradient shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "../default_shaders/background_gradient.wgsl"
+            ))),
+        });
+
+        let gradient_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Background gradient pipeline"),
+            layout: Some(&gradient_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &gradient_shader,
+                entry_point: Some("vertex"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: target.sample_count,
+                ..Default::default()
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &gradient_shader,
+                entry_point: Some("fragment"),
+                compilation_options: Default::default(),
+                targets: &target.color_targets(None, false),
+            }),
+            multiview_mask: None,
+            cache: None,
+        });
+
+        Self {
+            gradient_pipeline,
+            gradient_buffer,
+            gradient_bind_group,
+        }
+    }
+
+    fn update_gradient(&self, queue: &Queue, top: wgpu::Color, bottom: wgpu::Color) {
+        let to_array = |color: wgpu::Color| {
+            [
+                color.r as f32,
+                color.g as f32,
+                color.b as f32,
+                color.a as f32,
+            ]
+        };
+        queue.write_buffer(
+            &self.gradient_buffer,
+            0,
+            bytemuck::cast_slice(&[to_array(top), to_array(bottom)]),
+        );
+    }
+
+    fn render_gradient(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_pipeline(&self.gradient_pipeline);
+        render_pass.set_bind_group(0, &self.gradient_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[derive(Debug)]
+struct DebugLineRenderer {
+    pipeline: RenderPipeline,
+    vertex_buffer: Buffer,
+}
+
+impl DebugLineRenderer {
+    fn new(device: &Device, target: &PipelineTarget, depth_format: TextureFormat) -> Self {
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Debug line camera bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Debug line pipeline layout"),
+            bind_group_layouts: &[&camera_bind_group_layout],
+            immediate_size: 0,
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Debug line shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "../default_shaders/debug_lines.wgsl"
+            ))),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Debug line pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex"),
+                compilation_options: Default::default(),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<DebugLineVertex>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4],
+                }],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: target.sample_count,
+                ..Default::default()
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment"),
+                compilation_options: Default::default(),
+                targets: &target.color_targets(Some(wgpu::BlendState::ALPHA_BLENDING), false),
+            }),
+            multiview_mask: None,
+            cache: None,
+        });
+
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Debug line vertex buffer"),
+            size: 0,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            pipeline,
+            vertex_buffer,
+        }
+    }
+
+    fn render(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        render_pass: &mut wgpu::RenderPass,
+        lines: &[DebugLineVertex],
+        camera_bind_group: &BindGroup,
+    ) {
+        if lines.is_empty() {
+            return;
+        }
+
+        misc_utils::update_buffer(
+            &mut self.vertex_buffer,
+            bytemuck::cast_slice(lines),
+            device,
+            queue,
+            Some("Debug line vertex buffer"),
+        );
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..lines.len() as u32, 0..1);
+    }
+}
+
+/// Matches the uniform at group 0, binding 3 of screen-space effects
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ScreenSpaceUniforms {
+    time: f32,
+    _padding: f32,
+    resolution: [f32; 2],
+    mouse: [f32; 2],
+    _end_padding: [f32; 2],
+    viewport: [f32; 4],
+}
+
 #[derive(Debug)]
 struct ScreenSpaceAttachments {
+    format: TextureFormat,
     screen_space_input_texture: Texture,
+    /// The scene before any effect ran
+    original_texture: Texture,
+    gbuffer_views: Vec<TextureView>,
+    depth_view: TextureView,
+    uniform_buffer: Buffer,
+    screen_space_bind_group_layout: BindGroupLayout,
+    screen_space_texture_sampler: Sampler,
     screen_space_bind_group: BindGroup,
+    /// Reads the original texture at binding 0, for `ScreenSpaceInput::Original`
+    original_bind_group: BindGroup,
     screen_triangle_buffer: Buffer,
     screen_space_output_pipeline: RenderPipeline,
 }
 
 impl ScreenSpaceAttachments {
-    fn new(device: &Device, width: u32, height: u32, format: TextureFormat) -> Self {
+    fn new(
+        device: &Device,
+        width: u32,
+        height: u32,
+        target: &PipelineTarget,
+        gbuffer_views: Vec<TextureView>,
+        depth_texture: &Texture,
+        filter_mode: wgpu::FilterMode,
+    ) -> Self {
+        let format = target.render_format;
         let screen_space_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("Screen-space render output bind group layout"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            multisampled: false,
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
+                entries: &screen_space_bind_group_layout_entries(gbuffer_views.len()),
             });
 
-        let screen_space_texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("Screen-space render output sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::MipmapFilterMode::Nearest,
-            lod_min_clamp: 0.0,
-            lod_max_clamp: 100.0,
-            ..Default::default()
-        });
+        let screen_space_texture_sampler = Self::create_sampler(device, filter_mode);
 
-        let screen_space_input_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Screen-space effect output texture"),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
+        let screen_space_input_texture = Self::create_input_texture(
+            device,
+            width,
+            height,
             format,
-            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
+            "Screen-space effect output texture",
+        );
+        let original_texture = Self::create_input_texture(
+            device,
+            width,
+            height,
+            format,
+            "Screen-space original scene texture",
+        );
 
-        let screen_space_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Screen-space render bind group"),
-            layout: &screen_space_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(
-                        &screen_space_input_texture
-                            .create_view(&wgpu::TextureViewDescriptor::default()),
-                    ),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&screen_space_texture_sampler),
-                },
-            ],
+        let depth_view = Self::create_depth_view(depth_texture);
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Screen-space uniform buffer"),
+            size: std::mem::size_of::<ScreenSpaceUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
 
+        let screen_space_bind_group = Self::create_bind_group(
+            device,
+            &screen_space_bind_group_layout,
+            &screen_space_input_texture,
+            &original_texture,
+            &gbuffer_views,
+            &screen_space_texture_sampler,
+            &depth_view,
+            &uniform_buffer,
+        );
+        let original_bind_group = Self::create_bind_group(
+            device,
+            &screen_space_bind_group_layout,
+            &original_texture,
+            &original_texture,
+            &gbuffer_views,
+            &screen_space_texture_sampler,
+            &depth_view,
+            &uniform_buffer,
+        );
+
         const SCREEN_SPACE_VERTEX_ATTRIBUTES: &[wgpu::VertexAttribute] =
             &wgpu::vertex_attr_array![0=>Float32x3, 1=>Float32x2];
 
@@ -704,6 +2085,8 @@ impl ScreenSpaceAttachments {
                 )),
             ),
             spirv_fragment_shader: false,
+            vertex_entry: "main",
+            fragment_entry: "main",
             vertex_layouts: vec![wgpu::VertexBufferLayout {
                 array_stride: 4 * 5,
                 step_mode: wgpu::VertexStepMode::Vertex,
@@ -714,31 +2097,207 @@ impl ScreenSpaceAttachments {
             geometry_details: Default::default(),
             immediate_size: 0,
             render_priority: i32::MAX,
+            blend_state: PipelineId::DEFAULT_BLEND_STATE,
+            writes_gbuffer: false,
         };
 
         let screen_space_output_pipeline = create_render_pipeline(
             &device,
             &screen_space_output_pipeline_id,
             None,
-            format,
+            &PipelineTarget {
+                sample_count: 1,
+                ..target.clone()
+            },
+            depth_texture.format(),
             false,
             false,
-        );
+            false,
+        )
+        .expect("Failed to create the built-in screen-space output pipeline.");
 
         ScreenSpaceAttachments {
+            format,
             screen_space_input_texture,
+            original_texture,
+            gbuffer_views,
+            depth_view,
+            uniform_buffer,
+            screen_space_bind_group_layout,
+            screen_space_texture_sampler,
             screen_space_bind_group,
+            original_bind_group,
             screen_triangle_buffer,
             screen_space_output_pipeline,
         }
     }
 
+    fn create_sampler(device: &Device, filter_mode: wgpu::FilterMode) -> Sampler {
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Screen-space render output sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: filter_mode,
+            min_filter: filter_mode,
+            mipmap_filter: wgpu::MipmapFilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 100.0,
+            ..Default::default()
+        })
+    }
+
+    fn create_input_texture(
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        label: &str,
+    ) -> Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    fn create_depth_view(depth_texture: &Texture) -> TextureView {
+        depth_texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Screen-space depth view"),
+            aspect: wgpu::TextureAspect::DepthOnly,
+            ..Default::default()
+        })
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        screen_space_input_texture: &Texture,
+        original_texture: &Texture,
+        gbuffer_views: &[TextureView],
+        sampler: &Sampler,
+        depth_view: &TextureView,
+        uniform_buffer: &Buffer,
+    ) -> BindGroup {
+        let input_view =
+            screen_space_input_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let original_view = original_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let entries: Vec<wgpu::BindGroupEntry> = [
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&input_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(depth_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: wgpu::BindingResource::TextureView(&original_view),
+            },
+        ]
+        .into_iter()
+        .chain(
+            gbuffer_views
+                .iter()
+                .enumerate()
+                .map(|(index, gbuffer_view)| wgpu::BindGroupEntry {
+                    binding: 5 + index as u32,
+                    resource: wgpu::BindingResource::TextureView(gbuffer_view),
+                }),
+        )
+        .collect();
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Screen-space render bind group"),
+            layout,
+            entries: &entries,
+        })
+    }
+
+    fn resize(
+        &mut self,
+        device: &Device,
+        width: u32,
+        height: u32,
+        gbuffer_views: Vec<TextureView>,
+        depth_texture: &Texture,
+    ) {
+        self.screen_space_input_texture = Self::create_input_texture(
+            device,
+            width,
+            height,
+            self.format,
+            "Screen-space effect output texture",
+        );
+        self.original_texture = Self::create_input_texture(
+            device,
+            width,
+            height,
+            self.format,
+            "Screen-space original scene texture",
+        );
+        self.gbuffer_views = gbuffer_views;
+        self.depth_view = Self::create_depth_view(depth_texture);
+        self.rebuild_bind_group(device);
+    }
+
+    fn rebuild_bind_group(&mut self, device: &Device) {
+        self.screen_space_bind_group = Self::create_bind_group(
+            device,
+            &self.screen_space_bind_group_layout,
+            &self.screen_space_input_texture,
+            &self.original_texture,
+            &self.gbuffer_views,
+            &self.screen_space_texture_sampler,
+            &self.depth_view,
+            &self.uniform_buffer,
+        );
+        self.original_bind_group = Self::create_bind_group(
+            device,
+            &self.screen_space_bind_group_layout,
+            &self.original_texture,
+            &self.original_texture,
+            &self.gbuffer_views,
+            &self.screen_space_texture_sampler,
+            &self.depth_view,
+            &self.uniform_buffer,
+        );
+    }
+
+    fn update_uniforms(&self, queue: &Queue, uniforms: ScreenSpaceUniforms) {
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+    }
+
+    fn set_filter_mode(&mut self, device: &Device, filter_mode: wgpu::FilterMode) {
+        self.screen_space_texture_sampler = Self::create_sampler(device, filter_mode);
+        self.rebuild_bind_group(device);
+    }
+
     fn execute_effect_render_pass(
         &self,
         encoder: &mut CommandEncoder,
         screen_space_output_view: &TextureView,
         pipeline: &RenderPipeline,
         material_bind_group: &BindGroup,
+        input: ScreenSpaceInput,
     ) {
         let mut effect_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Effect render pass"),
@@ -759,7 +2318,11 @@ impl ScreenSpaceAttachments {
 
         effect_pass.set_pipeline(pipeline);
 
-        effect_pass.set_bind_group(0, &self.screen_space_bind_group, &[]);
+        let screen_space_bind_group = match input {
+            ScreenSpaceInput::Previous => &self.screen_space_bind_group,
+            ScreenSpaceInput::Original => &self.original_bind_group,
+        };
+        effect_pass.set_bind_group(0, screen_space_bind_group, &[]);
 
         effect_pass.set_bind_group(1, material_bind_group, &[]);
 
@@ -767,7 +2330,13 @@ impl ScreenSpaceAttachments {
         effect_pass.draw(0..3, 0..1);
     }
 
-    fn execute_output_render_pass(&self, encoder: &mut CommandEncoder, output_view: &TextureView) {
+    /// With a viewport the output is drawn into it and the rest of the window is cleared to black
+    fn execute_output_render_pass(
+        &self,
+        encoder: &mut CommandEncoder,
+        output_view: &TextureView,
+        viewport: Option<[f32; 4]>,
+    ) {
         let mut screen_space_application_render_pass =
             encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Screen-space display render pass"),
@@ -775,7 +2344,11 @@ impl ScreenSpaceAttachments {
                     view: output_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
+                        load: if viewport.is_some() {
+                            wgpu::LoadOp::Clear(wgpu::Color::BLACK)
+                        } else {
+                            wgpu::LoadOp::Load
+                        },
                         store: wgpu::StoreOp::Store,
                     },
                     depth_slice: None,
@@ -786,6 +2359,9 @@ impl ScreenSpaceAttachments {
                 multiview_mask: None,
             });
 
+        if let Some([x, y, width, height]) = viewport {
+            screen_space_application_render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+        }
         screen_space_application_render_pass.set_pipeline(&self.screen_space_output_pipeline);
         screen_space_application_render_pass.set_bind_group(0, &self.screen_space_bind_group, &[]);
         screen_space_application_render_pass
@@ -793,3 +2369,450 @@ impl ScreenSpaceAttachments {
         screen_space_application_render_pass.draw(0..3, 0..1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ecs::component::ComponentId,
+        engine_management::pipeline::{GeometryDetails, PipelineShader},
+    };
+    use texture_support::TextureBundle;
+
+    const COLOR_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+
+    const SHADER: &str = "
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(corner * 2.0 - 1.0, 0.5, 1.0);
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+";
+
+    fn pipeline_id() -> PipelineId {
+        PipelineId {
+            vertex_shader: PipelineShader::Raw(SHADER.into()),
+            spirv_vertex_shader: false,
+            fragment_shader: PipelineShader::Raw(SHADER.into()),
+            spirv_fragment_shader: false,
+            vertex_entry: "vertex",
+            fragment_entry: "fragment",
+            vertex_layouts: Vec::new(),
+            uses_camera: false,
+            is_screen_space: false,
+            geometry_details: GeometryDetails::default(),
+            immediate_size: 0,
+            render_priority: 0,
+            blend_state: PipelineId::DEFAULT_BLEND_STATE,
+            writes_gbuffer: false,
+        }
+    }
+
+    /// Builds a pipeline for the depth format and draws with it into a depth texture of that
+    /// format, in a pass set up like the engine's
+    fn draw_with_depth_format(depth_format: TextureFormat) {
+        let (device, queue) = Device::noop(&wgpu::DeviceDescriptor::default());
+        let pipeline = create_render_pipeline(
+            &device,
+            &pipeline_id(),
+            None,
+            &PipelineTarget::new(COLOR_FORMAT),
+            depth_format,
+            false,
+            false,
+            false,
+        )
+        .expect("The pipeline should accept the depth format");
+        let color_view = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Depth format test color"),
+                size: wgpu::Extent3d {
+                    width: 4,
+                    height: 4,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: COLOR_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let (_depth_texture, depth_bundle) =
+            TextureBundle::create_sized_depth_texture(&device, 4, 4, depth_format);
+
+        let error_scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Depth format test encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Depth format test pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: Some(RenderingManager::depth_stencil_attachment(
+                    depth_bundle.view(),
+                    wgpu::LoadOp::Clear(1.0),
+                    depth_format,
+                )),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                multiview_mask: None,
+            });
+            render_pass.set_pipeline(&pipeline);
+            render_pass.draw(0..3, 0..1);
+        }
+        queue.submit([encoder.finish()]);
+
+        assert!(
+            pollster::block_on(error_scope.pop()).is_none(),
+            "Drawing with {depth_format:?} raised a validation error"
+        );
+    }
+
+    #[test]
+    fn depth24plus_pipelines_draw() {
+        draw_with_depth_format(TextureFormat::Depth24Plus);
+    }
+
+    #[test]
+    fn depth24plus_stencil8_pipelines_draw() {
+        draw_with_depth_format(TextureFormat::Depth24PlusStencil8);
+    }
+
+    #[test]
+    fn stencil_is_only_cleared_for_stencil_formats() {
+        let (device, _queue) = Device::noop(&wgpu::DeviceDescriptor::default());
+        for (depth_format, has_stencil) in [
+            (TextureFormat::Depth32Float, false),
+            (TextureFormat::Depth24Plus, false),
+            (TextureFormat::Depth24PlusStencil8, true),
+        ] {
+            let (_depth_texture, depth_bundle) =
+                TextureBundle::create_sized_depth_texture(&device, 4, 4, depth_format);
+            let attachment = RenderingManager::depth_stencil_attachment(
+                depth_bundle.view(),
+                wgpu::LoadOp::Load,
+                depth_format,
+            );
+
+            assert_eq!(attachment.stencil_ops.is_some(), has_stencil);
+        }
+    }
+
+    #[test]
+    fn loaded_depth_is_cleared_until_it_holds_a_frame() {
+        let mut depth_needs_clear = true;
+        let mut frame = |depth_produced_this_frame| {
+            RenderingManager::geometry_depth_load_op(
+                wgpu::LoadOp::Load,
+                depth_produced_this_frame,
+                &mut depth_needs_clear,
+            )
+        };
+
+        assert_eq!(frame(false), wgpu::LoadOp::Clear(1.0));
+        assert_eq!(frame(false), wgpu::LoadOp::Load);
+        assert_eq!(frame(false), wgpu::LoadOp::Load);
+    }
+
+    #[test]
+    fn depth_is_cleared_again_after_a_resize() {
+        let mut depth_needs_clear = true;
+        RenderingManager::geometry_depth_load_op(wgpu::LoadOp::Load, false, &mut depth_needs_clear);
+
+        // `resize` recreates the depth texture and flags it
+        depth_needs_clear = true;
+        assert_eq!(
+            RenderingManager::geometry_depth_load_op(
+                wgpu::LoadOp::Load,
+                false,
+                &mut depth_needs_clear
+            ),
+            wgpu::LoadOp::Clear(1.0)
+        );
+    }
+
+    #[test]
+    fn produced_or_cleared_depth_is_kept() {
+        let mut depth_needs_clear = true;
+        assert_eq!(
+            RenderingManager::geometry_depth_load_op(
+                wgpu::LoadOp::Load,
+                true,
+                &mut depth_needs_clear
+            ),
+            wgpu::LoadOp::Load
+        );
+        assert!(!depth_needs_clear);
+
+        let mut depth_needs_clear = true;
+        assert_eq!(
+            RenderingManager::geometry_depth_load_op(
+                wgpu::LoadOp::Clear(0.5),
+                false,
+                &mut depth_needs_clear
+            ),
+            wgpu::LoadOp::Clear(0.5)
+        );
+    }
+
+    /// Keeps the red channel of the scene color, which screen-space effects read at binding 0
+    const EFFECT_SHADER: &str = "
+@group(0) @binding(0) var scene_color: texture_2d<f32>;
+
+@fragment
+fn fragment(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return textureLoad(scene_color, vec2<i32>(position.xy), 0) * vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+";
+
+    /// A device of the default adapter, which actually renders, or the noop device without one
+    fn rendering_device() -> (Device, Queue, bool) {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        match pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        {
+            Ok(adapter) => {
+                let (device, queue) =
+                    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default()))
+                        .expect("The adapter should provide a default device");
+                (device, queue, true)
+            }
+            Err(_) => {
+                let (device, queue) = Device::noop(&wgpu::DeviceDescriptor::default());
+                (device, queue, false)
+            }
+        }
+    }
+
+    fn create_scene_effect(device: &Device, queue: &Queue) -> (Scene, ComponentId) {
+        let mut scene = Scene::default();
+        let effect = scene.create_material(
+            PipelineId {
+                fragment_shader: PipelineShader::Raw(EFFECT_SHADER.into()),
+                is_screen_space: true,
+                ..pipeline_id()
+            },
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            true,
+        );
+        let (workload_sender, _workload_receiver) = crossbeam_channel::unbounded();
+        let (_workload_output_sender, workload_output_receiver) = crossbeam_channel::unbounded();
+        let (engine_action_sender, _engine_action_receiver) = crossbeam_channel::unbounded();
+        pollster::block_on(scene.initialize(
+            device,
+            queue,
+            workload_sender,
+            workload_output_receiver,
+            engine_action_sender,
+        ));
+
+        (scene, effect)
+    }
+
+    /// The copy into the screen-space input needs a single-sampled source. The resolve into the
+    /// raw render texture, added along with MSAA, provides it.
+    #[test]
+    fn msaa_scenes_resolve_before_screen_space_effects() {
+        const SIZE: u32 = 4;
+        const SAMPLE_COUNT: u32 = 4;
+        let depth_format = TextureBundle::DEPTH_FORMAT;
+        let (device, queue, renders) = rendering_device();
+        let (scene, effect) = create_scene_effect(&device, &queue);
+
+        let target = PipelineTarget {
+            sample_count: SAMPLE_COUNT,
+            ..PipelineTarget::new(COLOR_FORMAT)
+        };
+        let geometry_pipeline = create_render_pipeline(
+            &device,
+            &pipeline_id(),
+            None,
+            &target,
+            depth_format,
+            false,
+            false,
+            false,
+        )
+        .expect("The geometry pipeline should accept the multisampled target");
+        let effect = scene.get_material(effect).expect("The effect was created");
+        let effect_pipeline = create_render_pipeline(
+            &device,
+            effect.pipeline_id(),
+            effect.bind_group_layout(),
+            &PipelineTarget::new(COLOR_FORMAT),
+            depth_format,
+            false,
+            false,
+            false,
+        )
+        .expect("The effect pipeline should be created");
+        let pipelines = HashMap::from([(effect.pipeline_id().clone(), effect_pipeline)]);
+
+        let multisampled_targets = MultisampledTargets::new(
+            &device,
+            SIZE,
+            SIZE,
+            COLOR_FORMAT,
+            depth_format,
+            SAMPLE_COUNT,
+        );
+        let (depth_texture, _depth_bundle) =
+            TextureBundle::create_sized_depth_texture(&device, SIZE, SIZE, depth_format);
+        let screen_space_attachments = ScreenSpaceAttachments::new(
+            &device,
+            SIZE,
+            SIZE,
+            &target,
+            Vec::new(),
+            &depth_texture,
+            wgpu::FilterMode::Nearest,
+        );
+        let create_texture = |label, usage| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: SIZE,
+                    height: SIZE,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: COLOR_FORMAT,
+                usage,
+                view_formats: &[],
+            })
+        };
+        let raw_render_tex = create_texture(
+            "MSAA test raw render texture",
+            TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC,
+        );
+        let output = create_texture(
+            "MSAA test output",
+            TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        );
+        let raw_view = raw_render_tex.create_view(&wgpu::TextureViewDescriptor::default());
+        let output_view = output.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let error_scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("MSAA test encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("MSAA test pass"),
+                color_attachments: &[Some(RenderingManager::scene_color_attachment(
+                    Some(&multisampled_targets),
+                    &raw_view,
+                    false,
+                    wgpu::Color::BLACK,
+                ))],
+                depth_stencil_attachment: Some(RenderingManager::depth_stencil_attachment(
+                    &multisampled_targets.depth_view,
+                    wgpu::LoadOp::Clear(1.0),
+                    depth_format,
+                )),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                multiview_mask: None,
+            });
+            render_pass.set_pipeline(&geometry_pipeline);
+            render_pass.draw(0..3, 0..1);
+        }
+        RenderingManager::render_screen_space_effects(
+            &scene,
+            SIZE,
+            SIZE,
+            None,
+            &pipelines,
+            &output_view,
+            &device,
+            &raw_render_tex,
+            COLOR_FORMAT,
+            &screen_space_attachments,
+            &mut encoder,
+        );
+        queue.submit([encoder.finish()]);
+
+        assert!(
+            pollster::block_on(error_scope.pop()).is_none(),
+            "MSAA with a screen-space effect raised a validation error"
+        );
+        // The noop device validates but never draws, so only a real adapter has pixels to check
+        if renders {
+            let frame = RenderingManager::read_frame(&device, &queue, &output);
+            assert!(
+                frame.pixels().all(|pixel| pixel.0 == [255, 0, 0, 255]),
+                "The white scene should come out of the effect red"
+            );
+        }
+    }
+
+    const DESIGN_RESOLUTION: (u32, u32) = (320, 180);
+
+    #[test]
+    fn wide_windows_are_pillarboxed() {
+        let [x, y, width, height] = RenderingManager::letterbox(DESIGN_RESOLUTION, (1000, 360));
+
+        assert_eq!([x, y, width, height], [180.0, 0.0, 640.0, 360.0]);
+        assert_eq!(width / height, 320.0 / 180.0);
+    }
+
+    #[test]
+    fn tall_windows_are_letterboxed() {
+        let [x, y, width, height] = RenderingManager::letterbox(DESIGN_RESOLUTION, (640, 600));
+
+        assert_eq!([x, y, width, height], [0.0, 120.0, 640.0, 360.0]);
+        assert_eq!(width / height, 320.0 / 180.0);
+    }
+
+    #[test]
+    fn matching_windows_are_filled() {
+        assert_eq!(
+            RenderingManager::letterbox(DESIGN_RESOLUTION, (1280, 720)),
+            [0.0, 0.0, 1280.0, 720.0]
+        );
+    }
+
+    #[test]
+    fn cursors_are_mapped_into_the_design_resolution() {
+        let to_design = |position| {
+            RenderingManager::letterboxed_to_design(position, DESIGN_RESOLUTION, (1000, 360))
+        };
+
+        assert_eq!(to_design((180.0, 0.0)), (0.0, 0.0));
+        assert_eq!(to_design((500.0, 180.0)), (160.0, 90.0));
+        assert_eq!(to_design((820.0, 360.0)), (320.0, 180.0));
+        // The bars are outside of the design resolution
+        assert!(to_design((100.0, 10.0)).0 < 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "has no depth aspect")]
+    fn color_only_depth_format_is_rejected() {
+        pollster::block_on(
+            crate::V4Builder::default()
+                .backends(wgpu::Backends::NOOP)
+                .depth_format(TextureFormat::Rgba8Unorm)
+                .build_headless(),
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use wgpu::{Buffer, BufferUsages, Device};
+
+/// Recycles transient buffers by size class and usage. Buffers released during a frame become
+/// available again once the pool is reset, after the frame has been submitted.
+#[derive(Debug, Default)]
+pub struct BufferPool {
+    free_buffers: HashMap<(u64, BufferUsages), Vec<Buffer>>,
+    released_buffers: Vec<Buffer>,
+}
+
+impl BufferPool {
+    const MIN_BUFFER_SIZE: u64 = 256;
+
+    /// The returned buffer may be larger than `size`, as sizes are rounded up to the next power of two
+    pub fn acquire(
+        &mut self,
+        device: &Device,
+        size: u64,
+        usage: BufferUsages,
+        label: Option<&str>,
+    ) -> Buffer {
+        let size_class = Self::size_class(size);
+
+        if let Some(buffer) = self
+            .free_buffers
+            .get_mut(&(size_class, usage))
+            .and_then(|buffers| buffers.pop())
+        {
+            return buffer;
+        }
+
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label,
+            size: size_class,
+            usage,
+            mapped_at_creation: false,
+        })
+    }
+
+    pub fn release(&mut self, buffer: Buffer) {
+        self.released_buffers.push(buffer);
+    }
+
+    pub fn reset(&mut self) {
+        for buffer in self.released_buffers.drain(..) {
+            self.free_buffers
+                .entry((buffer.size(), buffer.usage()))
+                .or_default()
+                .push(buffer);
+        }
+    }
+
+    pub fn free_buffer_count(&self) -> usize {
+        self.free_buffers.values().map(Vec::len).sum()
+    }
+
+    fn size_class(size: u64) -> u64 {
+        size.max(Self::MIN_BUFFER_SIZE).next_power_of_two()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device() -> Device {
+        wgpu::Device::noop(&wgpu::DeviceDescriptor::default()).0
+    }
+
+    const USAGE: BufferUsages = BufferUsages::VERTEX.union(BufferUsages::COPY_DST);
+
+    #[test]
+    fn reuses_released_buffer_after_reset() {
+        let device = device();
+        let mut pool = BufferPool::default();
+
+        let buffer = pool.acquire(&device, 1024, USAGE, None);
+        pool.release(buffer.clone());
+        pool.reset();
+
+        assert_eq!(pool.free_buffer_count(), 1);
+        assert_eq!(pool.acquire(&device, 1024, USAGE, None), buffer);
+        assert_eq!(pool.free_buffer_count(), 0);
+    }
+
+    #[test]
+    fn keeps_released_buffer_until_reset() {
+        let device = device();
+        let mut pool = BufferPool::default();
+
+        let buffer = pool.acquire(&device, 1024, USAGE, None);
+        pool.release(buffer.clone());
+
+        assert_ne!(pool.acquire(&device, 1024, USAGE, None), buffer);
+    }
+
+    #[test]
+    fn rounds_sizes_up_to_power_of_two_classes() {
+        let device = device();
+        let mut pool = BufferPool::default();
+
+        assert_eq!(pool.acquire(&device, 1, USAGE, None).size(), 256);
+        assert_eq!(pool.acquire(&device, 300, USAGE, None).size(), 512);
+        assert_eq!(pool.acquire(&device, 512, USAGE, None).size(), 512);
+
+        let buffer = pool.acquire(&device, 300, USAGE, None);
+        pool.release(buffer.clone());
+        pool.reset();
+        assert_eq!(pool.acquire(&device, 400, USAGE, None), buffer);
+    }
+
+    #[test]
+    fn does_not_share_buffers_across_usages() {
+        let device = device();
+        let mut pool = BufferPool::default();
+
+        let buffer = pool.acquire(&device, 1024, USAGE, None);
+        pool.release(buffer.clone());
+        pool.reset();
+
+        let uniform = BufferUsages::UNIFORM | BufferUsages::COPY_DST;
+        assert_ne!(pool.acquire(&device, 1024, uniform, None), buffer);
+        assert_eq!(pool.free_buffer_count(), 1);
+    }
+}
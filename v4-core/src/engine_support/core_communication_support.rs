@@ -1,19 +1,17 @@
-use async_scoped::TokioScope;
+use std::{thread::JoinHandle, time::Duration};
+
 use crossbeam_channel::{Receiver, Sender};
 
 use crate::{
-    ecs::{
-        component::ComponentId,
-        scene::{WorkloadOutput, WorkloadPacket},
-    },
+    ecs::scene::{Workload, WorkloadOutput, WorkloadOutputPacket, WorkloadPacket, WorkloadTimeout},
     engine_management::engine_action::EngineAction,
 };
 
 #[derive(Debug)]
 pub struct CoreCommunication {
     workload_sender: Sender<WorkloadPacket>,
-    workload_output_receiver: Receiver<(ComponentId, WorkloadOutput)>,
-    _workload_thread_handle: std::thread::JoinHandle<()>,
+    workload_output_receiver: Receiver<WorkloadOutputPacket>,
+    workload_executor: WorkloadExecutor,
     engine_action_sender: Sender<Box<dyn EngineAction>>,
     engine_action_receiver: Receiver<Box<dyn EngineAction>>,
 }
@@ -23,7 +21,7 @@ impl CoreCommunication {
         self.workload_sender.clone()
     }
 
-    pub fn workload_output_receiver(&self) -> Receiver<(ComponentId, WorkloadOutput)> {
+    pub fn workload_output_receiver(&self) -> Receiver<WorkloadOutputPacket> {
         self.workload_output_receiver.clone()
     }
 
@@ -34,43 +32,134 @@ impl CoreCommunication {
     pub fn engine_action_receiver(&self) -> Receiver<Box<dyn EngineAction>> {
         self.engine_action_receiver.clone()
     }
+
+    pub fn shutdown_workload_executor(&mut self) {
+        self.workload_executor.shutdown();
+    }
 }
 
-impl Default for CoreCommunication {
-    fn default() -> Self {
-        let (workload_sender, workload_receiver): (
-            Sender<WorkloadPacket>,
-            Receiver<WorkloadPacket>,
-        ) = crossbeam_channel::unbounded();
+/// Runs workloads on a dedicated thread with its own tokio runtime. The thread is stopped and
+/// joined when the executor is dropped.
+#[derive(Debug)]
+pub struct WorkloadExecutor {
+    shutdown_sender: Sender<()>,
+    thread_handle: Option<JoinHandle<()>>,
+}
 
-        let (workload_output_sender, workload_output_receiver): (
-            Sender<(ComponentId, WorkloadOutput)>,
-            Receiver<_>,
-        ) = crossbeam_channel::unbounded();
+impl WorkloadExecutor {
+    fn launch(
+        workload_receiver: Receiver<WorkloadPacket>,
+        workload_output_sender: Sender<WorkloadOutputPacket>,
+    ) -> Self {
+        let (shutdown_sender, shutdown_receiver) = crossbeam_channel::bounded::<()>(1);
 
-        let _workload_thread_handle = std::thread::spawn(move || {
+        let thread_handle = std::thread::spawn(move || {
             let runtime = tokio::runtime::Runtime::new()
                 .expect("Failed to create tokio runtime for workloads.");
-            runtime.block_on(async move {
-                TokioScope::scope_and_block(|async_scope| {
-                    if let Ok(workload_packet) = workload_receiver.try_recv() {
+            loop {
+                crossbeam_channel::select! {
+                    recv(workload_receiver) -> workload_packet => {
+                        let Ok(workload_packet) = workload_packet else {
+                            break;
+                        };
                         let sender = workload_output_sender.clone();
-                        async_scope.spawn(async move {
-                            let workload_result = workload_packet.workload.await;
+                        runtime.spawn(async move {
+                            let WorkloadPacket {
+                                component_id,
+                                output_key,
+                                workload,
+                                cancel_receiver,
+                                timeout,
+                                ..
+                            } = workload_packet;
+                            // A handle dropped without cancelling lets the workload finish
+                            let cancelled = async move {
+                                if let Some(cancel_receiver) = cancel_receiver
+                                    && cancel_receiver.await.is_ok()
+                                {
+                                    return;
+                                }
+                                std::future::pending::<()>().await
+                            };
+                            let workload = run_workload(workload, timeout);
+                            let workload_result = tokio::select! {
+                                workload_result = workload => workload_result,
+                                _ = cancelled => return,
+                            };
                             sender
-                                .send((workload_packet.component_id, workload_result))
+                                .send(WorkloadOutputPacket {
+                                    component_id,
+                                    output_key,
+                                    output: workload_result,
+                                })
                                 .unwrap_or_else(|_| {
                                     panic!(
                                         "Failed to send workload output for component {}",
-                                        workload_packet.component_id
+                                        component_id
                                     )
                                 });
                         });
                     }
-                });
-            });
+                    recv(shutdown_receiver) -> _ => break,
+                }
+            }
+            runtime.shutdown_background();
         });
 
+        Self {
+            shutdown_sender,
+            thread_handle: Some(thread_handle),
+        }
+    }
+
+    /// Stops accepting workloads, cancels the ones still running and joins the thread. A panic
+    /// of the thread is logged rather than resumed, since this also runs while dropping.
+    pub fn shutdown(&mut self) {
+        if let Some(thread_handle) = self.thread_handle.take() {
+            let _ = self.shutdown_sender.try_send(());
+            if let Err(panic) = thread_handle.join() {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("unknown panic");
+                log::warn!("The workload executor thread panicked: {message}");
+            }
+        }
+    }
+}
+
+/// Awaits `workload`, dropping it with a `WorkloadTimeout` output once `timeout` passes
+async fn run_workload(workload: Workload, timeout: Option<Duration>) -> WorkloadOutput {
+    let Some(timeout) = timeout else {
+        return workload.await;
+    };
+    match tokio::time::timeout(timeout, workload).await {
+        Ok(output) => output,
+        Err(_) => Box::new(WorkloadTimeout { timeout }),
+    }
+}
+
+impl Drop for WorkloadExecutor {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl Default for CoreCommunication {
+    fn default() -> Self {
+        let (workload_sender, workload_receiver): (
+            Sender<WorkloadPacket>,
+            Receiver<WorkloadPacket>,
+        ) = crossbeam_channel::unbounded();
+
+        let (workload_output_sender, workload_output_receiver): (
+            Sender<WorkloadOutputPacket>,
+            Receiver<_>,
+        ) = crossbeam_channel::unbounded();
+
+        let workload_executor = WorkloadExecutor::launch(workload_receiver, workload_output_sender);
+
         let (engine_action_sender, engine_action_receiver): (
             Sender<Box<dyn EngineAction>>,
             Receiver<Box<dyn EngineAction>>,
@@ -79,9 +168,59 @@ impl Default for CoreCommunication {
         Self {
             workload_sender,
             workload_output_receiver,
-            _workload_thread_handle,
+            workload_executor,
             engine_action_sender,
             engine_action_receiver,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crossbeam_channel::TryRecvError;
+
+    use super::*;
+
+    fn launch() -> (
+        WorkloadExecutor,
+        Sender<WorkloadPacket>,
+        Receiver<WorkloadOutputPacket>,
+    ) {
+        let (workload_sender, workload_receiver) = crossbeam_channel::unbounded();
+        let (workload_output_sender, workload_output_receiver) = crossbeam_channel::unbounded();
+        let executor = WorkloadExecutor::launch(workload_receiver, workload_output_sender);
+
+        (executor, workload_sender, workload_output_receiver)
+    }
+
+    #[test]
+    fn dropping_the_executor_stops_its_thread() {
+        // Holding the workload sender keeps the thread from exiting on a disconnected channel
+        let (executor, _workload_sender, workload_output_receiver) = launch();
+        assert!(matches!(
+            workload_output_receiver.try_recv(),
+            Err(TryRecvError::Empty)
+        ));
+
+        drop(executor);
+
+        // The thread owned the output sender, which is gone once it has been joined
+        assert!(matches!(
+            workload_output_receiver.try_recv(),
+            Err(TryRecvError::Disconnected)
+        ));
+    }
+
+    #[test]
+    fn dropping_after_a_shutdown_does_not_join_twice() {
+        let (mut executor, _workload_sender, workload_output_receiver) = launch();
+
+        executor.shutdown();
+        assert!(executor.thread_handle.is_none());
+        assert!(matches!(
+            workload_output_receiver.try_recv(),
+            Err(TryRecvError::Disconnected)
+        ));
+        drop(executor);
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Polls a file's modification time, for reloading assets while the engine is running
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+}
+
+impl FileWatcher {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let last_modified = Self::modified_time(&path);
+
+        Self {
+            path,
+            last_modified,
+        }
+    }
+
+    /// Returns true once per modification of the file since the last call
+    pub fn has_changed(&mut self) -> bool {
+        let modified = Self::modified_time(&self.path);
+        if modified.is_some() && modified != self.last_modified {
+            self.last_modified = modified;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
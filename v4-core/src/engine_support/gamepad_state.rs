@@ -0,0 +1,145 @@
+use std::collections::{HashMap, HashSet};
+
+use gilrs::{EventType, Gilrs};
+
+pub use gilrs::{Axis, Button, GamepadId};
+
+/// The state of one connected gamepad
+#[derive(Debug, Default, Clone)]
+pub struct PadState {
+    name: String,
+    pressed_buttons: HashSet<Button>,
+    held_buttons: HashSet<Button>,
+    released_buttons: HashSet<Button>,
+    axes: HashMap<Axis, f32>,
+}
+
+impl PadState {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The connected gamepads and their buttons and axes, polled by the engine at the start of every
+/// frame. Presses, releases, connections and disconnections last for one frame, like key presses.
+#[derive(Debug, Default, Clone)]
+pub struct GamepadState {
+    pads: HashMap<GamepadId, PadState>,
+    connected: Vec<GamepadId>,
+    disconnected: Vec<GamepadId>,
+}
+
+impl GamepadState {
+    /// Starts with the gamepads that were connected before the engine started
+    pub fn new(gilrs: &Gilrs) -> Self {
+        Self {
+            pads: gilrs
+                .gamepads()
+                .map(|(id, gamepad)| {
+                    (
+                        id,
+                        PadState {
+                            name: gamepad.name().to_string(),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Clears the previous frame's presses and releases, then applies the events received since
+    pub fn poll(&mut self, gilrs: &mut Gilrs) {
+        self.connected.clear();
+        self.disconnected.clear();
+        for pad in self.pads.values_mut() {
+            pad.pressed_buttons.clear();
+            pad.released_buttons.clear();
+        }
+
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::Connected => {
+                    self.pads.insert(
+                        event.id,
+                        PadState {
+                            name: gilrs.gamepad(event.id).name().to_string(),
+                            ..Default::default()
+                        },
+                    );
+                    self.connected.push(event.id);
+                }
+                EventType::Disconnected => {
+                    self.pads.remove(&event.id);
+                    self.disconnected.push(event.id);
+                }
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(pad) = self.pads.get_mut(&event.id)
+                        && pad.held_buttons.insert(button)
+                    {
+                        pad.pressed_buttons.insert(button);
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(pad) = self.pads.get_mut(&event.id)
+                        && pad.held_buttons.remove(&button)
+                    {
+                        pad.released_buttons.insert(button);
+                    }
+                }
+                EventType::AxisChanged(axis, value, _) => {
+                    if let Some(pad) = self.pads.get_mut(&event.id) {
+                        pad.axes.insert(axis, value);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn pad(&self, pad: GamepadId) -> Option<&PadState> {
+        self.pads.get(&pad)
+    }
+
+    pub fn connected_pads(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.pads.keys().copied()
+    }
+
+    /// The gamepads plugged in since the previous frame
+    pub fn just_connected(&self) -> &[GamepadId] {
+        &self.connected
+    }
+
+    /// The gamepads unplugged since the previous frame, they are no longer in `connected_pads`
+    pub fn just_disconnected(&self) -> &[GamepadId] {
+        &self.disconnected
+    }
+
+    pub fn button_pressed(&self, pad: GamepadId, button: Button) -> bool {
+        self.pads
+            .get(&pad)
+            .is_some_and(|pad| pad.pressed_buttons.contains(&button))
+    }
+
+    pub fn button_held(&self, pad: GamepadId, button: Button) -> bool {
+        self.pads
+            .get(&pad)
+            .is_some_and(|pad| pad.held_buttons.contains(&button))
+    }
+
+    pub fn button_released(&self, pad: GamepadId, button: Button) -> bool {
+        self.pads
+            .get(&pad)
+            .is_some_and(|pad| pad.released_buttons.contains(&button))
+    }
+
+    /// Between -1 and 1, 0 for axes that haven't moved and for disconnected gamepads
+    pub fn axis(&self, pad: GamepadId, axis: Axis) -> f32 {
+        self.pads
+            .get(&pad)
+            .and_then(|pad| pad.axes.get(&axis))
+            .copied()
+            .unwrap_or(0.0)
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use winit::{
+    event::MouseButton,
+    keyboard::{Key, KeyCode},
+};
+use winit_input_helper::WinitInputHelper;
+
+/// The input queries components can make during `update`. Implemented for `WinitInputHelper`,
+/// which the engine uses while running, and for `MockInput`, which scripts input for
+/// `V4::step_frame`.
+pub trait InputState {
+    fn key_pressed(&self, key: KeyCode) -> bool;
+
+    fn key_held(&self, key: KeyCode) -> bool;
+
+    fn key_released(&self, key: KeyCode) -> bool;
+
+    fn mouse_pressed(&self, button: MouseButton) -> bool;
+
+    fn mouse_held(&self, button: MouseButton) -> bool;
+
+    fn mouse_released(&self, button: MouseButton) -> bool;
+
+    fn cursor(&self) -> Option<(f32, f32)>;
+
+    /// How far the cursor moved since the previous step
+    fn cursor_diff(&self) -> (f32, f32);
+
+    fn scroll_diff(&self) -> (f32, f32);
+
+    /// Keys that produced text this step, in the order they were typed
+    fn text(&self) -> &[Key];
+}
+
+impl InputState for WinitInputHelper {
+    fn key_pressed(&self, key: KeyCode) -> bool {
+        WinitInputHelper::key_pressed(self, key)
+    }
+
+    fn key_held(&self, key: KeyCode) -> bool {
+        WinitInputHelper::key_held(self, key)
+    }
+
+    fn key_released(&self, key: KeyCode) -> bool {
+        WinitInputHelper::key_released(self, key)
+    }
+
+    fn mouse_pressed(&self, button: MouseButton) -> bool {
+        WinitInputHelper::mouse_pressed(self, button)
+    }
+
+    fn mouse_held(&self, button: MouseButton) -> bool {
+        WinitInputHelper::mouse_held(self, button)
+    }
+
+    fn mouse_released(&self, button: MouseButton) -> bool {
+        WinitInputHelper::mouse_released(self, button)
+    }
+
+    fn cursor(&self) -> Option<(f32, f32)> {
+        WinitInputHelper::cursor(self)
+    }
+
+    fn cursor_diff(&self) -> (f32, f32) {
+        WinitInputHelper::cursor_diff(self)
+    }
+
+    fn scroll_diff(&self) -> (f32, f32) {
+        WinitInputHelper::scroll_diff(self)
+    }
+
+    fn text(&self) -> &[Key] {
+        WinitInputHelper::text(self)
+    }
+}
+
+/// Input that is set by hand instead of coming from window events. Presses and releases last for
+/// one step, so call `end_step` between frames the same way the engine steps `WinitInputHelper`.
+#[derive(Debug, Default, Clone)]
+pub struct MockInput {
+    pressed_keys: HashSet<KeyCode>,
+    held_keys: HashSet<KeyCode>,
+    released_keys: HashSet<KeyCode>,
+    pressed_buttons: HashSet<MouseButton>,
+    held_buttons: HashSet<MouseButton>,
+    released_buttons: HashSet<MouseButton>,
+    cursor: Option<(f32, f32)>,
+    cursor_diff: (f32, f32),
+    scroll_diff: (f32, f32),
+    text: Vec<Key>,
+}
+
+impl MockInput {
+    pub fn press_key(&mut self, key: KeyCode) {
+        if self.held_keys.insert(key) {
+            self.pressed_keys.insert(key);
+        }
+    }
+
+    pub fn release_key(&mut self, key: KeyCode) {
+        if self.held_keys.remove(&key) {
+            self.released_keys.insert(key);
+        }
+    }
+
+    pub fn press_mouse(&mut self, button: MouseButton) {
+        if self.held_buttons.insert(button) {
+            self.pressed_buttons.insert(button);
+        }
+    }
+
+    pub fn release_mouse(&mut self, button: MouseButton) {
+        if self.held_buttons.remove(&button) {
+            self.released_buttons.insert(button);
+        }
+    }
+
+    /// Also records the movement from the previous position as this step's cursor diff
+    pub fn set_cursor(&mut self, cursor: Option<(f32, f32)>) {
+        if let (Some(previous), Some(current)) = (self.cursor, cursor) {
+            self.cursor_diff.0 += current.0 - previous.0;
+            self.cursor_diff.1 += current.1 - previous.1;
+        }
+        self.cursor = cursor;
+    }
+
+    pub fn scroll(&mut self, scroll_diff: (f32, f32)) {
+        self.scroll_diff.0 += scroll_diff.0;
+        self.scroll_diff.1 += scroll_diff.1;
+    }
+
+    pub fn type_text(&mut self, text: &str) {
+        self.text
+            .extend(text.chars().map(|c| Key::Character(c.to_string().into())));
+    }
+
+    /// Clears the presses, releases, movement and text of the current step while keeping held
+    /// keys, buttons and the cursor position
+    pub fn end_step(&mut self) {
+        self.pressed_keys.clear();
+        self.released_keys.clear();
+        self.pressed_buttons.clear();
+        self.released_buttons.clear();
+        self.cursor_diff = (0.0, 0.0);
+        self.scroll_diff = (0.0, 0.0);
+        self.text.clear();
+    }
+}
+
+impl InputState for MockInput {
+    fn key_pressed(&self, key: KeyCode) -> bool {
+        self.pressed_keys.contains(&key)
+    }
+
+    fn key_held(&self, key: KeyCode) -> bool {
+        self.held_keys.contains(&key)
+    }
+
+    fn key_released(&self, key: KeyCode) -> bool {
+        self.released_keys.contains(&key)
+    }
+
+    fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.pressed_buttons.contains(&button)
+    }
+
+    fn mouse_held(&self, button: MouseButton) -> bool {
+        self.held_buttons.contains(&button)
+    }
+
+    fn mouse_released(&self, button: MouseButton) -> bool {
+        self.released_buttons.contains(&button)
+    }
+
+    fn cursor(&self) -> Option<(f32, f32)> {
+        self.cursor
+    }
+
+    fn cursor_diff(&self) -> (f32, f32) {
+        self.cursor_diff
+    }
+
+    fn scroll_diff(&self) -> (f32, f32) {
+        self.scroll_diff
+    }
+
+    fn text(&self) -> &[Key] {
+        &self.text
+    }
+}
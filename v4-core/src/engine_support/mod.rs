@@ -1,3 +1,8 @@
+pub mod buffer_pool;
+pub mod file_watcher;
+pub mod gamepad_state;
+pub mod input_state;
 pub mod misc_utils;
 pub mod texture_support;
+pub mod window_shortcuts;
 pub mod core_communication_support;
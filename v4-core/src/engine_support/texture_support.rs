@@ -14,7 +14,12 @@ pub struct TextureProperties {
     pub is_filtered: bool,
     pub is_sampled: bool,
     pub is_hdr: bool,
+    /// OR-ed with `COPY_DST` and either `TEXTURE_BINDING` or `STORAGE_BINDING`, e.g.
+    /// `RENDER_ATTACHMENT | COPY_SRC` to draw into the texture and read it back
     pub extra_usages: TextureUsages,
+    /// Gives the texture a full mip chain, generated from the image by `from_bytes`. The format has
+    /// to be renderable and filterable, HDR, cube and storage textures never get mips.
+    pub generate_mipmaps: bool,
 }
 
 impl Default for TextureProperties {
@@ -27,6 +32,27 @@ impl Default for TextureProperties {
             is_sampled: true,
             is_hdr: false,
             extra_usages: TextureUsages::TEXTURE_BINDING,
+            generate_mipmaps: false,
+        }
+    }
+}
+
+/// Limits which mip levels a material's samplers read from. Raising `lod_min_clamp` forces coarser
+/// mips. wgpu samplers have no bias setting, so `lod_bias` is meant to be passed to
+/// `textureSampleBias` by the material's shaders.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplerLod {
+    pub lod_min_clamp: f32,
+    pub lod_max_clamp: f32,
+    pub lod_bias: f32,
+}
+
+impl Default for SamplerLod {
+    fn default() -> Self {
+        Self {
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 100.0,
+            lod_bias: 0.0,
         }
     }
 }
@@ -40,6 +66,7 @@ pub struct TextureBundle {
 pub type CompleteTexture = (WgpuTexture, TextureBundle);
 
 impl TextureBundle {
+    /// The depth format used unless another one is chosen on the engine builder
     pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
     pub fn new(view: TextureView, properties: TextureProperties) -> TextureBundle {
@@ -61,6 +88,7 @@ impl TextureBundle {
                     is_cubemap: false,
                     is_filtered: false,
                     extra_usages: props.extra_usages | TextureUsages::COPY_DST,
+                    generate_mipmaps: false,
                     ..props
                 },
                 (0, 0),
@@ -139,12 +167,66 @@ impl TextureBundle {
                     depth_or_array_layers: 1,
                 },
             );
+            if texture_bundle.0.mip_level_count() > 1 {
+                Self::generate_mipmaps(device, queue, &texture_bundle.0);
+            }
             texture_bundle
         };
 
         texture_bundle
     }
 
+    /// A cube texture from six tightly packed faces of `dimensions`, in the +X, -X, +Y, -Y, +Z, -Z
+    /// order of the cube's array layers
+    pub fn create_cubemap(
+        device: &Device,
+        queue: &Queue,
+        faces: [&[u8]; 6],
+        dimensions: (u32, u32),
+        format: TextureFormat,
+    ) -> CompleteTexture {
+        let complete_texture = Self::create_texture(
+            device,
+            dimensions.0,
+            dimensions.1,
+            TextureProperties {
+                format,
+                is_cubemap: true,
+                ..Default::default()
+            },
+        );
+        let face_size = wgpu::Extent3d {
+            width: dimensions.0,
+            height: dimensions.1,
+            depth_or_array_layers: 1,
+        };
+        let row_size = format.theoretical_memory_footprint(face_size) / dimensions.1 as u64;
+
+        for (layer, face) in faces.into_iter().enumerate() {
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &complete_texture.0,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                face,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(row_size as u32),
+                    rows_per_image: Some(dimensions.1),
+                },
+                face_size,
+            );
+        }
+
+        complete_texture
+    }
+
     pub fn create_texture(
         device: &wgpu::Device,
         width: u32,
@@ -156,8 +238,11 @@ impl TextureBundle {
             storage_texture,
             is_cubemap,
             extra_usages,
+            generate_mipmaps,
             ..
         } = properties;
+        let has_mipmaps =
+            generate_mipmaps && storage_texture.is_none() && !is_cubemap && !properties.is_hdr;
 
         let size = wgpu::Extent3d {
             width,
@@ -168,7 +253,11 @@ impl TextureBundle {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("New created texture"),
             size,
-            mip_level_count: 1,
+            mip_level_count: if has_mipmaps {
+                size.max_mips(wgpu::TextureDimension::D2)
+            } else {
+                1
+            },
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format,
@@ -178,6 +267,11 @@ impl TextureBundle {
                     TextureUsages::STORAGE_BINDING
                 } else {
                     TextureUsages::TEXTURE_BINDING
+                }
+                | if has_mipmaps {
+                    TextureUsages::RENDER_ATTACHMENT
+                } else {
+                    TextureUsages::empty()
                 },
             view_formats: &[],
         });
@@ -203,13 +297,117 @@ impl TextureBundle {
         (texture, TextureBundle { view, properties })
     }
 
+    /// Fills every mip level after the first by drawing the previous level into it with linear
+    /// filtering. Odd sizes round down, so the last levels of a non-power-of-two texture are 1 wide.
+    pub fn generate_mipmaps(device: &Device, queue: &Queue, texture: &WgpuTexture) {
+        let format = texture.format();
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Mipmap blit shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "../default_shaders/mipmap_blit.wgsl"
+            ))),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mipmap blit pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment"),
+                compilation_options: Default::default(),
+                targets: &[Some(format.into())],
+            }),
+            multiview_mask: None,
+            cache: None,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Mipmap blit sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group_layout = pipeline.get_bind_group_layout(0);
+
+        let level_view = |mip_level: u32| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_mip_level: mip_level,
+                mip_level_count: Some(1),
+                base_array_layer: 0,
+                array_layer_count: Some(1),
+                ..Default::default()
+            })
+        };
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Mipmap generation encoder"),
+        });
+        for mip_level in 1..texture.mip_level_count() {
+            let source_view = level_view(mip_level - 1);
+            let target_view = level_view(mip_level);
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mipmap blit bind group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+            });
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap blit render pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target_view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                multiview_mask: None,
+            });
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        queue.submit([encoder.finish()]);
+    }
+
     pub fn create_depth_texture(
         device: &wgpu::Device,
         config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+    ) -> CompleteTexture {
+        Self::create_sized_depth_texture(device, config.width, config.height, format)
+    }
+
+    pub fn create_sized_depth_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
     ) -> CompleteTexture {
         let size = wgpu::Extent3d {
-            width: config.width,
-            height: config.height,
+            width,
+            height,
             depth_or_array_layers: 1,
         };
         let desc = wgpu::TextureDescriptor {
@@ -218,7 +416,7 @@ impl TextureBundle {
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: Self::DEPTH_FORMAT,
+            format,
             usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
             view_formats: &[],
         };
@@ -231,13 +429,14 @@ impl TextureBundle {
             TextureBundle {
                 view,
                 properties: TextureProperties {
-                    format: Self::DEPTH_FORMAT,
+                    format,
                     storage_texture: None,
                     is_cubemap: false,
                     is_filtered: false,
                     is_sampled: true,
                     is_hdr: false,
                     extra_usages: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                    generate_mipmaps: false,
                 },
             },
         )
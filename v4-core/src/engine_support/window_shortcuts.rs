@@ -0,0 +1,89 @@
+use winit::keyboard::KeyCode;
+
+use super::input_state::InputState;
+
+/// The shortcuts the engine handles itself unless disabled with `V4Builder::default_shortcuts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowShortcut {
+    /// Alt+Enter or F11
+    ToggleFullscreen,
+    /// Ctrl+Q
+    Exit,
+}
+
+impl WindowShortcut {
+    /// The shortcut triggered by the keys pressed this step, if any
+    pub fn from_input(input: &dyn InputState) -> Option<Self> {
+        let alt_held = input.key_held(KeyCode::AltLeft) || input.key_held(KeyCode::AltRight);
+        let control_held =
+            input.key_held(KeyCode::ControlLeft) || input.key_held(KeyCode::ControlRight);
+
+        if input.key_pressed(KeyCode::F11)
+            || (alt_held
+                && (input.key_pressed(KeyCode::Enter) || input.key_pressed(KeyCode::NumpadEnter)))
+        {
+            Some(WindowShortcut::ToggleFullscreen)
+        } else if control_held && input.key_pressed(KeyCode::KeyQ) {
+            Some(WindowShortcut::Exit)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_support::input_state::MockInput;
+
+    fn shortcut_for(keys: &[KeyCode]) -> Option<WindowShortcut> {
+        let mut input = MockInput::default();
+        for &key in keys {
+            input.press_key(key);
+        }
+        WindowShortcut::from_input(&input)
+    }
+
+    #[test]
+    fn alt_enter_toggles_fullscreen() {
+        assert_eq!(
+            shortcut_for(&[KeyCode::AltLeft, KeyCode::Enter]),
+            Some(WindowShortcut::ToggleFullscreen)
+        );
+        assert_eq!(
+            shortcut_for(&[KeyCode::AltRight, KeyCode::NumpadEnter]),
+            Some(WindowShortcut::ToggleFullscreen)
+        );
+        assert_eq!(
+            shortcut_for(&[KeyCode::F11]),
+            Some(WindowShortcut::ToggleFullscreen)
+        );
+        assert_eq!(shortcut_for(&[KeyCode::Enter]), None);
+    }
+
+    #[test]
+    fn alt_held_from_an_earlier_step_still_counts() {
+        let mut input = MockInput::default();
+        input.press_key(KeyCode::AltLeft);
+        input.end_step();
+        input.press_key(KeyCode::Enter);
+
+        assert_eq!(
+            WindowShortcut::from_input(&input),
+            Some(WindowShortcut::ToggleFullscreen)
+        );
+
+        // Holding both keys doesn't toggle again on the next step
+        input.end_step();
+        assert_eq!(WindowShortcut::from_input(&input), None);
+    }
+
+    #[test]
+    fn control_q_exits() {
+        assert_eq!(
+            shortcut_for(&[KeyCode::ControlRight, KeyCode::KeyQ]),
+            Some(WindowShortcut::Exit)
+        );
+        assert_eq!(shortcut_for(&[KeyCode::KeyQ]), None);
+    }
+}
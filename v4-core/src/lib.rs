@@ -1,26 +1,25 @@
 use async_scoped::TokioScope;
-use ecs::scene::Scene;
+use ecs::{render_target::RenderTarget, scene::Scene, system::System};
 use egui::{FontDefinitions, Style};
 use egui_winit_platform::{Platform, PlatformDescriptor};
 use engine_management::{
-    engine_action::V4Mutable,
+    engine_action::{ToggleFullscreenEngineAction, V4Mutable},
     font_management::FontState,
-    pipeline::{PipelineId, create_render_pipeline},
+    pipeline_cache::PipelineCache,
     rendering_management::RenderingManager,
 };
 use glyphon::{FontSystem, SwashCache, TextAtlas, TextRenderer};
 use std::{
     collections::{HashMap, HashSet},
     fmt::Debug,
-    time::Instant,
+    time::{Duration, Instant},
 };
-use wgpu::Device;
-use wgpu::{RenderPipeline, TextureFormat};
+use wgpu::TextureFormat;
 
 use winit::{
     application::ApplicationHandler,
     dpi::PhysicalSize,
-    event::{DeviceEvent, DeviceId, MouseButton, WindowEvent},
+    event::{DeviceEvent, DeviceId, ElementState, Ime, MouseButton, MouseScrollDelta, WindowEvent},
     event_loop::{ActiveEventLoop, EventLoop},
     window::{Window, WindowAttributes},
 };
@@ -28,7 +27,10 @@ use winit_input_helper::WinitInputHelper;
 
 use crate::{
     engine_management::rendering_management::RenderingManagerDetails,
-    engine_support::core_communication_support::CoreCommunication,
+    engine_support::{
+        core_communication_support::CoreCommunication, gamepad_state::GamepadState,
+        input_state::InputState, texture_support::TextureBundle, window_shortcuts::WindowShortcut,
+    },
 };
 
 pub mod engine_management;
@@ -40,38 +42,154 @@ pub mod ecs;
 /// The main engine struct. Contains the state for the whole engine.
 #[derive(Debug)]
 pub struct V4 {
-    event_loop: EventLoop,
+    /// `None` when built with `V4Builder::build_headless`
+    event_loop: Option<EventLoop>,
     app: V4App,
 }
 
 struct V4App {
     window_attributes: WindowAttributes,
     input_manager: WinitInputHelper,
+    /// `None` when the platform's gamepad backend failed to start
+    gilrs: Option<gilrs::Gilrs>,
+    gamepads: GamepadState,
     rendering_manager: RenderingManager,
     scenes: Vec<Scene>,
+    systems: Vec<Box<dyn System>>,
     last_active_scene_index: usize,
     active_scene: usize,
     initialized_scene: bool,
     window: Option<Box<dyn Window>>,
     details: EngineDetails,
-    pipelines: HashMap<PipelineId, RenderPipeline>,
+    /// The pipelines of the main pass
+    pipelines: PipelineCache,
+    render_target_pipelines: PipelineCache,
     font_state: Option<FontState>,
+    font_paths: Vec<String>,
     hide_cursor: bool,
     core_communication: CoreCommunication,
     egui_platform: Option<Platform>,
     egui_clear_color: Option<wgpu::Color>,
+    default_shortcuts: bool,
+    exit_requested: bool,
+    fixed_timestep: Option<Duration>,
+    fixed_time_accumulator: Duration,
 }
 
+/// Fixed updates a single frame may run to catch up, the rest of the backlog is dropped so a slow
+/// frame can't make the following ones slower
+const MAX_FIXED_STEPS_PER_FRAME: u32 = 5;
+
 #[derive(Debug)]
 pub struct EngineDetails {
     pub initialization_time: Instant,
     pub frames_elapsed: u128,
     pub last_frame_instant: Instant,
+    /// Time between the start of the previous frame and the start of this one, set before the
+    /// scene is updated. The first frame reports a 60 FPS frame.
+    pub delta_time: Duration,
     pub window_resolution: (u32, u32),
     pub scale_factor: f32,
     pub cursor_position: (u32, u32),
+    /// The mouse buttons currently held down
     pub mouse_state: HashSet<MouseButton>,
+    /// The longest time between two presses of a button that still counts as a double click
+    pub double_click_interval: Duration,
     pub cursor_delta: (f32, f32),
+    /// Horizontal and vertical scrolling during this frame, in lines for mouse wheels and in
+    /// pixels for touchpads that report precise deltas
+    pub scroll_delta: (f32, f32),
+    /// CPU time of the previous frame's systems, component and material updates and actions
+    pub update_duration: Duration,
+    /// CPU time of the previous frame's compute dispatches
+    pub compute_duration: Duration,
+    /// CPU time of the previous frame's pipeline creation and render recording
+    pub render_duration: Duration,
+    typed_this_frame: String,
+    mouse_just_pressed: HashSet<MouseButton>,
+    mouse_just_released: HashSet<MouseButton>,
+    mouse_double_clicked: HashSet<MouseButton>,
+    last_mouse_presses: HashMap<MouseButton, Instant>,
+    frame_start_instant: Option<Instant>,
+}
+
+impl EngineDetails {
+    const FIRST_FRAME_DELTA: Duration = Duration::from_nanos(16_666_667);
+    const DEFAULT_DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta_time.as_secs_f32()
+    }
+
+    /// All text typed since the previous frame, including keystrokes from several events
+    pub fn typed_this_frame(&self) -> &str {
+        &self.typed_this_frame
+    }
+
+    /// Whether the button went down since the previous frame
+    pub fn mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_just_pressed.contains(&button)
+    }
+
+    /// Whether the button went up since the previous frame
+    pub fn mouse_just_released(&self, button: MouseButton) -> bool {
+        self.mouse_just_released.contains(&button)
+    }
+
+    /// Whether the button was pressed twice within `double_click_interval`, the second press being
+    /// since the previous frame. A third quick press starts a new double click.
+    pub fn mouse_double_clicked(&self, button: MouseButton) -> bool {
+        self.mouse_double_clicked.contains(&button)
+    }
+
+    /// Records text from a key press or an IME commit, appended to what was already typed this
+    /// frame
+    pub fn handle_typed_text(&mut self, state: ElementState, text: &str) {
+        if state == ElementState::Pressed {
+            self.typed_this_frame.push_str(text);
+        }
+    }
+
+    /// Records a button event received at `time`
+    pub fn handle_mouse_button(&mut self, button: MouseButton, state: ElementState, time: Instant) {
+        match state {
+            ElementState::Pressed => {
+                // Repeated presses without a release in between don't count as clicks
+                if !self.mouse_state.insert(button) {
+                    return;
+                }
+                self.mouse_just_pressed.insert(button);
+                match self.last_mouse_presses.remove(&button) {
+                    Some(last_press) if time - last_press <= self.double_click_interval => {
+                        self.mouse_double_clicked.insert(button);
+                    }
+                    _ => {
+                        self.last_mouse_presses.insert(button, time);
+                    }
+                }
+            }
+            ElementState::Released => {
+                if self.mouse_state.remove(&button) {
+                    self.mouse_just_released.insert(button);
+                }
+            }
+        }
+    }
+
+    /// Releases every held button, for when their release events can't arrive
+    pub fn release_mouse_buttons(&mut self) {
+        self.mouse_just_released.extend(self.mouse_state.drain());
+    }
+
+    /// Clears the input that only lasts a frame, called once the frame is rendered
+    fn end_frame_input(&mut self) {
+        self.cursor_delta = (0.0, 0.0);
+        self.scroll_delta = (0.0, 0.0);
+        self.typed_this_frame.clear();
+        self.mouse_just_pressed.clear();
+        self.mouse_just_released.clear();
+        self.mouse_double_clicked.clear();
+    }
 }
 
 impl Default for EngineDetails {
@@ -80,11 +198,23 @@ impl Default for EngineDetails {
             initialization_time: Instant::now(),
             frames_elapsed: 0,
             last_frame_instant: Instant::now(),
+            delta_time: Self::FIRST_FRAME_DELTA,
             window_resolution: (0, 0),
             scale_factor: 1.0,
             cursor_position: (0, 0),
             mouse_state: HashSet::new(),
+            double_click_interval: Self::DEFAULT_DOUBLE_CLICK_INTERVAL,
             cursor_delta: (0.0, 0.0),
+            scroll_delta: (0.0, 0.0),
+            update_duration: Duration::ZERO,
+            compute_duration: Duration::ZERO,
+            render_duration: Duration::ZERO,
+            typed_this_frame: String::new(),
+            mouse_just_pressed: HashSet::new(),
+            mouse_just_released: HashSet::new(),
+            mouse_double_clicked: HashSet::new(),
+            last_mouse_presses: HashMap::new(),
+            frame_start_instant: None,
         }
     }
 }
@@ -94,11 +224,16 @@ impl V4 {
         V4Builder::default()
     }
 
-    pub async fn main_loop(mut self) {
-        self.app.details.initialization_time = Instant::now();
+    pub async fn main_loop(self) {
+        let V4 {
+            event_loop,
+            mut app,
+        } = self;
+        app.details.initialization_time = Instant::now();
 
-        self.event_loop
-            .run_app(self.app)
+        event_loop
+            .expect("A headless engine has no event loop, drive it with `step_frame` instead.")
+            .run_app(app)
             .expect("An error occured in the main loop.");
     }
 
@@ -109,6 +244,10 @@ impl V4 {
         index
     }
 
+    pub fn add_system(&mut self, system: Box<dyn System>) {
+        self.app.systems.push(system);
+    }
+
     pub fn scene_count(&self) -> usize {
         self.app.scenes.len()
     }
@@ -117,34 +256,118 @@ impl V4 {
         &self.app.rendering_manager
     }
 
+    pub fn rendering_manager_mut(&mut self) -> &mut RenderingManager {
+        &mut self.app.rendering_manager
+    }
+
+    /// Advances the active scene by a single frame without running the event loop, as if
+    /// `simulated_dt` had passed since the previous frame. Components see `input` instead of the
+    /// window's input, e.g. a `MockInput` scripted by a test. Nothing is rendered before the
+    /// window exists, which makes this usable for driving gameplay logic in tests with an engine
+    /// from `V4Builder::build_headless`.
+    pub fn step_frame(&mut self, simulated_dt: Duration, input: &dyn InputState) {
+        let now = Instant::now();
+        self.app.details.last_frame_instant = now.checked_sub(simulated_dt).unwrap_or(now);
+        self.app.run_frame(Some(input), Some(simulated_dt));
+    }
+
+    pub fn active_scene(&self) -> Option<&Scene> {
+        self.app.scenes.get(self.app.active_scene)
+    }
+
+    pub fn details(&self) -> &EngineDetails {
+        &self.app.details
+    }
+
+    /// Whether a scene or a shortcut asked the engine to exit. The event loop exits on its own,
+    /// code driving the engine with `step_frame` should stop once this returns true
+    pub fn exit_requested(&self) -> bool {
+        self.app.exit_requested
+    }
+
+    /// Collects the pipelines that finished compiling and starts compiling the ones the scene is
+    /// missing. Returns the last compilation error, if any
     fn create_new_pipelines(
-        device: &Device,
+        rendering_manager: &mut RenderingManager,
         render_format: TextureFormat,
         active_scene: &mut Scene,
-        pipelines: &mut HashMap<PipelineId, RenderPipeline>,
-    ) {
-        if active_scene.new_pipelines_needed {
-            let active_scene_pipelines = active_scene.get_pipeline_ids();
-            for pipeline_id in active_scene_pipelines {
-                if !pipelines.contains_key(pipeline_id) {
-                    let attachment_bind_group_layout =
-                        active_scene.get_pipeline_materials(pipeline_id)[0].bind_group_layout();
+        pipelines: &mut PipelineCache,
+        render_target_pipelines: &mut PipelineCache,
+    ) -> Option<String> {
+        let mut shader_error = None;
+        // Render targets are never multisampled and have no G-buffer, so with either they need
+        // their own pipelines even when their format matches the surface
+        if pipelines.set_target(rendering_manager.pipeline_target(render_format)) {
+            active_scene.new_pipelines_needed = true;
+        }
+        let compiled_pipelines = rendering_manager.poll_compiled_pipelines();
+        for (pipeline_id, target, result) in compiled_pipelines {
+            match result {
+                Ok(pipeline) => {
+                    let mut replaced = false;
+                    for cache in [&mut *render_target_pipelines, &mut *pipelines] {
+                        if *cache.target() == target {
+                            replaced |= cache
+                                .insert(pipeline_id.clone(), pipeline.clone())
+                                .is_some();
+                        }
+                    }
+                    // A reloaded shader that compiles fixes the error it may have shown before
+                    if replaced {
+                        rendering_manager.clear_shader_error();
+                    }
+                }
+                Err(error) => {
+                    log::warn!("Failed to create pipeline {pipeline_id:?}: {error}");
+                    shader_error = Some(error.to_string());
+                }
+            }
+        }
 
-                    pipelines.insert(
-                        pipeline_id.clone(),
-                        create_render_pipeline(
-                            device,
+        let changed_shaders = rendering_manager.changed_shader_paths();
+        if !changed_shaders.is_empty() {
+            for cache in [&*pipelines, &*render_target_pipelines] {
+                for pipeline_id in active_scene.get_pipeline_ids() {
+                    if pipeline_id
+                        .shader_paths()
+                        .any(|path| changed_shaders.contains(&path))
+                    {
+                        let attachment_bind_group_layout =
+                            active_scene.get_pipeline_materials(pipeline_id)[0].bind_group_layout();
+                        cache.recompile(
+                            rendering_manager,
                             pipeline_id,
                             attachment_bind_group_layout,
-                            render_format,
-                            pipeline_id.spirv_vertex_shader,
-                            pipeline_id.spirv_fragment_shader,
-                        ),
+                        );
+                    }
+                }
+            }
+        }
+
+        if active_scene.new_pipelines_needed {
+            let mut caches = vec![(&*pipelines, false)];
+            // Render targets have their own format, so they need their own copy of each pipeline
+            if !active_scene.render_targets().is_empty() {
+                caches.push((&*render_target_pipelines, true));
+            }
+
+            for (cache, is_render_target) in caches {
+                for pipeline_id in active_scene.get_pipeline_ids() {
+                    if is_render_target && pipeline_id.is_screen_space {
+                        continue;
+                    }
+                    let attachment_bind_group_layout =
+                        active_scene.get_pipeline_materials(pipeline_id)[0].bind_group_layout();
+                    cache.get_or_create(
+                        rendering_manager,
+                        pipeline_id,
+                        attachment_bind_group_layout,
                     );
                 }
             }
             active_scene.new_pipelines_needed = false;
         }
+        shader_error
     }
 }
 
@@ -175,6 +398,157 @@ impl V4App {
             style: Style::default(),
         }));
     }
+
+    /// Runs one frame: scene updates, actions, computes and, when a window exists, rendering
+    fn run_frame(&mut self, input: Option<&dyn InputState>, simulated_dt: Option<Duration>) {
+        if self.scenes.is_empty() {
+            return;
+        }
+        let frame_start = Instant::now();
+        self.details.delta_time = simulated_dt.unwrap_or_else(|| {
+            self.details
+                .frame_start_instant
+                .map_or(EngineDetails::FIRST_FRAME_DELTA, |previous_start| {
+                    frame_start - previous_start
+                })
+        });
+        self.details.frame_start_instant = Some(frame_start);
+        // Scripted frames keep the gamepads as they were, like they keep the window input
+        if let (None, Some(gilrs)) = (input, &mut self.gilrs) {
+            self.gamepads.poll(gilrs);
+        }
+        let input = input.unwrap_or(&self.input_manager);
+        if let Some(egui_platform) = self.egui_platform.as_mut() {
+            egui_platform.update_time(self.details.initialization_time.elapsed().as_secs_f64());
+        }
+        if self.scenes[self.active_scene].reload_changed_ron() {
+            self.initialized_scene = false;
+        }
+        let rendering_manager = &mut self.rendering_manager;
+        // A newly active scene is initialized and updated in the same frame
+        if self.active_scene != self.last_active_scene_index {
+            self.initialized_scene = false;
+            self.last_active_scene_index = self.active_scene;
+        }
+        if !self.initialized_scene {
+            let device = rendering_manager.device();
+            let queue = rendering_manager.queue();
+            let workload_output_receiver = self.core_communication.workload_output_receiver();
+
+            let workload_sender = self.core_communication.workload_sender();
+            let engine_action_sender = self.core_communication.engine_action_sender();
+            let scene = &mut self.scenes[self.active_scene];
+
+            TokioScope::scope_and_block(|scope| {
+                scope.spawn(async {
+                    let action_queue = scene
+                        .initialize(
+                            device,
+                            queue,
+                            workload_sender,
+                            workload_output_receiver,
+                            engine_action_sender,
+                        )
+                        .await;
+                    scene
+                        .execute_action_queue(action_queue, device, queue)
+                        .await;
+                });
+            });
+            self.initialized_scene = true;
+        }
+
+        // Engine actions act on the window, so they stay queued while running without one
+        if let Some(window) = self.window.as_deref() {
+            while let Ok(engine_action) =
+                self.core_communication.engine_action_receiver().try_recv()
+            {
+                engine_action.execute(V4Mutable {
+                    window,
+                    active_scene: &mut self.active_scene,
+                    initialized_scene: &mut self.initialized_scene,
+                    font_state: self.font_state.as_mut().unwrap(),
+                    rendering_manager: &mut *rendering_manager,
+                });
+            }
+        }
+
+        let scene = &mut self.scenes[self.active_scene];
+        let device = rendering_manager.device();
+        let queue = rendering_manager.queue();
+
+        let update_start = Instant::now();
+        for system in &mut self.systems {
+            system.run(scene, &self.details);
+        }
+
+        if let Some(fixed_delta) = self.fixed_timestep {
+            self.fixed_time_accumulator += self.details.delta_time;
+            let mut steps = 0;
+            while self.fixed_time_accumulator >= fixed_delta && steps < MAX_FIXED_STEPS_PER_FRAME {
+                let action_queue = scene.fixed_update(
+                    device,
+                    queue,
+                    input,
+                    &self.gamepads,
+                    &self.details,
+                    fixed_delta,
+                );
+                pollster::block_on(scene.execute_action_queue(action_queue, device, queue));
+                self.fixed_time_accumulator -= fixed_delta;
+                steps += 1;
+            }
+            if self.fixed_time_accumulator >= fixed_delta {
+                self.fixed_time_accumulator = Duration::from_nanos(
+                    (self.fixed_time_accumulator.as_nanos() % fixed_delta.as_nanos()) as u64,
+                );
+            }
+        }
+
+        let action_queue = scene.update(device, queue, input, &self.gamepads, &self.details);
+        pollster::block_on(scene.execute_action_queue(action_queue, device, queue));
+
+        // Materials read the groupings left by this frame's actions
+        scene.update_materials(device, queue, input, &self.gamepads, &self.details);
+        self.details.update_duration = update_start.elapsed();
+
+        let compute_start = Instant::now();
+        rendering_manager.individual_compute_execution(scene.computes());
+        self.details.compute_duration = compute_start.elapsed();
+
+        if scene.exit_requested() {
+            self.exit_requested = true;
+        }
+
+        let render_start = Instant::now();
+        if let Some(render_format) = rendering_manager.format() {
+            if let Some(shader_error) = V4::create_new_pipelines(
+                rendering_manager,
+                render_format,
+                scene,
+                &mut self.pipelines,
+                &mut self.render_target_pipelines,
+            ) {
+                rendering_manager.show_shader_error(shader_error);
+            }
+
+            pollster::block_on(rendering_manager.render(
+                scene,
+                self.pipelines.pipelines(),
+                self.font_state.as_mut().unwrap(),
+                self.egui_platform.as_mut().unwrap(),
+                self.window.as_deref(),
+                self.egui_clear_color,
+                &self.details,
+                self.render_target_pipelines.pipelines(),
+            ));
+        }
+        self.details.render_duration = render_start.elapsed();
+
+        self.details.frames_elapsed += 1;
+        self.details.last_frame_instant = Instant::now();
+        self.details.end_frame_input();
+    }
 }
 
 impl ApplicationHandler for V4App {
@@ -208,7 +582,7 @@ impl ApplicationHandler for V4App {
                 });
         }
 
-        let font_state = FontState {
+        let mut font_state = FontState {
             font_system,
             swash_cache,
             viewport,
@@ -216,6 +590,15 @@ impl ApplicationHandler for V4App {
             text_renderer,
             text_buffers: HashMap::new(),
         };
+        for path in &self.font_paths {
+            match font_state.load_font_from_path(path) {
+                Ok(family_names) if family_names.is_empty() => {
+                    log::warn!("The file {path} holds no fonts.");
+                }
+                Ok(_) => {}
+                Err(error) => log::warn!("Failed to load the font {path}: {error}"),
+            }
+        }
 
         self.egui_platform = Some(Platform::new(PlatformDescriptor {
             physical_width: window.surface_size().width,
@@ -260,91 +643,46 @@ impl ApplicationHandler for V4App {
             WindowEvent::CloseRequested => {
                 event_loop.exit();
             }
+            WindowEvent::KeyboardInput {
+                event: ref key_event,
+                ..
+            } => {
+                if let Some(text) = &key_event.text {
+                    self.details.handle_typed_text(key_event.state, text);
+                }
+            }
+            WindowEvent::Ime(Ime::Commit(ref text)) => {
+                self.details.handle_typed_text(ElementState::Pressed, text);
+            }
             WindowEvent::PointerMoved { position, .. } => {
-                self.details.cursor_position = (position.x as u32, position.y as u32);
+                let (x, y) = self
+                    .rendering_manager
+                    .window_to_design((position.x as f32, position.y as f32));
+                self.details.cursor_position = (x as u32, y as u32);
             }
-            WindowEvent::PointerButton { button, .. } => {
-                let button = button.clone().mouse_button().unwrap();
-                if self.details.mouse_state.contains(&button) {
-                    self.details.mouse_state.remove(&button);
-                } else {
-                    self.details.mouse_state.insert(button);
+            WindowEvent::PointerButton { button, state, .. } => {
+                // Touch and pen contacts have no mouse button to track
+                if let Some(button) = button.clone().mouse_button() {
+                    self.details
+                        .handle_mouse_button(button, state, Instant::now());
                 }
             }
+            // Buttons released while another window has focus never report their release
+            WindowEvent::Focused(false) => {
+                self.details.release_mouse_buttons();
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let (x, y) = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => (x, y),
+                    MouseScrollDelta::PixelDelta(position) => {
+                        (position.x as f32, position.y as f32)
+                    }
+                };
+                self.details.scroll_delta.0 += x;
+                self.details.scroll_delta.1 += y;
+            }
             WindowEvent::RedrawRequested => {
-                if self.scenes.is_empty() {
-                    return;
-                }
-                egui_platform
-                    .update_time(self.details.initialization_time.elapsed().as_secs_f64());
-                let rendering_manager = &mut self.rendering_manager;
-                if !self.initialized_scene {
-                    let device = rendering_manager.device();
-                    let queue = rendering_manager.queue();
-                    let workload_output_receiver =
-                        self.core_communication.workload_output_receiver();
-
-                    let action_queue = self.scenes[self.active_scene].initialize(
-                        device,
-                        self.core_communication.workload_sender(),
-                        workload_output_receiver,
-                        self.core_communication.engine_action_sender(),
-                    );
-                    TokioScope::scope_and_block(|scope| {
-                        scope.spawn(self.scenes[self.active_scene].execute_action_queue(
-                            action_queue,
-                            device,
-                            queue,
-                        ));
-                    });
-                    self.initialized_scene = true;
-                }
-                if self.active_scene != self.last_active_scene_index {
-                    self.initialized_scene = false;
-                    self.last_active_scene_index = self.active_scene;
-                    return;
-                }
-
-                while let Ok(engine_action) =
-                    self.core_communication.engine_action_receiver().try_recv()
-                {
-                    engine_action.execute(V4Mutable {
-                        window: self.window.as_deref().unwrap(),
-                        active_scene: &mut self.active_scene,
-                        initialized_scene: &mut self.initialized_scene,
-                        font_state: self.font_state.as_mut().unwrap(),
-                    });
-                }
-
-                let scene = &mut self.scenes[self.active_scene];
-                let device = rendering_manager.device();
-                let queue = rendering_manager.queue();
-
-                let action_queue = scene.update(device, queue, &self.input_manager, &self.details);
-                pollster::block_on(scene.execute_action_queue(action_queue, device, queue));
-
-                scene.update_materials(device, queue, &self.input_manager, &self.details);
-                rendering_manager.individual_compute_execution(scene.computes());
-
-                V4::create_new_pipelines(
-                    device,
-                    rendering_manager.format().unwrap(),
-                    scene,
-                    &mut self.pipelines,
-                );
-
-                pollster::block_on(rendering_manager.render(
-                    scene,
-                    &self.pipelines,
-                    self.font_state.as_mut().unwrap(),
-                    egui_platform,
-                    self.window.as_deref(),
-                    self.egui_clear_color,
-                ));
-
-                self.details.frames_elapsed += 1;
-                self.details.last_frame_instant = Instant::now();
-                self.details.cursor_delta = (0.0, 0.0);
+                self.run_frame(None, None);
             }
             _ => {}
         }
@@ -365,7 +703,25 @@ impl ApplicationHandler for V4App {
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &dyn ActiveEventLoop) {
+    fn about_to_wait(&mut self, event_loop: &dyn ActiveEventLoop) {
+        if self.default_shortcuts
+            && let Some(shortcut) = WindowShortcut::from_input(&self.input_manager)
+        {
+            match shortcut {
+                WindowShortcut::ToggleFullscreen => {
+                    self.core_communication
+                        .engine_action_sender()
+                        .try_send(Box::new(ToggleFullscreenEngineAction))
+                        .expect("Failed to send engine action.");
+                }
+                WindowShortcut::Exit => self.exit_requested = true,
+            }
+        }
+        if self.exit_requested {
+            self.core_communication.shutdown_workload_executor();
+            event_loop.exit();
+            return;
+        }
         self.input_manager.end_step();
         if let Some(window) = self.window.as_ref() {
             window.request_redraw();
@@ -383,10 +739,12 @@ impl Debug for V4App {
             .field("window_attributes", &self.window_attributes)
             .field("rendering_manager", &self.rendering_manager)
             .field("scenes", &self.scenes)
+            .field("systems", &self.systems)
             .field("active_scene", &self.active_scene)
             .field("initialized_scene", &self.initialized_scene)
             .field("window", &self.window)
             .field("details", &self.details)
+            .field("gamepads", &self.gamepads)
             .field("pipelines", &self.pipelines)
             .field("font_state", &self.font_state)
             .field("hide_cursor", &self.hide_cursor)
@@ -395,16 +753,35 @@ impl Debug for V4App {
     }
 }
 
+impl Drop for V4App {
+    fn drop(&mut self) {
+        self.core_communication.shutdown_workload_executor();
+    }
+}
+
 #[derive(Debug)]
 pub struct V4Builder {
     window_attributes: WindowAttributes,
     antialiasing_enabled: bool,
+    msaa_samples: u32,
+    gbuffer_formats: Vec<TextureFormat>,
     clear_color: wgpu::Color,
     features: wgpu::Features,
     hide_cursor: bool,
     limits: wgpu::Limits,
     backends: wgpu::Backends,
     egui_clear_color: Option<wgpu::Color>,
+    depth_format: TextureFormat,
+    screen_space_filter_mode: wgpu::FilterMode,
+    surface_view_formats: Vec<TextureFormat>,
+    design_resolution: Option<(u32, u32)>,
+    present_mode: wgpu::PresentMode,
+    depth_prepass: bool,
+    fixed_timestep: Option<Duration>,
+    shader_hot_reload: bool,
+    default_shortcuts: bool,
+    font_paths: Vec<String>,
+    double_click_interval: Duration,
 }
 
 impl Default for V4Builder {
@@ -414,12 +791,25 @@ impl Default for V4Builder {
                 winit::dpi::Size::Physical(winit::dpi::PhysicalSize::new(800, 800)),
             ),
             antialiasing_enabled: false,
+            msaa_samples: 1,
+            gbuffer_formats: Vec::new(),
             clear_color: wgpu::Color::BLACK,
             features: wgpu::Features::default(),
             hide_cursor: false,
             limits: wgpu::Limits::default(),
-            backends: wgpu::Backends::all(),
+            backends: wgpu::Backends::all().with_env(),
             egui_clear_color: None,
+            depth_format: TextureBundle::DEPTH_FORMAT,
+            screen_space_filter_mode: wgpu::FilterMode::Linear,
+            surface_view_formats: Vec::new(),
+            design_resolution: None,
+            present_mode: wgpu::PresentMode::AutoNoVsync,
+            depth_prepass: false,
+            fixed_timestep: None,
+            shader_hot_reload: false,
+            default_shortcuts: true,
+            font_paths: Vec::new(),
+            double_click_interval: EngineDetails::DEFAULT_DOUBLE_CLICK_INTERVAL,
         }
     }
 }
@@ -430,11 +820,34 @@ impl V4Builder {
         self
     }
 
+    /// Enables SMAA, which can't be combined with `msaa_samples`
     pub fn antialiasing_enabled(mut self, enabled: bool) -> Self {
         self.antialiasing_enabled = enabled;
         self
     }
 
+    /// Draws the scene's geometry with hardware MSAA at 1, 2, 4 or 8 samples per pixel, 1
+    /// disables it. MSAA and SMAA are mutually exclusive, building with both enabled panics.
+    /// Counts other than 4 need `Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES` and fall
+    /// back to 4 when the formats don't support them. Render targets and screen-space effects stay
+    /// single-sampled. The multisampled depth can't be sampled, so screen-space effects and render
+    /// graph passes reading the scene depth don't see the geometry, and the main pass clears what
+    /// passes producing the scene color or depth drew.
+    pub fn msaa_samples(mut self, samples: u32) -> Self {
+        self.msaa_samples = samples;
+        self
+    }
+
+    /// Adds up to 3 color targets to the main pass for deferred rendering, e.g. normals and
+    /// material properties. Pipelines with `PipelineId::writes_gbuffer` write them at fragment
+    /// locations 1 and up, the rest leave them untouched. They are cleared every frame and
+    /// screen-space effects read them as unfilterable `texture_2d<f32>`s at group 0, bindings 5
+    /// and up, so only float formats work. Can't be combined with `msaa_samples`.
+    pub fn gbuffer_formats(mut self, formats: Vec<TextureFormat>) -> Self {
+        self.gbuffer_formats = formats;
+        self
+    }
+
     pub fn clear_color(mut self, color: wgpu::Color) -> Self {
         self.clear_color = color;
         self
@@ -455,6 +868,10 @@ impl V4Builder {
         self
     }
 
+    /// Restricts the backends adapters are picked from, e.g. to force Vulkan for RenderDoc.
+    /// Defaults to the `WGPU_BACKEND` environment variable, or all backends if it is unset.
+    /// `Backends::NOOP` on its own, with wgpu's `noop` feature, gives a device that does nothing,
+    /// for tests driving the engine with `V4::step_frame`.
     pub fn backends(mut self, backends: wgpu::Backends) -> Self {
         self.backends = backends;
         self
@@ -465,9 +882,115 @@ impl V4Builder {
         self
     }
 
+    /// Must be a format with a depth aspect, e.g. `Depth24Plus` or `Depth24PlusStencil8`
+    pub fn depth_format(mut self, depth_format: TextureFormat) -> Self {
+        self.depth_format = depth_format;
+        self
+    }
+
+    /// The filter used when sampling the screen-space input texture
+    pub fn screen_space_filter_mode(mut self, filter_mode: wgpu::FilterMode) -> Self {
+        self.screen_space_filter_mode = filter_mode;
+        self
+    }
+
+    /// Extra formats the surface texture can be viewed as. Only the sRGB or linear twin of the
+    /// chosen surface format is accepted, e.g. `Bgra8Unorm` for a `Bgra8UnormSrgb` surface. When the
+    /// linear twin is given, the egui pass draws through it so its colors aren't encoded twice.
+    pub fn surface_view_formats(mut self, view_formats: Vec<TextureFormat>) -> Self {
+        self.surface_view_formats = view_formats;
+        self
+    }
+
+    /// Renders the scene at a fixed resolution and scales it into the window, keeping its aspect
+    /// ratio with black bars on the sides. The cursor position is reported in design pixels. egui
+    /// and text are still drawn at the window's resolution.
+    pub fn design_resolution(mut self, design_resolution: (u32, u32)) -> Self {
+        self.design_resolution = Some(design_resolution);
+        self
+    }
+
+    /// Unsupported modes fall back to `AutoVsync`. Defaults to `AutoNoVsync`.
+    pub fn present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Renders the depth of the scene's geometry in a pass of its own before the main pass, which
+    /// then only shades the nearest surface of each pixel. Within a material the pre-pass draws
+    /// components in rendering order, so giving nearer components a lower order draws it front to
+    /// back. This only helps opaque materials: pipelines that blend with what is behind them should
+    /// set `GeometryDetails::depth_test` to false, otherwise only their front-most layer is drawn.
+    pub fn depth_prepass(mut self, enabled: bool) -> Self {
+        self.depth_prepass = enabled;
+        self
+    }
+
+    /// Runs `Scene::fixed_update` `hz` times per second, independently of the framerate. Each
+    /// frame runs as many fixed steps as time has passed, up to 5.
+    pub fn fixed_timestep(mut self, hz: f32) -> Self {
+        assert!(
+            hz > 0.0,
+            "The fixed timestep rate must be positive, got {hz}."
+        );
+        self.fixed_timestep = Some(Duration::from_secs_f32(1.0 / hz));
+        self
+    }
+
+    /// Watches the files of `PipelineShader::Path` shaders and recompiles the active scene's
+    /// pipelines when they change. A shader that fails to compile is reported and the previous
+    /// pipeline is kept.
+    pub fn shader_hot_reload(mut self, enabled: bool) -> Self {
+        self.shader_hot_reload = enabled;
+        self
+    }
+
+    /// Handles Alt+Enter and F11 to toggle fullscreen and Ctrl+Q to exit. Enabled by default,
+    /// disable it for games that handle these keys themselves.
+    pub fn default_shortcuts(mut self, enabled: bool) -> Self {
+        self.default_shortcuts = enabled;
+        self
+    }
+
+    /// Font files loaded alongside the system fonts when the window is created, so text can use
+    /// fonts shipped with the game. See `FontState::load_font_from_path` for the family names.
+    pub fn font_paths(mut self, paths: Vec<String>) -> Self {
+        self.font_paths = paths;
+        self
+    }
+
+    /// See `EngineDetails::mouse_double_clicked`, 500 milliseconds by default
+    pub fn double_click_interval(mut self, interval: Duration) -> Self {
+        self.double_click_interval = interval;
+        self
+    }
+
     pub async fn build(self) -> V4 {
         let event_loop = EventLoop::new().expect("Failed to create event loop.");
         event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
+        let app = self.build_app().await;
+
+        V4 {
+            event_loop: Some(event_loop),
+            app,
+        }
+    }
+
+    /// Builds the engine without an event loop or a window, so it can only be driven with
+    /// `V4::step_frame`. An event loop can only be created once per process, so this is the way
+    /// to build engines in tests, together with `backends(wgpu::Backends::NOOP)` where there is
+    /// no GPU.
+    pub async fn build_headless(self) -> V4 {
+        V4 {
+            event_loop: None,
+            app: self.build_app().await,
+        }
+    }
+
+    async fn build_app(self) -> V4App {
+        // Warnings go through `log`, shown unless `RUST_LOG` says otherwise
+        let _ = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"))
+            .try_init();
         let input_manager = WinitInputHelper::new();
         let window_attributes = self.window_attributes;
 
@@ -476,32 +999,240 @@ impl V4Builder {
             window_size,
             RenderingManagerDetails {
                 antialiasing_enabled: self.antialiasing_enabled,
+                msaa_samples: self.msaa_samples,
+                gbuffer_formats: self.gbuffer_formats,
                 clear_color: self.clear_color,
                 features: self.features,
                 limits: self.limits,
                 backends: self.backends,
+                depth_format: self.depth_format,
+                screen_space_filter_mode: self.screen_space_filter_mode,
+                surface_view_formats: self.surface_view_formats,
+                design_resolution: self.design_resolution,
+                present_mode: self.present_mode,
+                depth_prepass: self.depth_prepass,
+                shader_hot_reload: self.shader_hot_reload,
             },
         )
         .await;
 
-        let app = V4App {
+        let gilrs = match gilrs::Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(err) => {
+                log::warn!("Gamepads are unavailable: {err}");
+                None
+            }
+        };
+        let gamepads = gilrs.as_ref().map(GamepadState::new).unwrap_or_default();
+
+        V4App {
             window_attributes,
             input_manager,
+            gilrs,
+            gamepads,
             rendering_manager,
             scenes: Vec::new(),
+            systems: Vec::new(),
             last_active_scene_index: usize::MAX,
             active_scene: 0,
             initialized_scene: false,
             window: None,
-            details: Default::default(),
-            pipelines: HashMap::new(),
+            details: EngineDetails {
+                double_click_interval: self.double_click_interval,
+                ..Default::default()
+            },
+            // The main pass target is only known once the surface exists
+            pipelines: PipelineCache::new(RenderTarget::pipeline_target()),
+            render_target_pipelines: PipelineCache::new(RenderTarget::pipeline_target()),
             font_state: None,
+            font_paths: self.font_paths,
             hide_cursor: self.hide_cursor,
             core_communication: CoreCommunication::default(),
             egui_platform: None,
             egui_clear_color: self.egui_clear_color,
-        };
+            default_shortcuts: self.default_shortcuts,
+            exit_requested: false,
+            fixed_timestep: self.fixed_timestep,
+            fixed_time_accumulator: Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::engine_support::input_state::MockInput;
+
+    #[derive(Debug)]
+    struct CountUpdates(Arc<AtomicUsize>);
+
+    impl System for CountUpdates {
+        fn run(&mut self, _scene: &mut Scene, _engine_details: &EngineDetails) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    async fn headless_engine() -> V4 {
+        let mut engine = V4::builder()
+            .backends(wgpu::Backends::NOOP)
+            .build_headless()
+            .await;
+        engine.attach_scene(Scene::default());
+        engine
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn every_step_updates_the_scene() {
+        let mut engine = headless_engine().await;
+        let updates = Arc::new(AtomicUsize::new(0));
+        engine.add_system(Box::new(CountUpdates(updates.clone())));
+
+        let input = MockInput::default();
+        for _ in 0..5 {
+            engine.step_frame(Duration::from_millis(16), &input);
+        }
+
+        assert_eq!(updates.load(Ordering::Relaxed), 5);
+        assert_eq!(engine.details().frames_elapsed, 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn steps_use_the_simulated_delta_time() {
+        let mut engine = headless_engine().await;
+        let input = MockInput::default();
+
+        engine.step_frame(Duration::from_millis(25), &input);
+        assert_eq!(engine.details().delta_time, Duration::from_millis(25));
+
+        engine.step_frame(Duration::from_secs(2), &input);
+        assert_eq!(engine.details().delta_time, Duration::from_secs(2));
+        assert_eq!(engine.details().delta_seconds(), 2.0);
+        assert_eq!(engine.details().frames_elapsed, 2);
+    }
+
+    fn press(details: &mut EngineDetails, button: MouseButton, time: Instant) {
+        details.handle_mouse_button(button, ElementState::Pressed, time);
+    }
+
+    fn release(details: &mut EngineDetails, button: MouseButton, time: Instant) {
+        details.handle_mouse_button(button, ElementState::Released, time);
+    }
+
+    #[test]
+    fn text_typed_in_one_frame_is_all_delivered() {
+        let mut details = EngineDetails::default();
+
+        details.handle_typed_text(ElementState::Pressed, "a");
+        details.handle_typed_text(ElementState::Released, "a");
+        details.handle_typed_text(ElementState::Pressed, "b");
+        assert_eq!(details.typed_this_frame(), "ab");
+
+        details.end_frame_input();
+        assert_eq!(details.typed_this_frame(), "");
+        details.handle_typed_text(ElementState::Pressed, "é");
+        assert_eq!(details.typed_this_frame(), "é");
+    }
+
+    #[test]
+    fn presses_and_releases_last_a_frame() {
+        let mut details = EngineDetails::default();
+        let start = Instant::now();
+
+        press(&mut details, MouseButton::Left, start);
+        assert!(details.mouse_just_pressed(MouseButton::Left));
+        assert!(details.mouse_state.contains(&MouseButton::Left));
+        assert!(!details.mouse_just_pressed(MouseButton::Right));
+
+        details.end_frame_input();
+        assert!(!details.mouse_just_pressed(MouseButton::Left));
+        assert!(details.mouse_state.contains(&MouseButton::Left));
+
+        release(&mut details, MouseButton::Left, start);
+        assert!(details.mouse_just_released(MouseButton::Left));
+        assert!(details.mouse_state.is_empty());
+
+        details.end_frame_input();
+        assert!(!details.mouse_just_released(MouseButton::Left));
+    }
+
+    #[test]
+    fn repeated_presses_and_stray_releases_are_ignored() {
+        let mut details = EngineDetails::default();
+        let start = Instant::now();
+
+        release(&mut details, MouseButton::Left, start);
+        assert!(!details.mouse_just_released(MouseButton::Left));
+
+        // The release got lost, so the next press repeats the held button
+        let later = start + Duration::from_millis(10);
+        press(&mut details, MouseButton::Left, start);
+        details.end_frame_input();
+        press(&mut details, MouseButton::Left, later);
+        assert!(!details.mouse_just_pressed(MouseButton::Left));
+        assert!(!details.mouse_double_clicked(MouseButton::Left));
+    }
+
+    #[test]
+    fn losing_focus_releases_held_buttons() {
+        let mut details = EngineDetails::default();
+        let start = Instant::now();
+
+        press(&mut details, MouseButton::Left, start);
+        press(&mut details, MouseButton::Right, start);
+        details.end_frame_input();
+        details.release_mouse_buttons();
+
+        assert!(details.mouse_state.is_empty());
+        assert!(details.mouse_just_released(MouseButton::Left));
+        assert!(details.mouse_just_released(MouseButton::Right));
+        assert!(!details.mouse_just_released(MouseButton::Middle));
+
+        // The real releases arriving afterwards change nothing
+        details.end_frame_input();
+        release(&mut details, MouseButton::Left, start);
+        assert!(!details.mouse_just_released(MouseButton::Left));
+
+        let refocused = start + Duration::from_secs(1);
+        press(&mut details, MouseButton::Left, refocused);
+        assert!(details.mouse_just_pressed(MouseButton::Left));
+    }
+
+    #[test]
+    fn double_clicks_need_two_presses_within_the_interval() {
+        let mut details = EngineDetails::default();
+        let start = Instant::now();
+        let interval = details.double_click_interval;
+
+        press(&mut details, MouseButton::Left, start);
+        release(&mut details, MouseButton::Left, start);
+        assert!(!details.mouse_double_clicked(MouseButton::Left));
+        details.end_frame_input();
+
+        press(&mut details, MouseButton::Left, start + interval);
+        assert!(details.mouse_double_clicked(MouseButton::Left));
+        release(&mut details, MouseButton::Left, start + interval);
+        details.end_frame_input();
+
+        // The third quick press starts a new double click rather than finishing one
+        let third = start + interval + Duration::from_millis(1);
+        press(&mut details, MouseButton::Left, third);
+        assert!(!details.mouse_double_clicked(MouseButton::Left));
+        release(&mut details, MouseButton::Left, third);
+        details.end_frame_input();
+
+        // Too slow, so this press starts over as well
+        let fourth = third + interval + Duration::from_millis(1);
+        press(&mut details, MouseButton::Left, fourth);
+        assert!(!details.mouse_double_clicked(MouseButton::Left));
+        release(&mut details, MouseButton::Left, fourth);
+        details.end_frame_input();
 
-        V4 { event_loop, app }
+        press(&mut details, MouseButton::Right, fourth);
+        assert!(!details.mouse_double_clicked(MouseButton::Right));
     }
 }
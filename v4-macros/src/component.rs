@@ -1,28 +1,24 @@
+use std::collections::HashMap;
+
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
-use quote::{format_ident, quote};
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{ToTokens, format_ident, quote};
 use syn::{
-    Expr, Field, GenericParam, Generics, Ident, ItemStruct, Meta, MetaNameValue, Path, Token, Type,
-    TypeParam, parse::Parser, parse_macro_input, punctuated::Punctuated,
+    Expr, Field, GenericParam, Generics, Ident, ItemStruct, LifetimeParam, Meta, MetaNameValue,
+    Path, Token, Type, TypeParam, parse::Parser, parse_macro_input, punctuated::Punctuated,
 };
 
 pub fn component_impl(args: TokenStream, item: TokenStream) -> TokenStream {
-    let property_string = get_property_string(
+    let properties = get_properties(
         parse_macro_input!(args with Punctuated::<Meta, Token![,]>::parse_terminated),
     );
-    let rendering_order = if let Some((string, val)) = property_string.as_ref()
-        && string == "rendering_order"
-        && let Some(val) = val
-    {
+    let rendering_order = if let Some(Some(val)) = properties.get("rendering_order") {
         quote! {#val}
     } else {
         quote! {0}
     };
 
-    // panic!("{property_string:?}");
-    let debug_impl = if let Some((string, _)) = property_string.as_ref()
-        && string == "custom_debug"
-    {
+    let debug_impl = if properties.contains_key("custom_debug") {
         quote! {}
     } else {
         quote! {#[derive(Debug)]}
@@ -35,13 +31,21 @@ pub fn component_impl(args: TokenStream, item: TokenStream) -> TokenStream {
 
     let builder_ident = format_ident!("{}Builder", ident.to_string());
 
-    let builder = builder_construction(builder_ident, &component_struct);
+    let builder = builder_construction(builder_ident, &component_struct, &rendering_order);
+
+    let clone_impl = if properties.contains_key("clone") {
+        clone_impl_constructor(&ident, &generics, &component_struct.fields)
+    } else {
+        quote! {}
+    };
 
     component_struct
         .fields
         .iter_mut()
         .for_each(|field| field.attrs = Vec::new());
 
+    let component_fields: Vec<Field> = component_struct.fields.iter().cloned().collect();
+
     let added_component_fields = [
         Field::parse_named
             .parse2(quote! {
@@ -63,6 +67,11 @@ pub fn component_impl(args: TokenStream, item: TokenStream) -> TokenStream {
                 is_enabled: bool
             })
             .unwrap(),
+        Field::parse_named
+            .parse2(quote! {
+                rendering_order: i32
+            })
+            .unwrap(),
     ];
 
     if let syn::Fields::Named(fields) = &mut component_struct.fields {
@@ -74,6 +83,13 @@ pub fn component_impl(args: TokenStream, item: TokenStream) -> TokenStream {
     }
 
     let component_generics = remove_generics_bounds(&generics);
+    let where_clause = &generics.where_clause;
+
+    let accessors_impl = if properties.contains_key("accessors") {
+        accessors_impl_constructor(&ident, &generics, &component_fields)
+    } else {
+        quote! {}
+    };
 
     quote! {
         #debug_impl
@@ -81,7 +97,11 @@ pub fn component_impl(args: TokenStream, item: TokenStream) -> TokenStream {
 
         #builder
 
-        impl #generics v4::ecs::component::ComponentDetails for #ident #component_generics {
+        #clone_impl
+
+        #accessors_impl
+
+        impl #generics v4::ecs::component::ComponentDetails for #ident #component_generics #where_clause {
             fn id(&self) -> v4::ecs::component::ComponentId {
                 self.id
             }
@@ -94,6 +114,10 @@ pub fn component_impl(args: TokenStream, item: TokenStream) -> TokenStream {
                 self.is_initialized = true;
             }
 
+            fn set_uninitialized(&mut self) {
+                self.is_initialized = false;
+            }
+
             fn parent_entity_id(&self) -> v4::ecs::entity::EntityId {
                 self.parent_entity_id
             }
@@ -111,14 +135,18 @@ pub fn component_impl(args: TokenStream, item: TokenStream) -> TokenStream {
             }
 
             fn rendering_order(&self) -> i32 {
-                #rendering_order
+                self.rendering_order
+            }
+
+            fn set_rendering_order(&mut self, rendering_order: i32) {
+                self.rendering_order = rendering_order;
             }
         }
     }
     .into()
 }
 
-fn get_property_string(args: Punctuated<Meta, Token![,]>) -> Option<(String, Option<Expr>)> {
+fn get_properties(args: Punctuated<Meta, Token![,]>) -> HashMap<String, Option<Expr>> {
     args.into_iter()
         .flat_map(|arg| match arg {
             Meta::NameValue(MetaNameValue { path, value, .. }) => {
@@ -131,7 +159,117 @@ fn get_property_string(args: Punctuated<Meta, Token![,]>) -> Option<(String, Opt
             Meta::Path(Path { segments, .. }) => Some((segments[0].ident.to_string(), None)),
             _ => None,
         })
-        .next()
+        .collect()
+}
+
+/// Clones every field, but gives the clone a fresh id so it can coexist with the original in a
+/// scene. Fields marked `#[no_clone]` are reset to their `Default` instead.
+fn clone_impl_constructor(
+    component_ident: &Ident,
+    component_generics: &Generics,
+    component_fields: &syn::Fields,
+) -> TokenStream2 {
+    let component_generics_no_bounds = remove_generics_bounds(component_generics);
+    let generic_idents: Vec<&Ident> = component_generics
+        .type_params()
+        .map(|param| &param.ident)
+        .chain(component_generics.const_params().map(|param| &param.ident))
+        .collect();
+
+    let (field_values, field_bounds): (Vec<TokenStream2>, Vec<Option<TokenStream2>>) =
+        component_fields
+            .iter()
+            .map(|field| {
+                let field_ident = field.ident.as_ref().unwrap();
+                let field_type = &field.ty;
+                let is_cloned = !field
+                    .attrs
+                    .iter()
+                    .any(|attr| attr.path().is_ident("no_clone"));
+                let (value, bound) = if is_cloned {
+                    (
+                        quote! {#field_ident: Clone::clone(&self.#field_ident)},
+                        quote! {#field_type: Clone},
+                    )
+                } else {
+                    (
+                        quote! {#field_ident: Default::default()},
+                        quote! {#field_type: Default},
+                    )
+                };
+                // Bounds on concrete types can't be satisfied when they are false, so only fields
+                // using the component's generics get one
+                (
+                    value,
+                    type_uses_generics(field_type, &generic_idents).then_some(bound),
+                )
+            })
+            .unzip();
+    let field_bounds = field_bounds.into_iter().flatten();
+    let component_predicates = component_generics
+        .where_clause
+        .iter()
+        .flat_map(|where_clause| where_clause.predicates.iter());
+
+    quote! {
+        impl #component_generics Clone for #component_ident #component_generics_no_bounds
+        where #(#field_bounds,)* #(#component_predicates,)* {
+            fn clone(&self) -> Self {
+                Self {
+                    #(#field_values,)*
+                    id: v4::ecs::component::next_component_id(),
+                    parent_entity_id: 0,
+                    is_initialized: false,
+                    is_enabled: self.is_enabled,
+                    rendering_order: self.rendering_order,
+                }
+            }
+        }
+    }
+}
+
+fn type_uses_generics(ty: &Type, generic_idents: &[&Ident]) -> bool {
+    fn tokens_use_generics(tokens: TokenStream2, generic_idents: &[&Ident]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(ident) => generic_idents.contains(&&ident),
+            TokenTree::Group(group) => tokens_use_generics(group.stream(), generic_idents),
+            _ => false,
+        })
+    }
+
+    tokens_use_generics(ty.to_token_stream(), generic_idents)
+}
+
+/// Getters and setters for the user-declared fields, not the injected ones
+fn accessors_impl_constructor(
+    component_ident: &Ident,
+    component_generics: &Generics,
+    component_fields: &[Field],
+) -> TokenStream2 {
+    let component_generics_no_bounds = remove_generics_bounds(component_generics);
+    let where_clause = &component_generics.where_clause;
+
+    let accessors = component_fields.iter().map(|field| {
+        let field_ident = field.ident.as_ref().unwrap();
+        let field_type = &field.ty;
+        let setter_ident = format_ident!("set_{field_ident}");
+
+        quote! {
+            pub fn #field_ident(&self) -> &#field_type {
+                &self.#field_ident
+            }
+
+            pub fn #setter_ident(&mut self, #field_ident: #field_type) {
+                self.#field_ident = #field_ident;
+            }
+        }
+    });
+
+    quote! {
+        impl #component_generics #component_ident #component_generics_no_bounds #where_clause {
+            #(#accessors)*
+        }
+    }
 }
 
 fn builder_struct_construction(
@@ -148,7 +286,7 @@ fn builder_struct_construction(
         .fields
         .iter()
         .map(|field| {
-            if field.attrs.is_empty() {
+            if is_required_field(field) {
                 let field_ty = &field.ty;
                 Field {
                     ty: Type::Verbatim(quote! {Option<#field_ty>}),
@@ -174,6 +312,11 @@ fn builder_struct_construction(
                 is_enabled: bool
             })
             .unwrap(),
+        Field::parse_named
+            .parse2(quote! {
+                rendering_order: i32
+            })
+            .unwrap(),
         Field::parse_named
             .parse2(quote! {
                 _marker: std::marker::PhantomData<(#(#builder_required_field_generics),*)>
@@ -244,7 +387,7 @@ fn separate_required_and_optional_fields<'a>(
     let basic_separation: (Vec<Option<&Field>>, Vec<Option<&Field>>) = all_fields
         .iter()
         .map(|field| {
-            if field.attrs.is_empty() {
+            if is_required_field(field) {
                 (Some(*field), None)
             } else {
                 (None, Some(*field))
@@ -262,6 +405,104 @@ fn separate_required_and_optional_fields<'a>(
     )
 }
 
+enum FieldDefault {
+    Required,
+    Default,
+    Expr(Expr),
+}
+
+/// Types that get an implicit `Default::default()` when no attribute is given
+const AUTO_DEFAULT_TYPES: &[&str] = &[
+    "Option", "Vec", "VecDeque", "HashMap", "HashSet", "BTreeMap", "BTreeSet",
+];
+
+fn field_default(field: &Field) -> FieldDefault {
+    for attr in &field.attrs {
+        if attr.path().is_ident("default") {
+            return if let Ok(expr) = attr.parse_args::<Expr>() {
+                FieldDefault::Expr(expr)
+            } else {
+                FieldDefault::Default
+            };
+        } else if attr.path().is_ident("required") {
+            return FieldDefault::Required;
+        } else if attr.path().is_ident("setter") || attr.path().is_ident("no_clone") {
+            continue;
+        } else {
+            panic!(
+                "Invalid field attribute '{}'",
+                attr.path().get_ident().unwrap()
+            )
+        }
+    }
+
+    if let Type::Path(type_path) = &field.ty
+        && let Some(last_segment) = type_path.path.segments.last()
+        && AUTO_DEFAULT_TYPES.contains(&last_segment.ident.to_string().as_str())
+    {
+        FieldDefault::Default
+    } else {
+        FieldDefault::Required
+    }
+}
+
+fn is_required_field(field: &Field) -> bool {
+    matches!(field_default(field), FieldDefault::Required)
+}
+
+#[derive(Default)]
+struct SetterOptions {
+    name: Option<Ident>,
+    validate: Option<Path>,
+}
+
+/// Parses `#[setter(name = "with_foo", validate = path::to::fn)]`
+fn setter_options(field: &Field) -> SetterOptions {
+    let Some(attr) = field
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("setter"))
+    else {
+        return SetterOptions::default();
+    };
+
+    let args = attr
+        .parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
+        .expect("Setter attributes must be of the form `name = value`");
+
+    let mut options = SetterOptions::default();
+    for MetaNameValue { path, value, .. } in args {
+        if path.is_ident("name") {
+            let Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(name),
+                ..
+            }) = value
+            else {
+                panic!("The setter name must be a string literal");
+            };
+            options.name = Some(format_ident!("{}", name.value()));
+        } else if path.is_ident("validate") {
+            let Expr::Path(validate) = value else {
+                panic!("The setter validation must be a path to a function");
+            };
+            options.validate = Some(validate.path);
+        } else {
+            panic!(
+                "Invalid setter option '{}'",
+                path.get_ident().map(|ident| ident.to_string()).unwrap_or_default()
+            );
+        }
+    }
+
+    options
+}
+
+fn setter_ident(field: &Field) -> Ident {
+    setter_options(field)
+        .name
+        .unwrap_or_else(|| field.ident.clone().unwrap())
+}
+
 fn to_pascal_case(str: &str) -> String {
     let chars: Vec<char> = str.chars().collect();
     chars
@@ -304,12 +545,23 @@ fn remove_generics_bounds(generics: &Generics) -> Generics {
                 eq_token: None,
                 default: None,
             })
+        } else if let GenericParam::Lifetime(lifetime_param) = generic {
+            GenericParam::Lifetime(LifetimeParam::new(lifetime_param.lifetime.clone()))
         } else {
             generic.clone()
         }
     }))
 }
 
+/// Lifetimes have to come before the builder's type parameters
+fn split_lifetime_params(generics: &Generics) -> (Vec<GenericParam>, Vec<GenericParam>) {
+    generics
+        .params
+        .iter()
+        .cloned()
+        .partition(|generic| matches!(generic, GenericParam::Lifetime(_)))
+}
+
 /// Required, Required + Component, Unset + Component
 fn generics_constructor(
     required_fields_idents: &[Ident],
@@ -329,19 +581,34 @@ fn generics_constructor(
         .collect();
 
     let component_generics = &component_struct.generics;
-
-    let required_and_component_generics = generics_helper(
-        required_fields_generics
-            .iter()
-            .map(|generic_type| GenericParam::Type(generic_type.clone()))
-            .chain(component_generics.params.iter().cloned()),
-    );
+    let (component_lifetimes, component_other_generics) =
+        split_lifetime_params(component_generics);
+
+    let required_and_component_generics = Generics {
+        where_clause: component_generics.where_clause.clone(),
+        ..generics_helper(
+            component_lifetimes
+                .iter()
+                .cloned()
+                .chain(
+                    required_fields_generics
+                        .iter()
+                        .map(|generic_type| GenericParam::Type(generic_type.clone())),
+                )
+                .chain(component_other_generics.iter().cloned()),
+        )
+    };
 
     let unset_and_component_generics = generics_helper(
-        required_fields_generics
+        component_lifetimes
             .iter()
-            .map(|_| GenericParam::Type(unset_type.clone()))
-            .chain(component_generics.params.iter().cloned()),
+            .cloned()
+            .chain(
+                required_fields_generics
+                    .iter()
+                    .map(|_| GenericParam::Type(unset_type.clone())),
+            )
+            .chain(component_other_generics.iter().cloned()),
     );
 
     (
@@ -361,7 +628,11 @@ fn builder_required_methods_constructor(
     set_type: &TypeParam,
     builder_ident: Ident,
 ) -> Vec<TokenStream2> {
-    required_fields.iter().enumerate().map(|(i, field)| {
+    let lifetime_count = full_builder_generics.lifetimes().count();
+    let where_clause = &full_builder_generics.where_clause;
+
+    required_fields.iter().enumerate().map(|(field_index, field)| {
+        let i = field_index + lifetime_count;
         let field_ident = field.ident.as_ref().unwrap();
         let field_type = &field.ty;
         let current_excluded_generics = generics_helper(full_builder_generics.params.iter().enumerate().flat_map(|(index, generic)| {
@@ -391,18 +662,21 @@ fn builder_required_methods_constructor(
 
     let required_fields_idents: Vec<&Ident> = required_fields
         .iter().enumerate()
-        .flat_map(|(index, field)| if index == i {None} else { Some(field.ident.as_ref().unwrap())})
+        .flat_map(|(index, field)| if index == field_index {None} else { Some(field.ident.as_ref().unwrap())})
         .collect();
 
+        let setter_ident = setter_ident(field);
+
         quote! {
-            impl #current_excluded_generics #builder_ident #current_unset_generics {
-                pub fn #field_ident(self, #field_ident: #field_type) -> #builder_ident #current_set_generics {
+            impl #current_excluded_generics #builder_ident #current_unset_generics #where_clause {
+                pub fn #setter_ident(self, #field_ident: #field_type) -> #builder_ident #current_set_generics {
                     #builder_ident {
                         #field_ident: Some(#field_ident),
                         #(#required_fields_idents: self.#required_fields_idents,)*
                         #(#optional_fields_idents: self.#optional_fields_idents,)*
                         id: self.id,
                         is_enabled: self.is_enabled,
+                        rendering_order: self.rendering_order,
                         _marker: std::marker::PhantomData,
                     }
                 }
@@ -417,9 +691,10 @@ fn builder_optional_methods_constructor(optional_fields: &[&Field]) -> Vec<Token
         .map(|field| {
             let field_ident = field.ident.as_ref().unwrap();
             let field_type = &field.ty;
+            let setter_ident = setter_ident(field);
 
             quote! {
-                pub fn #field_ident(self, #field_ident: #field_type) -> Self {
+                pub fn #setter_ident(self, #field_ident: #field_type) -> Self {
                 Self { #field_ident, ..self }
                 }
             }
@@ -457,11 +732,12 @@ fn builder_methods_constructor(
     let optional_fields_methods = builder_optional_methods_constructor(optional_fields);
 
     let full_builder_generics_no_bounds = remove_generics_bounds(full_builder_generics);
+    let where_clause = &full_builder_generics.where_clause;
 
     quote! {
         #(#required_fields_methods)*
 
-        impl #full_builder_generics #builder_ident #full_builder_generics_no_bounds {
+        impl #full_builder_generics #builder_ident #full_builder_generics_no_bounds #where_clause {
             #(#optional_fields_methods)*
 
             pub fn is_enabled(self, is_enabled: bool) -> Self {
@@ -471,6 +747,10 @@ fn builder_methods_constructor(
             pub fn id(self, id: v4::ecs::component::ComponentId) -> Self {
                 Self {id, ..self}
             }
+
+            pub fn rendering_order(self, rendering_order: i32) -> Self {
+                Self {rendering_order, ..self}
+            }
         }
     }
 }
@@ -493,30 +773,51 @@ fn build_method_constructor(
     let required_fields_idents = fields_to_idents(required_fields);
     let optional_fields_idents = fields_to_idents(optional_fields);
 
-    quote! {
-        impl #full_builder_generics #builder_ident #full_builder_generics_no_bounds
-        where #(#builder_required_fields_generics_arr: #required_fields_trait_idents),* {
-            pub fn build(self) -> #component_ident #component_generics_no_bounds {
-                use std::hash::{DefaultHasher, Hash, Hasher};
-
-                let mut hasher = DefaultHasher::new();
+    let validations: Vec<TokenStream2> = required_fields
+        .iter()
+        .chain(optional_fields.iter())
+        .flat_map(|field| {
+            let validate = setter_options(field).validate?;
+            let field_ident = field.ident.as_ref().unwrap();
+            let field_name = field_ident.to_string();
+            Some(quote! {
+                if let Err(error) = #validate(&component.#field_ident) {
+                    return Err(format!("Invalid value for field `{}`: {error}", #field_name));
+                }
+            })
+        })
+        .collect();
 
-                std::time::Instant::now().hash(&mut hasher);
+    let where_clause = &full_builder_generics.where_clause;
 
-                let id = hasher.finish();
+    quote! {
+        impl #full_builder_generics #builder_ident #full_builder_generics_no_bounds #where_clause {
+            /// Panics if a field fails its setter validation
+            pub fn build(self) -> #component_ident #component_generics_no_bounds
+            where #(#builder_required_fields_generics_arr: #required_fields_trait_idents),* {
+                self.try_build().unwrap_or_else(|error| panic!("{error}"))
+            }
 
-                #component_ident {
+            // The bounds are on the method so missing fields report the `HasX` diagnostics
+            pub fn try_build(self) -> Result<#component_ident #component_generics_no_bounds, String>
+            where #(#builder_required_fields_generics_arr: #required_fields_trait_idents),* {
+                let component = #component_ident {
                     #(#required_fields_idents: self.#required_fields_idents.unwrap(),)*
                     #(#optional_fields_idents: self.#optional_fields_idents,)*
                     id: if self.id == 0 {
-                            id
+                            v4::ecs::component::next_component_id()
                         } else {
                             self.id
                         },
                     parent_entity_id: 0,
                     is_initialized: false,
                     is_enabled: self.is_enabled,
-                }
+                    rendering_order: self.rendering_order,
+                };
+
+                #(#validations)*
+
+                Ok(component)
             }
         }
     }
@@ -527,21 +828,10 @@ fn get_all_defaults(all_fields: &[&Field]) -> TokenStream2 {
         .iter()
         .map(|field| {
             let field_ident = field.ident.as_ref().unwrap();
-            if let Some(attr) = field.attrs.first() {
-                if attr.path().is_ident("default") {
-                    if let Ok(expr) = attr.parse_args::<Expr>() {
-                        return quote! {#field_ident: #expr};
-                    } else {
-                        return quote! {#field_ident: Default::default()};
-                    };
-                } else {
-                    panic!(
-                        "Invalid field attribute '{}'",
-                        attr.path().get_ident().unwrap()
-                    )
-                }
-            } else {
-                return quote! {#field_ident: None};
+            match field_default(field) {
+                FieldDefault::Required => quote! {#field_ident: None},
+                FieldDefault::Default => quote! {#field_ident: Default::default()},
+                FieldDefault::Expr(expr) => quote! {#field_ident: #expr},
             }
         })
         .collect();
@@ -549,7 +839,11 @@ fn get_all_defaults(all_fields: &[&Field]) -> TokenStream2 {
     quote! {#(#field_defaults,)*}
 }
 
-fn builder_construction(builder_ident: Ident, component_struct: &ItemStruct) -> TokenStream2 {
+fn builder_construction(
+    builder_ident: Ident,
+    component_struct: &ItemStruct,
+    rendering_order: &TokenStream2,
+) -> TokenStream2 {
     let all_fields: Vec<&Field> = component_struct.fields.iter().collect();
     let (required_fields, optional_fields) = separate_required_and_optional_fields(&all_fields);
 
@@ -594,6 +888,7 @@ fn builder_construction(builder_ident: Ident, component_struct: &ItemStruct) ->
     let component_ident = &component_struct.ident;
     let component_generics = &component_struct.generics;
     let component_generics_no_bounds = remove_generics_bounds(&component_generics);
+    let component_where_clause = &component_generics.where_clause;
 
     let build_method = build_method_constructor(
         &full_builder_generics,
@@ -605,6 +900,22 @@ fn builder_construction(builder_ident: Ident, component_struct: &ItemStruct) ->
         &optional_fields,
     );
 
+    let required_fields_diagnostics: Vec<TokenStream2> = required_fields
+        .iter()
+        .map(|field| {
+            let field_name = field.ident.as_ref().unwrap().to_string();
+            let setter_name = setter_ident(field).to_string();
+            // Worded for both `build` and `try_build`, which share the bound
+            let message =
+                format!("`{builder_ident}` can't build before the field `{field_name}` is set");
+            let label = format!("the required field `{field_name}` has not been set");
+            let note = format!("call `.{setter_name}(...)` on the builder first");
+            quote! {
+                #[diagnostic::on_unimplemented(message = #message, label = #label, note = #note)]
+            }
+        })
+        .collect();
+
     let field_defaults = get_all_defaults(&all_fields);
 
     let builder_struct = builder_struct_construction(
@@ -620,7 +931,10 @@ fn builder_construction(builder_ident: Ident, component_struct: &ItemStruct) ->
         pub struct #set_type_ident;
         pub struct #unset_type_ident;
 
-        #(pub trait #required_fields_trait_idents {})*
+        #(
+            #required_fields_diagnostics
+            pub trait #required_fields_trait_idents {}
+        )*
 
         #(impl #required_fields_trait_idents for #set_type_ident {})*
 
@@ -628,18 +942,23 @@ fn builder_construction(builder_ident: Ident, component_struct: &ItemStruct) ->
 
         #build_method
 
-        impl #component_generics #component_ident #component_generics_no_bounds {
+        impl #component_generics #component_ident #component_generics_no_bounds #component_where_clause {
+            /// The rendering order given to `#[component(rendering_order = ...)]`, which built
+            /// components start with
+            pub const DEFAULT_RENDERING_ORDER: i32 = #rendering_order;
+
             pub fn builder() -> #builder_ident #unset_builder_generics_no_bounds {
                 #builder_ident::default()
             }
         }
 
-        impl #component_generics Default for #builder_ident #unset_builder_generics_no_bounds{
+        impl #component_generics Default for #builder_ident #unset_builder_generics_no_bounds #component_where_clause {
             fn default() -> Self {
                 Self {
                     #field_defaults
                     is_enabled: true,
                     id: 0,
+                    rendering_order: <#component_ident #component_generics_no_bounds>::DEFAULT_RENDERING_ORDER,
                     _marker: std::marker::PhantomData,
                 }
             }
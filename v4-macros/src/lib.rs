@@ -7,6 +7,19 @@ use syn::parse_macro_input;
 mod component;
 mod scene;
 
+/// Fields without attributes are required by the generated builder, except for `Option` and
+/// common collection types which default to empty. `#[default]` or `#[default(expr)]` makes a
+/// field optional and `#[required]` forces it to be set.
+///
+/// `#[setter(name = "with_foo", validate = path::to::fn)]` renames a field's builder method and
+/// checks the value with a `fn(&T) -> Result<(), impl Display>` in `try_build`.
+///
+/// `#[component(clone)]` implements `Clone`. Clones get a fresh id and are detached from their
+/// entity, so they must be initialized again. Fields that can't or shouldn't be cloned, like GPU
+/// buffers, opt out with `#[no_clone]` and start from their `Default` in the clone.
+///
+/// `#[component(accessors)]` generates `field(&self)` getters and `set_field` setters for every
+/// declared field.
 #[proc_macro_attribute]
 pub fn component(args: TokenStream, item: TokenStream) -> TokenStream {
     component::component_impl(args, item)
@@ -217,6 +217,8 @@ impl quote::ToTokens for SceneDescriptor {
                         #pipeline_id,
                         vec![#(#attachments),*],
                         Vec::new(),
+                        Vec::new(),
+                        true,
                     );
                 }
             })
@@ -246,14 +248,28 @@ impl quote::ToTokens for SceneDescriptor {
                 quote! {true}
             };
 
-            quote! {
+            let create_material = quote! {
                 #scene_name.create_material(
                     #pipeline_id,
                     vec![#(#attachments),*],
                     vec![#(#entities_attached),*],
                     #immediate_data,
                     #is_enabled,
-                );
+                )
+            };
+
+            if let Some(sampler_lod) = mat.sampler_lod.as_ref() {
+                quote! {
+                    let material_id = #create_material;
+                    #scene_name
+                        .get_material_mut(material_id)
+                        .unwrap()
+                        .set_sampler_lod(#sampler_lod);
+                }
+            } else {
+                quote! {
+                    #create_material;
+                }
             }
         });
 
@@ -842,6 +858,7 @@ struct MaterialDescriptor {
     pipeline_id: PipelineIdVariants,
     attachments: Vec<ShaderAttachmentDescriptor>,
     immediate_data: Option<Expr>,
+    sampler_lod: Option<Expr>,
     is_enabled: Option<LitBool>,
     ident: Option<Lit>,
 }
@@ -856,6 +873,7 @@ impl MaterialDescriptor {
         let mut attachments: Vec<ShaderAttachmentDescriptor> = Vec::new();
         let mut is_enabled: Option<LitBool> = None;
         let mut immediate_data: Option<Expr> = None;
+        let mut sampler_lod: Option<Expr> = None;
         let mut ident: Option<Lit> = None;
 
         for param in params {
@@ -873,6 +891,7 @@ impl MaterialDescriptor {
                 }
                 MaterialParameters::IsEnabled(enabled_lit) => is_enabled = Some(enabled_lit),
                 MaterialParameters::ImmediateData(data) => immediate_data = Some(data),
+                MaterialParameters::SamplerLod(lod) => sampler_lod = Some(lod),
                 MaterialParameters::Ident(lit) => ident = Some(lit),
             }
         }
@@ -885,6 +904,7 @@ impl MaterialDescriptor {
             pipeline_id: PipelineIdVariants::ScreenSpace(pipeline_id),
             attachments,
             immediate_data,
+            sampler_lod,
             is_enabled,
             ident,
         })
@@ -911,6 +931,7 @@ impl MaterialDescriptor {
             attachments: self.attachments,
             entities_attached: vec![entity_ident],
             immediate_data: self.immediate_data,
+            sampler_lod: self.sampler_lod,
             is_enabled: self.is_enabled,
         });
 
@@ -927,6 +948,7 @@ impl Parse for MaterialDescriptor {
         let mut pipeline_id: Option<PipelineIdVariants> = None;
         let mut attachments: Vec<ShaderAttachmentDescriptor> = Vec::new();
         let mut immediate_data: Option<Expr> = None;
+        let mut sampler_lod: Option<Expr> = None;
         let mut is_enabled: Option<LitBool> = None;
         let mut ident: Option<Lit> = None;
 
@@ -939,6 +961,7 @@ impl Parse for MaterialDescriptor {
                     attachments = specified_attachments
                 }
                 MaterialParameters::ImmediateData(data) => immediate_data = Some(data),
+                MaterialParameters::SamplerLod(lod) => sampler_lod = Some(lod),
                 MaterialParameters::IsEnabled(enabled_lit) => is_enabled = Some(enabled_lit),
                 MaterialParameters::Ident(lit) => ident = Some(lit),
             }
@@ -952,6 +975,7 @@ impl Parse for MaterialDescriptor {
             pipeline_id,
             attachments,
             immediate_data,
+            sampler_lod,
             is_enabled,
             ident,
         })
@@ -963,6 +987,7 @@ struct TransformedMaterialDescriptor {
     attachments: Vec<ShaderAttachmentDescriptor>,
     entities_attached: Vec<EntityId>,
     immediate_data: Option<Expr>,
+    sampler_lod: Option<Expr>,
     is_enabled: Option<LitBool>,
 }
 
@@ -971,6 +996,7 @@ enum MaterialParameters {
     Attachments(Vec<ShaderAttachmentDescriptor>),
     IsEnabled(LitBool),
     ImmediateData(Expr),
+    SamplerLod(Expr),
     Ident(Lit),
 }
 
@@ -994,6 +1020,7 @@ impl MaterialParameters {
                 ))
             }
             "immediate_data" => Ok(Self::ImmediateData(input.parse()?)),
+            "sampler_lod" => Ok(Self::SamplerLod(input.parse()?)),
             "is_enabled" => Ok(Self::IsEnabled(input.parse()?)),
             "ident" => Ok(Self::Ident(input.parse()?)),
             _ => Err(syn::Error::new_spanned(
@@ -1022,6 +1049,7 @@ impl Parse for MaterialParameters {
                 ))
             }
             "immediate_data" => Ok(Self::ImmediateData(input.parse()?)),
+            "sampler_lod" => Ok(Self::SamplerLod(input.parse()?)),
             "is_enabled" => Ok(Self::IsEnabled(input.parse()?)),
             "ident" => Ok(Self::Ident(input.parse()?)),
             _ => Err(syn::Error::new_spanned(
@@ -1109,6 +1137,7 @@ impl Parse for PipelineIdVariants {
 #[derive(Clone)]
 struct ScreenSpacePipelineIdDescriptor {
     fragment_shader_path: LitStr,
+    fragment_entry: Option<LitStr>,
 }
 
 impl Parse for ScreenSpacePipelineIdDescriptor {
@@ -1117,6 +1146,7 @@ impl Parse for ScreenSpacePipelineIdDescriptor {
         braced!(content in input);
         let fields = content.parse_terminated(SimpleField::parse, Token![,])?;
         let mut fragment_shader_path: Option<LitStr> = None;
+        let mut fragment_entry: Option<LitStr> = None;
 
         for field in fields {
             match field.ident.to_string().as_str() {
@@ -1148,6 +1178,28 @@ impl Parse for ScreenSpacePipelineIdDescriptor {
                         }
                     }
                 }
+                "fragment_entry" => {
+                    if let Some(value) = field.value {
+                        match value {
+                            SimpleFieldValue::Literal(lit) => {
+                                if let Lit::Str(str) = lit {
+                                    fragment_entry = Some(str)
+                                } else {
+                                    return Err(syn::Error::new(
+                                        lit.span(),
+                                        "Only string literals are valid entry points",
+                                    ));
+                                }
+                            }
+                            rest => {
+                                return Err(syn::Error::new_spanned(
+                                    rest,
+                                    "Only string literals are valid entry points",
+                                ));
+                            }
+                        }
+                    }
+                }
                 "vertex_layouts" => {
                     return Err(syn::Error::new(
                         field.ident.span(),
@@ -1187,6 +1239,7 @@ impl Parse for ScreenSpacePipelineIdDescriptor {
 
         Ok(ScreenSpacePipelineIdDescriptor {
             fragment_shader_path,
+            fragment_entry,
         })
     }
 }
@@ -1195,18 +1248,29 @@ impl quote::ToTokens for ScreenSpacePipelineIdDescriptor {
     fn to_tokens(&self, tokens: &mut TokenStream2) {
         let ScreenSpacePipelineIdDescriptor {
             fragment_shader_path,
+            fragment_entry,
         } = self;
+        let fragment_entry = if let Some(entry) = fragment_entry {
+            quote! {#entry}
+        } else {
+            quote! {"main"}
+        };
         tokens.extend(quote! {
             v4::engine_management::pipeline::PipelineId {
                 vertex_shader: v4::engine_management::pipeline::PipelineShader::Path(""),
                 spirv_vertex_shader: false,
                 fragment_shader: v4::engine_management::pipeline::PipelineShader::Path(#fragment_shader_path),
                 spirv_fragment_shader: false,
+                vertex_entry: "main",
+                fragment_entry: #fragment_entry,
                 vertex_layouts: Vec::new(),
                 uses_camera: false,
                 is_screen_space: true,
                 geometry_details: Default::default(),
+                immediate_size: 0,
                 render_priority: i32::MAX,
+                blend_state: v4::engine_management::pipeline::PipelineId::DEFAULT_BLEND_STATE,
+                writes_gbuffer: false,
             }
         });
     }
@@ -1218,11 +1282,15 @@ struct PipelineIdDescriptor {
     spirv_vertex_shader: Option<LitBool>,
     fragment_shader_path: LitStr,
     spirv_fragment_shader: Option<LitBool>,
+    vertex_entry: Option<LitStr>,
+    fragment_entry: Option<LitStr>,
     vertex_layouts: Vec<ExprCall>,
     uses_camera: LitBool,
     geometry_details: Option<GeometryDetailsDescriptor>,
     immediate_size: Option<Expr>,
     render_priority: Option<Expr>,
+    blend: Option<Expr>,
+    writes_gbuffer: Option<LitBool>,
     ident: Option<Lit>,
 }
 
@@ -1235,11 +1303,15 @@ impl Parse for PipelineIdDescriptor {
         let mut spirv_vertex_shader: Option<LitBool> = None;
         let mut fragment_shader_path: Option<LitStr> = None;
         let mut spirv_fragment_shader: Option<LitBool> = None;
+        let mut vertex_entry: Option<LitStr> = None;
+        let mut fragment_entry: Option<LitStr> = None;
         let mut vertex_layouts: Vec<ExprCall> = Vec::new();
         let mut uses_camera: Option<LitBool> = None;
         let mut geometry_details: Option<GeometryDetailsDescriptor> = None;
         let mut immediate_size: Option<Expr> = None;
         let mut render_priority: Option<Expr> = None;
+        let mut blend: Option<Expr> = None;
+        let mut writes_gbuffer: Option<LitBool> = None;
         let mut ident: Option<Lit> = None;
 
         for field in fields {
@@ -1332,6 +1404,50 @@ impl Parse for PipelineIdDescriptor {
                         }
                     }
                 }
+                "vertex_entry" => {
+                    if let Some(value) = field.value {
+                        match value {
+                            SimpleFieldValue::Literal(lit) => {
+                                if let Lit::Str(str) = lit {
+                                    vertex_entry = Some(str)
+                                } else {
+                                    return Err(syn::Error::new(
+                                        lit.span(),
+                                        "Only string literals are valid entry points",
+                                    ));
+                                }
+                            }
+                            rest => {
+                                return Err(syn::Error::new_spanned(
+                                    rest,
+                                    "Only string literals are valid entry points",
+                                ));
+                            }
+                        }
+                    }
+                }
+                "fragment_entry" => {
+                    if let Some(value) = field.value {
+                        match value {
+                            SimpleFieldValue::Literal(lit) => {
+                                if let Lit::Str(str) = lit {
+                                    fragment_entry = Some(str)
+                                } else {
+                                    return Err(syn::Error::new(
+                                        lit.span(),
+                                        "Only string literals are valid entry points",
+                                    ));
+                                }
+                            }
+                            rest => {
+                                return Err(syn::Error::new_spanned(
+                                    rest,
+                                    "Only string literals are valid entry points",
+                                ));
+                            }
+                        }
+                    }
+                }
                 "vertex_layouts" => {
                     if let Some(value) = field.value {
                         match value {
@@ -1387,6 +1503,43 @@ impl Parse for PipelineIdDescriptor {
                         render_priority = Some(priority);
                     }
                 }
+                "blend" => {
+                    if let Some(value) = field.value {
+                        match value {
+                            SimpleFieldValue::Expression(expr) => {
+                                blend = Some(expr);
+                            }
+                            rest => {
+                                return Err(syn::Error::new_spanned(
+                                    rest,
+                                    "The blend state must be a wgpu::BlendState expression",
+                                ));
+                            }
+                        }
+                    }
+                }
+                "writes_gbuffer" => {
+                    if let Some(value) = field.value {
+                        match value {
+                            SimpleFieldValue::Literal(lit) => {
+                                if let Lit::Bool(bool) = lit {
+                                    writes_gbuffer = Some(bool);
+                                } else {
+                                    return Err(syn::Error::new(
+                                        lit.span(),
+                                        "Only boolean literals are valid here",
+                                    ));
+                                }
+                            }
+                            rest => {
+                                return Err(syn::Error::new_spanned(
+                                    rest,
+                                    "Only boolean literals are valid here",
+                                ));
+                            }
+                        }
+                    }
+                }
                 "ident" => {
                     if let Some(SimpleFieldValue::Literal(lit)) = field.value {
                         ident = Some(lit);
@@ -1417,11 +1570,15 @@ impl Parse for PipelineIdDescriptor {
             spirv_vertex_shader,
             fragment_shader_path,
             spirv_fragment_shader,
+            vertex_entry,
+            fragment_entry,
             vertex_layouts,
             uses_camera,
             geometry_details,
             immediate_size,
             render_priority,
+            blend,
+            writes_gbuffer,
             ident,
         })
     }
@@ -1434,11 +1591,15 @@ impl quote::ToTokens for PipelineIdDescriptor {
             spirv_vertex_shader,
             fragment_shader_path,
             spirv_fragment_shader,
+            vertex_entry,
+            fragment_entry,
             vertex_layouts,
             uses_camera,
             geometry_details,
             immediate_size,
             render_priority,
+            blend,
+            writes_gbuffer,
             ..
         } = self;
         let geometry_details = match geometry_details {
@@ -1456,6 +1617,17 @@ impl quote::ToTokens for PipelineIdDescriptor {
             quote! {false}
         };
 
+        let vertex_entry = if let Some(entry) = vertex_entry {
+            quote! {#entry}
+        } else {
+            quote! {"main"}
+        };
+        let fragment_entry = if let Some(entry) = fragment_entry {
+            quote! {#entry}
+        } else {
+            quote! {"main"}
+        };
+
         let immediate_size = if let Some(expr) = immediate_size.as_ref() {
             quote! {#expr}
         } else {
@@ -1468,18 +1640,34 @@ impl quote::ToTokens for PipelineIdDescriptor {
             quote! {0}
         };
 
+        let blend_state = if let Some(blend) = blend.as_ref() {
+            quote! {Some(#blend)}
+        } else {
+            quote! {v4::engine_management::pipeline::PipelineId::DEFAULT_BLEND_STATE}
+        };
+
+        let writes_gbuffer = if let Some(writes_gbuffer) = writes_gbuffer {
+            quote! {#writes_gbuffer}
+        } else {
+            quote! {false}
+        };
+
         tokens.extend(quote! {
             v4::engine_management::pipeline::PipelineId {
                 vertex_shader: v4::engine_management::pipeline::PipelineShader::Path(#vertex_shader_path),
                 spirv_vertex_shader: #spirv_vertex_shader,
                 fragment_shader: v4::engine_management::pipeline::PipelineShader::Path(#fragment_shader_path),
                 spirv_fragment_shader: #spirv_fragment_shader,
+                vertex_entry: #vertex_entry,
+                fragment_entry: #fragment_entry,
                 vertex_layouts: vec![#(#vertex_layouts),*],
                 uses_camera: #uses_camera,
                 is_screen_space: false,
                 geometry_details: #geometry_details,
                 immediate_size: #immediate_size,
                 render_priority: #render_priority,
+                blend_state: #blend_state,
+                writes_gbuffer: #writes_gbuffer,
             }
         });
     }
@@ -1507,6 +1695,7 @@ struct GeometryDetailsDescriptor {
     front_face: Option<ExprPath>,
     cull_mode: Option<ExprPath>,
     polygon_mode: Option<ExprPath>,
+    depth_test: Option<LitBool>,
 }
 
 impl Parse for GeometryDetailsDescriptor {
@@ -1582,6 +1771,17 @@ impl Parse for GeometryDetailsDescriptor {
                         ));
                     }
                 },
+                "depth_test" => match field.value.unwrap() {
+                    SimpleFieldValue::Literal(Lit::Bool(depth_test)) => {
+                        details.depth_test = Some(depth_test);
+                    }
+                    rest => {
+                        return Err(syn::Error::new_spanned(
+                            rest,
+                            "Invalid argument passed into geometry details depth test field",
+                        ));
+                    }
+                },
                 _ => {
                     return Err(syn::Error::new_spanned(
                         field.ident,
@@ -1603,6 +1803,7 @@ impl quote::ToTokens for GeometryDetailsDescriptor {
             front_face,
             cull_mode,
             polygon_mode,
+            depth_test,
         } = self;
 
         let topology = if let Some(topology) = topology {
@@ -1635,6 +1836,12 @@ impl quote::ToTokens for GeometryDetailsDescriptor {
             quote! {polygon_mode: Default::default()}
         };
 
+        let depth_test = if let Some(depth_test) = depth_test {
+            quote! {depth_test: #depth_test}
+        } else {
+            quote! {depth_test: true}
+        };
+
         tokens.extend(quote! {
             v4::engine_management::pipeline::GeometryDetails {
             #topology,
@@ -1642,6 +1849,7 @@ impl quote::ToTokens for GeometryDetailsDescriptor {
             #front_face,
             #cull_mode,
             #polygon_mode,
+            #depth_test,
             }
         });
     }
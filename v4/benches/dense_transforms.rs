@@ -0,0 +1,88 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use nalgebra::Vector3;
+use v4::{
+    EngineDetails,
+    builtin_components::transform_component::TransformComponent,
+    ecs::scene::Scene,
+    engine_support::{gamepad_state::GamepadState, input_state::MockInput},
+};
+
+const TRANSFORM_COUNT: u32 = 10_000;
+
+fn position(index: u32) -> Vector3<f32> {
+    Vector3::new(index as f32, 0.0, -(index as f32))
+}
+
+/// A scene with one entity per transform, the transforms stored either in the boxed component
+/// list or in the scene's dense storage
+fn create_scene(device: &wgpu::Device, queue: &wgpu::Queue, dense: bool) -> Scene {
+    let mut scene = Scene::default();
+    for index in 0..TRANSFORM_COUNT {
+        let transform = TransformComponent::builder()
+            .position(position(index))
+            .build();
+        if dense {
+            let entity = scene.create_entity(None, Vec::new(), Vec::new(), None, true);
+            scene.attach_dense_component(entity, transform);
+        } else {
+            scene.create_entity(None, vec![Box::new(transform)], Vec::new(), None, true);
+        }
+    }
+
+    let (workload_sender, _) = crossbeam_channel::unbounded();
+    let (_, workload_output_receiver) = crossbeam_channel::unbounded();
+    let (engine_action_sender, _) = crossbeam_channel::unbounded();
+    pollster::block_on(scene.initialize(
+        device,
+        queue,
+        workload_sender,
+        workload_output_receiver,
+        engine_action_sender,
+    ));
+    scene
+}
+
+/// Moves every transform and runs `Scene::update`, which rebuilds the moved matrices, once with
+/// boxed transforms and once with dense ones
+fn update_scene(c: &mut Criterion) {
+    let (device, queue) = wgpu::Device::noop(&wgpu::DeviceDescriptor::default());
+    let input = MockInput::default();
+    let gamepads = GamepadState::default();
+    let engine_details = EngineDetails::default();
+    let step = Vector3::new(0.01, 0.0, 0.0);
+
+    let mut group = c.benchmark_group("scene update");
+    group.sample_size(10);
+
+    let mut boxed_scene = create_scene(&device, &queue, false);
+    group.bench_function("boxed transforms", |b| {
+        b.iter(|| {
+            for component in boxed_scene.all_components_mut() {
+                let transform = component
+                    .downcast_mut::<TransformComponent>()
+                    .expect("Only transforms are benchmarked");
+                transform.set_position(transform.get_position() + step);
+            }
+            boxed_scene.update(&device, &queue, &input, &gamepads, &engine_details)
+        })
+    });
+
+    let mut dense_scene = create_scene(&device, &queue, true);
+    group.bench_function("dense transforms", |b| {
+        b.iter(|| {
+            let column = dense_scene
+                .dense_storage_mut()
+                .column_mut::<TransformComponent>()
+                .expect("The transforms were attached");
+            for transform in column.data_mut() {
+                transform.set_position(transform.get_position() + step);
+            }
+            dense_scene.update(&device, &queue, &input, &gamepads, &engine_details)
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, update_scene);
+criterion_main!(benches);
@@ -0,0 +1,201 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use nalgebra::Vector3;
+use v4::{
+    builtin_components::transform_component::TransformComponent,
+    ecs::component::{Component, ComponentSystem},
+};
+use wgpu::util::DeviceExt;
+
+const MATERIAL_COUNT: usize = 200;
+const ENTITIES_PER_MATERIAL: usize = 10;
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// One pipeline and its entities' transforms, standing in for a material
+struct BenchMaterial {
+    pipeline: wgpu::RenderPipeline,
+    transforms: Vec<Component>,
+}
+
+fn create_materials(device: &wgpu::Device) -> Vec<BenchMaterial> {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Render bundle benchmark shader"),
+        source: wgpu::ShaderSource::Wgsl(
+            "
+@vertex
+fn vertex(
+    @location(0) position: vec3<f32>,
+    @location(1) m0: vec4<f32>,
+    @location(2) m1: vec4<f32>,
+    @location(3) m2: vec4<f32>,
+    @location(4) m3: vec4<f32>,
+) -> @builtin(position) vec4<f32> {
+    return mat4x4<f32>(m0, m1, m2, m3) * vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+"
+            .into(),
+        ),
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Render bundle benchmark pipeline layout"),
+        bind_group_layouts: &[],
+        immediate_size: 0,
+    });
+
+    (0..MATERIAL_COUNT)
+        .map(|material_index| {
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(&format!(
+                    "Render bundle benchmark pipeline {material_index}"
+                )),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vertex"),
+                    compilation_options: Default::default(),
+                    buffers: &[
+                        wgpu::VertexBufferLayout {
+                            array_stride: 12,
+                            step_mode: wgpu::VertexStepMode::Vertex,
+                            attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+                        },
+                        TransformComponent::vertex_layout::<1>(),
+                    ],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fragment"),
+                    compilation_options: Default::default(),
+                    targets: &[Some(FORMAT.into())],
+                }),
+                multiview_mask: None,
+                cache: None,
+            });
+            let transforms = (0..ENTITIES_PER_MATERIAL)
+                .map(|entity_index| {
+                    let mut transform = TransformComponent::builder()
+                        .position(Vector3::new(
+                            entity_index as f32,
+                            material_index as f32,
+                            0.0,
+                        ))
+                        .build();
+                    transform.initialize(device);
+                    Box::new(transform) as Component
+                })
+                .collect();
+            BenchMaterial {
+                pipeline,
+                transforms,
+            }
+        })
+        .collect()
+}
+
+/// Records the geometry pass for 200 materials, once drawing every transform immediately and once
+/// replaying a render bundle per material
+fn record_geometry_pass(c: &mut Criterion) {
+    let (device, queue) = wgpu::Device::noop(&wgpu::DeviceDescriptor::default());
+    let materials = create_materials(&device);
+    let triangle = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Render bundle benchmark triangle"),
+        contents: bytemuck::cast_slice(&[[0.0f32, 0.5, 0.0], [-0.5, -0.5, 0.0], [0.5, -0.5, 0.0]]),
+        usage: wgpu::BufferUsages::VERTEX,
+    });
+    let view = device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: Some("Render bundle benchmark target"),
+            size: wgpu::Extent3d {
+                width: 64,
+                height: 64,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default());
+
+    let record_pass = |draw: &dyn Fn(&mut wgpu::RenderPass)| {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render bundle benchmark encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render bundle benchmark pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                multiview_mask: None,
+            });
+            draw(&mut render_pass);
+        }
+        queue.submit([encoder.finish()]);
+    };
+
+    c.bench_function("immediate recording", |b| {
+        b.iter(|| {
+            record_pass(&|render_pass| {
+                render_pass.set_vertex_buffer(0, triangle.slice(..));
+                for material in &materials {
+                    render_pass.set_pipeline(&material.pipeline);
+                    for transform in &material.transforms {
+                        transform.render(&device, &queue, render_pass, &[]);
+                        render_pass.draw(0..3, 0..1);
+                    }
+                }
+            })
+        })
+    });
+
+    let bundles: Vec<wgpu::RenderBundle> = materials
+        .iter()
+        .map(|material| {
+            let mut bundle_encoder =
+                device.create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                    label: Some("Render bundle benchmark bundle encoder"),
+                    color_formats: &[Some(FORMAT)],
+                    depth_stencil: None,
+                    sample_count: 1,
+                    multiview: None,
+                });
+            bundle_encoder.set_pipeline(&material.pipeline);
+            bundle_encoder.set_vertex_buffer(0, triangle.slice(..));
+            for transform in &material.transforms {
+                assert!(
+                    transform.render_bundle(&device, &queue, &mut bundle_encoder, &[]),
+                    "Transforms can be recorded into render bundles"
+                );
+                bundle_encoder.draw(0..3, 0..1);
+            }
+            bundle_encoder.finish(&wgpu::RenderBundleDescriptor {
+                label: Some("Render bundle benchmark bundle"),
+            })
+        })
+        .collect();
+    c.bench_function("render bundle replay", |b| {
+        b.iter(|| record_pass(&|render_pass| render_pass.execute_bundles(bundles.iter())))
+    });
+}
+
+criterion_group!(benches, record_geometry_pass);
+criterion_main!(benches);
@@ -6,12 +6,18 @@ use v4_core::{
         component::{Component, ComponentId},
         compute::Compute,
         entity::EntityId,
-        scene::{Scene, Workload},
+        render_target::RenderTargetId,
+        scene::{
+            Background, CancellableWorkload, DebugLineVertex, Scene, Workload, WorkloadFactory,
+            WorkloadProgressSender,
+        },
+        ui_hit_testing::UiRect,
     },
     engine_management::{
         engine_action::{
             CreateTextBufferEngineAction, SetCursorLockEngineAction, SetCursorPositionEngineAction,
-            UpdateTextBufferEngineAction,
+            SetCursorVisibleEngineAction, SetFullscreenEngineAction, SetWindowTitleEngineAction,
+            ToggleDebugDrawEngineAction, UpdateTextBufferEngineAction,
         },
         font_management::{TextAttributes, TextComponentProperties, TextDisplayInfo},
     },
@@ -38,6 +44,122 @@ impl Action for WorkloadAction {
     }
 }
 
+pub struct SequencedWorkloadAction {
+    pub component_id: ComponentId,
+    pub sequence: u64,
+    pub workload: Workload,
+}
+
+impl Debug for SequencedWorkloadAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SequencedWorkloadAction")
+            .field("component_id", &self.component_id)
+            .field("sequence", &self.sequence)
+            .field("workload", &"Future")
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl Action for SequencedWorkloadAction {
+    async fn execute_async(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene
+            .attach_sequenced_workload(self.component_id, self.sequence, self.workload)
+            .await;
+    }
+}
+
+/// A `WorkloadAction` whose workload is dropped if it runs longer than the timeout, a
+/// `WorkloadTimeout` is added to the component's outputs in its place
+pub struct TimeoutWorkloadAction(pub ComponentId, pub Workload, pub std::time::Duration);
+
+impl Debug for TimeoutWorkloadAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TimeoutWorkloadAction")
+            .field(&self.0)
+            .field(&"Future")
+            .field(&self.2)
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl Action for TimeoutWorkloadAction {
+    async fn execute_async(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene
+            .attach_workload_with_timeout(self.0, self.1, self.2)
+            .await;
+    }
+}
+
+/// Attaches a workload created by `WorkloadHandle::new`, the component keeps the handle to cancel
+/// the workload or read its output
+#[derive(Debug)]
+pub struct CancellableWorkloadAction(pub CancellableWorkload);
+
+impl Action for CancellableWorkloadAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.attach_handled_workload(self.0);
+    }
+}
+
+/// Creates the workload from a progress sender for the component, so it can report progress
+pub struct ProgressWorkloadAction(
+    pub ComponentId,
+    pub Box<dyn FnOnce(WorkloadProgressSender) -> Workload + Send>,
+);
+
+impl Debug for ProgressWorkloadAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ProgressWorkloadAction")
+            .field(&self.0)
+            .field(&"Workload factory")
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl Action for ProgressWorkloadAction {
+    async fn execute_async(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        let progress_sender = scene.workload_progress_sender(self.0);
+        let workload = (self.1)(progress_sender.clone());
+        scene
+            .attach_workload_with_progress(self.0, workload, progress_sender)
+            .await;
+    }
+}
+
+pub struct IntervalWorkloadAction {
+    pub component_id: ComponentId,
+    pub interval: std::time::Duration,
+    pub factory: WorkloadFactory,
+}
+
+impl Debug for IntervalWorkloadAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IntervalWorkloadAction")
+            .field("component_id", &self.component_id)
+            .field("interval", &self.interval)
+            .field("factory", &"Workload factory")
+            .finish()
+    }
+}
+
+impl Action for IntervalWorkloadAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.attach_interval_workload(self.component_id, self.interval, self.factory);
+    }
+}
+
+#[derive(Debug)]
+pub struct CancelIntervalWorkloadsAction(pub ComponentId);
+
+impl Action for CancelIntervalWorkloadsAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.cancel_interval_workloads(self.0);
+    }
+}
+
 #[derive(Debug)]
 pub struct WorkloadOutputFreeAction(pub ComponentId, pub usize);
 
@@ -48,6 +170,68 @@ impl Action for WorkloadOutputFreeAction {
     }
 }
 
+#[derive(Debug)]
+pub struct SequencedWorkloadOutputFreeAction(pub ComponentId, pub u64);
+
+impl Action for SequencedWorkloadOutputFreeAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.take_output_for(self.0, self.1);
+    }
+}
+
+/// Frees the output of an interval workload by its launch count
+#[derive(Debug)]
+pub struct IntervalWorkloadOutputFreeAction(pub ComponentId, pub u64);
+
+impl Action for IntervalWorkloadOutputFreeAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.take_interval_output_for(self.0, self.1);
+    }
+}
+
+#[derive(Debug)]
+pub struct SetMaterialRenderBundleAction(pub ComponentId, pub bool);
+
+impl Action for SetMaterialRenderBundleAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        if let Some(material) = scene.get_material_mut(self.0) {
+            material.set_render_bundle_enabled(self.1);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SetDepthLoadOpAction(pub wgpu::LoadOp<f32>);
+
+impl Action for SetDepthLoadOpAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.set_depth_load_op(self.0);
+    }
+}
+
+/// `None` falls back to the engine clear color
+#[derive(Debug)]
+pub struct SetBackgroundAction(pub Option<Background>);
+
+impl Action for SetBackgroundAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        match self.0 {
+            Some(background) => scene.set_background(background),
+            None => scene.clear_background(),
+        }
+    }
+}
+
+/// See `Scene::set_viewport`, `None` draws to the whole frame again
+#[derive(Debug)]
+pub struct SetViewportAction(pub Option<[f32; 4]>);
+
+impl Action for SetViewportAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.set_viewport(self.0);
+    }
+}
+
 #[derive(Debug)]
 pub struct EntityToggleAction(pub EntityId, pub Option<bool>);
 
@@ -79,6 +263,15 @@ impl Action for ComponentToggleAction {
     }
 }
 
+#[derive(Debug)]
+pub struct SetComponentRenderingOrderAction(pub ComponentId, pub i32);
+
+impl Action for SetComponentRenderingOrderAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.set_component_rendering_order(self.0, self.1);
+    }
+}
+
 #[derive(Debug)]
 pub struct RegisterUiComponentAction {
     pub component_id: ComponentId,
@@ -97,6 +290,16 @@ impl Action for RegisterUiComponentAction {
     }
 }
 
+/// Undoes `RegisterUiComponentAction`, e.g. when a text component is torn down
+#[derive(Debug)]
+pub struct UnregisterUiComponentAction(pub ComponentId);
+
+impl Action for UnregisterUiComponentAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.unregister_ui_component(self.0);
+    }
+}
+
 #[derive(Debug)]
 pub struct UpdateTextComponentAction {
     pub component_id: ComponentId,
@@ -104,6 +307,8 @@ pub struct UpdateTextComponentAction {
     pub text_attributes: Option<TextAttributes>,
     pub text_metrics: Option<glyphon::Metrics>,
     pub text_display_info: Option<TextDisplayInfo>,
+    /// Changes only the alignment, `text_display_info` takes precedence when both are given
+    pub alignment: Option<glyphon::cosmic_text::Align>,
 }
 
 impl Action for UpdateTextComponentAction {
@@ -121,6 +326,7 @@ impl Action for UpdateTextComponentAction {
             text_attributes: self.text_attributes,
             text_metrics: self.text_metrics,
             text_display_info: self.text_display_info,
+            alignment: self.alignment,
         }));
     }
 }
@@ -130,9 +336,7 @@ pub struct SetEntityActiveMaterialAction(pub EntityId, pub ComponentId);
 
 impl Action for SetEntityActiveMaterialAction {
     fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
-        if let Some(entity) = scene.get_entity_mut(self.0) {
-            entity.set_active_material(self.1);
-        }
+        scene.set_entity_active_material(self.0, self.1);
     }
 }
 
@@ -157,6 +361,74 @@ impl Action for CreateEntityAction {
     }
 }
 
+/// Creates an entity under `parent`, e.g. a muzzle flash spawned by its weapon. The child is
+/// dropped with a warning if the parent was removed before the action ran.
+#[derive(Debug)]
+pub struct CreateChildEntityAction {
+    pub parent: EntityId,
+    pub components: Vec<Component>,
+    pub computes: Vec<Compute>,
+    pub active_material: Option<ComponentId>,
+    pub is_enabled: bool,
+}
+
+impl Action for CreateChildEntityAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        if !scene.is_entity_alive(self.parent) || scene.get_entity(self.parent).is_none() {
+            log::warn!(
+                "Could not create a child of entity {}, it does not exist.",
+                self.parent
+            );
+            return;
+        }
+        scene.create_entity(
+            Some(self.parent),
+            self.components,
+            self.computes,
+            self.active_material,
+            self.is_enabled,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct RemoveEntityAction(pub EntityId);
+
+impl Action for RemoveEntityAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        if !scene.remove_entity(self.0) {
+            log::warn!("Ignoring removal of entity {}: it does not exist", self.0);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RemoveComponentAction(pub ComponentId);
+
+impl Action for RemoveComponentAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        if scene.remove_component(self.0).is_none() {
+            log::warn!(
+                "Ignoring removal of component {}: it does not exist",
+                self.0
+            );
+        }
+    }
+}
+
+/// Registers the component's hit-testable bounds with the scene, or removes them with `None`
+#[derive(Debug)]
+pub struct SetUiBoundsAction(pub ComponentId, pub Option<UiRect>);
+
+impl Action for SetUiBoundsAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        match self.1 {
+            Some(bounds) => scene.set_ui_bounds(self.0, bounds),
+            None => scene.clear_ui_bounds(self.0),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct SetActiveCameraAction(pub ComponentId);
 
@@ -219,6 +491,17 @@ impl Action for UpdateCameraBufferAction {
     }
 }
 
+#[derive(Debug)]
+pub struct UpdateRenderTargetCameraAction(pub RenderTargetId, pub RawCameraData);
+
+impl Action for UpdateRenderTargetCameraAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, device: &Device, queue: &Queue) {
+        if let Some(render_target) = scene.get_render_target_mut(self.0) {
+            render_target.update_camera(device, queue, bytemuck::cast_slice(&[self.1]));
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct SetCursorLockAction(pub bool);
 
@@ -228,6 +511,16 @@ impl Action for SetCursorLockAction {
     }
 }
 
+/// Exits the engine once the current frame is done, e.g. from a "Quit" button
+#[derive(Debug)]
+pub struct ExitAction;
+
+impl Action for ExitAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.request_exit();
+    }
+}
+
 #[derive(Debug)]
 pub struct SetCursorPositionAction(pub winit::dpi::Position);
 
@@ -236,3 +529,52 @@ impl Action for SetCursorPositionAction {
         scene.send_engine_action(Box::new(SetCursorPositionEngineAction(self.0)));
     }
 }
+
+/// Only shows or hides the cursor, unlike `SetCursorLockAction` it doesn't grab it
+#[derive(Debug)]
+pub struct SetCursorVisibleAction(pub bool);
+
+impl Action for SetCursorVisibleAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.send_engine_action(Box::new(SetCursorVisibleEngineAction(self.0)));
+    }
+}
+
+#[derive(Debug)]
+pub struct SetWindowTitleAction(pub String);
+
+impl Action for SetWindowTitleAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.send_engine_action(Box::new(SetWindowTitleEngineAction(self.0)));
+    }
+}
+
+/// `None` leaves fullscreen, the surface is resized once the window reports its new size
+#[derive(Debug)]
+pub struct SetFullscreenAction(pub Option<winit::monitor::Fullscreen>);
+
+impl Action for SetFullscreenAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.send_engine_action(Box::new(SetFullscreenEngineAction(self.0)));
+    }
+}
+
+/// Queues line segments, as pairs of vertices in world space, to be drawn this frame while debug
+/// drawing is enabled
+#[derive(Debug)]
+pub struct DrawDebugLinesAction(pub Vec<DebugLineVertex>);
+
+impl Action for DrawDebugLinesAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.push_debug_lines(&self.0);
+    }
+}
+
+#[derive(Debug)]
+pub struct ToggleDebugDrawAction;
+
+impl Action for ToggleDebugDrawAction {
+    fn execute(self: Box<Self>, scene: &mut Scene, _device: &Device, _queue: &Queue) {
+        scene.send_engine_action(Box::new(ToggleDebugDrawEngineAction));
+    }
+}
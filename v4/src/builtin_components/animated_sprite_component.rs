@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use crate::v4;
+use v4_core::ecs::{
+    actions::ActionQueue,
+    component::{ComponentSystem, UpdateParams},
+};
+use v4_macros::component;
+
+use super::sprite_component::SpriteComponent;
+
+/// Cycles the UV rect of the sibling `SpriteComponent` through `frames`. Only the sprite's
+/// per-sprite buffer is rewritten, so place this component before the sprite to show a new frame
+/// the same frame it is reached.
+#[component]
+pub struct AnimatedSpriteComponent {
+    /// The UV rect of every frame, e.g. from `AnimatedSpriteComponent::strip_frames`
+    frames: Vec<[f32; 4]>,
+    #[default(12.0)]
+    frames_per_second: f32,
+    #[default(true)]
+    looping: bool,
+    #[default(true)]
+    playing: bool,
+    #[default]
+    current_frame: usize,
+    #[default]
+    frame_time: Duration,
+    #[default(true)]
+    frame_changed: bool,
+}
+
+impl AnimatedSpriteComponent {
+    /// The UV rects of a horizontal strip of `frame_count` equally wide frames
+    pub fn strip_frames(frame_count: u32) -> Vec<[f32; 4]> {
+        (0..frame_count)
+            .map(|index| SpriteComponent::atlas_cell(frame_count, 1, index))
+            .collect()
+    }
+
+    /// Moves the animation forward by `elapsed`, returns whether the current frame changed.
+    /// Called by `update` with the time since the previous frame.
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        if !self.playing || self.frames.is_empty() || self.frames_per_second <= 0.0 {
+            return false;
+        }
+        let frame_duration = Duration::from_secs_f32(1.0 / self.frames_per_second);
+        self.frame_time += elapsed;
+
+        let previous_frame = self.current_frame;
+        while self.frame_time >= frame_duration {
+            self.frame_time -= frame_duration;
+            if self.current_frame + 1 < self.frames.len() {
+                self.current_frame += 1;
+            } else if self.looping {
+                self.current_frame = 0;
+            } else {
+                self.playing = false;
+                self.frame_time = Duration::ZERO;
+                break;
+            }
+        }
+
+        let changed = self.current_frame != previous_frame;
+        self.frame_changed |= changed;
+        changed
+    }
+
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn current_frame(&self) -> usize {
+        self.current_frame
+    }
+
+    /// Jumps to `frame` and restarts its timing, frames past the end are clamped to the last one
+    pub fn set_frame(&mut self, frame: usize) {
+        self.current_frame = frame.min(self.frames.len().saturating_sub(1));
+        self.frame_time = Duration::ZERO;
+        self.frame_changed = true;
+    }
+
+    pub fn frames_per_second(&self) -> f32 {
+        self.frames_per_second
+    }
+
+    pub fn set_frames_per_second(&mut self, frames_per_second: f32) {
+        self.frames_per_second = frames_per_second;
+    }
+}
+
+impl ComponentSystem for AnimatedSpriteComponent {
+    fn update(
+        &mut self,
+        UpdateParams {
+            other_components,
+            entity_component_groupings,
+            engine_details,
+            ..
+        }: UpdateParams<'_, '_>,
+    ) -> ActionQueue {
+        self.advance(engine_details.delta_time);
+
+        if self.frame_changed
+            && let Some(uv_rect) = self.frames.get(self.current_frame).copied()
+        {
+            let sibling_components =
+                &mut other_components[entity_component_groupings[&self.parent_entity_id].clone()];
+            if let Some(sprite) = sibling_components
+                .iter_mut()
+                .find_map(|comp| comp.downcast_mut::<SpriteComponent>())
+            {
+                sprite.set_uv_rect(uv_rect);
+                self.frame_changed = false;
+            }
+        }
+
+        Vec::new()
+    }
+}
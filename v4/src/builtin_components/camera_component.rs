@@ -1,16 +1,21 @@
 use std::any::TypeId;
 
 use crate::{
-    builtin_actions::{SetCursorLockAction, UpdateCameraBufferAction},
+    builtin_actions::{
+        SetCursorLockAction, UpdateCameraBufferAction, UpdateRenderTargetCameraAction,
+    },
     v4,
 };
 use algoe::{bivector::Bivector, vector::GeometricOperations};
 use nalgebra::{Matrix4, Vector3, Vector4};
+use serde::{Deserialize, Serialize};
 use v4_core::{
     EngineDetails,
     ecs::{
         actions::ActionQueue,
-        component::{ComponentDetails, ComponentSystem, UpdateParams},
+        component::{ComponentDetails, ComponentId, ComponentSystem, UpdateParams},
+        render_target::RenderTargetId,
+        scene_descriptor::SerializableComponent,
     },
 };
 use v4_macros::component;
@@ -18,8 +23,18 @@ use winit::keyboard::KeyCode;
 
 use super::transform_component::TransformComponent;
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum CameraProjection {
+    #[default]
+    Perspective,
+    /// Shows a `width` by `height` box in world units around the camera, `field_of_view` and
+    /// `aspect_ratio` are ignored
+    Orthographic { width: f32, height: f32 },
+}
+
 #[component]
 pub struct CameraComponent {
+    /// Vertical field of view in degrees, used by `CameraProjection::Perspective`
     field_of_view: f32,
     aspect_ratio: f32,
     near_plane: f32,
@@ -34,6 +49,22 @@ pub struct CameraComponent {
     pitch: f32,
     #[default(0.0)]
     yaw: f32,
+    /// Renders into this target instead of the window. Such a camera follows its entity's
+    /// transform but does not take input, even when it is the active camera
+    #[default]
+    render_target: Option<RenderTargetId>,
+    #[default]
+    projection: CameraProjection,
+}
+
+impl CameraComponent {
+    pub fn projection(&self) -> CameraProjection {
+        self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: CameraProjection) {
+        self.projection = projection;
+    }
 }
 
 // #[async_trait::async_trait]
@@ -49,6 +80,20 @@ impl ComponentSystem for CameraComponent {
             ..
         }: UpdateParams<'_, '_>,
     ) -> ActionQueue {
+        if let Some(render_target) = self.render_target {
+            let sibling_components =
+                &other_components[entity_component_groupings[&self.parent_entity_id].clone()];
+            let transform = sibling_components
+                .iter()
+                .find_map(|comp| comp.downcast_ref::<TransformComponent>());
+
+            let raw_camera = RawCameraData::from_component(self, transform);
+            return vec![Box::new(UpdateRenderTargetCameraAction(
+                render_target,
+                raw_camera,
+            ))];
+        }
+
         if let Some(active) = active_camera {
             if input_manager.key_pressed(KeyCode::Escape) {
                 self.frozen = !self.frozen;
@@ -126,6 +171,57 @@ impl ComponentSystem for CameraComponent {
     }
 }
 
+/// A camera as saved in a scene descriptor. Render targets are not saved, so a loaded camera
+/// renders to the window.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CameraParameters {
+    pub field_of_view: f32,
+    pub aspect_ratio: f32,
+    pub near_plane: f32,
+    pub far_plane: f32,
+    pub sensitivity: f32,
+    pub movement_speed: f32,
+    pub frozen: bool,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub projection: CameraProjection,
+}
+
+impl SerializableComponent for CameraComponent {
+    type Parameters = CameraParameters;
+
+    fn to_parameters(&self) -> CameraParameters {
+        CameraParameters {
+            field_of_view: self.field_of_view,
+            aspect_ratio: self.aspect_ratio,
+            near_plane: self.near_plane,
+            far_plane: self.far_plane,
+            sensitivity: self.sensitivity,
+            movement_speed: self.movement_speed,
+            frozen: self.frozen,
+            pitch: self.pitch,
+            yaw: self.yaw,
+            projection: self.projection,
+        }
+    }
+
+    fn from_parameters(parameters: CameraParameters, id: ComponentId) -> Self {
+        CameraComponent::builder()
+            .field_of_view(parameters.field_of_view)
+            .aspect_ratio(parameters.aspect_ratio)
+            .near_plane(parameters.near_plane)
+            .far_plane(parameters.far_plane)
+            .sensitivity(parameters.sensitivity)
+            .movement_speed(parameters.movement_speed)
+            .frozen(parameters.frozen)
+            .pitch(parameters.pitch)
+            .yaw(parameters.yaw)
+            .projection(parameters.projection)
+            .id(id)
+            .build()
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct RawCameraData {
@@ -137,16 +233,14 @@ pub struct RawCameraData {
 
 impl RawCameraData {
     fn from_component(comp: &CameraComponent, transform: Option<&TransformComponent>) -> Self {
-        let c = 1.0 / (comp.field_of_view * std::f32::consts::PI / 360.0).tan();
-        let aspect_ratio = comp.aspect_ratio;
         let far_plane = comp.far_plane;
         let near_plane = comp.near_plane;
         let difference = far_plane - near_plane;
 
         let (view_matrix, inverted_view_matrix, pos) = if let Some(transform) = transform {
-            let mat = transform.create_matrix();
+            let mat = transform.world_matrix();
             if let Some(inverted) = mat.try_inverse() {
-                (inverted, mat, transform.get_position())
+                (inverted, mat, mat.fixed_view::<3, 1>(0, 3).into_owned())
             } else {
                 (Matrix4::identity(), Matrix4::identity(), Vector3::zeros())
             }
@@ -154,12 +248,24 @@ impl RawCameraData {
             (Matrix4::identity(), Matrix4::identity(), Vector3::zeros())
         };
 
-        let projection_matrix = Matrix4::from_columns(&[
-            Vector4::new(c / aspect_ratio, 0.0, 0.0, 0.0),
-            Vector4::new(0.0, c, 0.0, 0.0),
-            Vector4::new(0.0, 0.0, far_plane / difference, 1.0),
-            Vector4::new(0.0, 0.0, -(far_plane * near_plane) / difference, 0.0),
-        ]);
+        // Both map the near plane to depth 0 and the far plane to depth 1
+        let projection_matrix = match comp.projection {
+            CameraProjection::Perspective => {
+                let c = 1.0 / (comp.field_of_view * std::f32::consts::PI / 360.0).tan();
+                Matrix4::from_columns(&[
+                    Vector4::new(c / comp.aspect_ratio, 0.0, 0.0, 0.0),
+                    Vector4::new(0.0, c, 0.0, 0.0),
+                    Vector4::new(0.0, 0.0, far_plane / difference, 1.0),
+                    Vector4::new(0.0, 0.0, -(far_plane * near_plane) / difference, 0.0),
+                ])
+            }
+            CameraProjection::Orthographic { width, height } => Matrix4::from_columns(&[
+                Vector4::new(2.0 / width, 0.0, 0.0, 0.0),
+                Vector4::new(0.0, 2.0 / height, 0.0, 0.0),
+                Vector4::new(0.0, 0.0, 1.0 / difference, 0.0),
+                Vector4::new(0.0, 0.0, -near_plane / difference, 1.0),
+            ]),
+        };
 
         let matrix =projection_matrix * view_matrix;
 
@@ -0,0 +1,143 @@
+use std::marker::PhantomData;
+
+use crate::{
+    builtin_actions::{DrawDebugLinesAction, ToggleDebugDrawAction},
+    v4,
+};
+use nalgebra::{Matrix3, Matrix4, Point3, Vector3};
+use v4_core::ecs::{
+    actions::ActionQueue,
+    component::{ComponentSystem, UpdateParams},
+    scene::DebugLineVertex,
+};
+use v4_macros::component;
+use winit::keyboard::KeyCode;
+
+use super::{
+    mesh_component::{MeshComponent, VertexDescriptor},
+    transform_component::TransformComponent,
+};
+
+const BOUNDS_COLOR: [f32; 4] = [1.0, 0.85, 0.0, 1.0];
+const NORMAL_COLOR: [f32; 4] = [0.0, 0.8, 1.0, 1.0];
+
+/// Draws the bounding box and vertex normals of the sibling `MeshComponent<V>`, placed by the
+/// sibling `TransformComponent` if there is one. Nothing is shown until debug drawing is enabled,
+/// either through `toggle_key` or `RenderingManager::set_debug_draw_enabled`.
+#[component]
+pub struct DebugDrawComponent<V: VertexDescriptor> {
+    #[default(true)]
+    draw_bounds: bool,
+    #[default(true)]
+    draw_normals: bool,
+    #[default(0.1)]
+    normal_length: f32,
+    #[default]
+    toggle_key: Option<KeyCode>,
+    #[default]
+    vertex_type: PhantomData<V>,
+}
+
+impl<V: VertexDescriptor + Send + Sync> ComponentSystem for DebugDrawComponent<V> {
+    fn update(
+        &mut self,
+        UpdateParams {
+            other_components,
+            entity_component_groupings,
+            input_manager,
+            ..
+        }: UpdateParams<'_, '_>,
+    ) -> ActionQueue {
+        let mut actions: ActionQueue = Vec::new();
+        if let Some(toggle_key) = self.toggle_key
+            && input_manager.key_pressed(toggle_key)
+        {
+            actions.push(Box::new(ToggleDebugDrawAction));
+        }
+
+        let sibling_components =
+            &other_components[entity_component_groupings[&self.parent_entity_id].clone()];
+        let Some(mesh) = sibling_components
+            .iter()
+            .find_map(|comp| comp.downcast_ref::<MeshComponent<V>>())
+        else {
+            return actions;
+        };
+        let matrix = sibling_components
+            .iter()
+            .find_map(|comp| comp.downcast_ref::<TransformComponent>())
+            .map(|transform| transform.world_matrix())
+            .unwrap_or_else(Matrix4::identity);
+        let normal_matrix = matrix
+            .fixed_view::<3, 3>(0, 0)
+            .try_inverse()
+            .unwrap_or_else(Matrix3::identity)
+            .transpose();
+
+        let to_world = |position: [f32; 3]| -> [f32; 3] {
+            matrix
+                .transform_point(&Point3::from(position))
+                .coords
+                .into()
+        };
+
+        let mut lines = Vec::new();
+
+        if self.draw_bounds
+            && let Some((min, max)) = mesh.aabb()
+        {
+            let corners: Vec<[f32; 3]> = (0..8)
+                .map(|i| {
+                    to_world([
+                        if i & 1 == 0 { min[0] } else { max[0] },
+                        if i & 2 == 0 { min[1] } else { max[1] },
+                        if i & 4 == 0 { min[2] } else { max[2] },
+                    ])
+                })
+                .collect();
+            // Corners that differ in exactly one axis share an edge
+            for i in 0..8 {
+                for axis in [1, 2, 4] {
+                    if i & axis == 0 {
+                        lines.push(DebugLineVertex {
+                            position: corners[i],
+                            color: BOUNDS_COLOR,
+                        });
+                        lines.push(DebugLineVertex {
+                            position: corners[i | axis],
+                            color: BOUNDS_COLOR,
+                        });
+                    }
+                }
+            }
+        }
+
+        if self.draw_normals {
+            for (index, _) in mesh.enabled_models() {
+                for vertex in &mesh.vertices()[*index] {
+                    let (Some(position), Some(normal)) = (vertex.position(), vertex.normal())
+                    else {
+                        continue;
+                    };
+                    let start = to_world(position);
+                    let direction =
+                        (normal_matrix * Vector3::from(normal)).normalize() * self.normal_length;
+                    lines.push(DebugLineVertex {
+                        position: start,
+                        color: NORMAL_COLOR,
+                    });
+                    lines.push(DebugLineVertex {
+                        position: (Vector3::from(start) + direction).into(),
+                        color: NORMAL_COLOR,
+                    });
+                }
+            }
+        }
+
+        if !lines.is_empty() {
+            actions.push(Box::new(DrawDebugLinesAction(lines)));
+        }
+
+        actions
+    }
+}
@@ -0,0 +1,137 @@
+use crate::v4;
+use bytemuck::{Pod, Zeroable};
+use v4_core::ecs::{
+    actions::ActionQueue,
+    component::{Component, ComponentSystem, UpdateParams},
+};
+use v4_macros::component;
+use wgpu::{
+    Buffer, BufferUsages, Device, Queue, RenderBundleEncoder, RenderPass, VertexAttribute,
+    VertexBufferLayout,
+    util::{BufferInitDescriptor, DeviceExt},
+};
+
+/// Per-object values for entities that share a material, bound as an instance vertex buffer next
+/// to the transform's. Add `InstanceDataComponent::vertex_layout` to the pipeline's vertex layouts
+/// after the transform layout and read the color and UV offset from those locations.
+#[component]
+pub struct InstanceDataComponent {
+    #[default([1.0, 1.0, 1.0, 1.0])]
+    color: [f32; 4],
+    #[default]
+    uv_offset: [f32; 2],
+    /// The vertex buffer slot, the mesh uses 0 and the transform 1
+    #[default(2)]
+    buffer_slot: u32,
+    #[default]
+    buffer: Option<Buffer>,
+    #[default]
+    is_dirty: bool,
+}
+
+impl InstanceDataComponent {
+    pub fn vertex_layout<const VERTEX_ATTRIBUTE_COUNT: u32>() -> VertexBufferLayout<'static> {
+        let attributes: &'static [VertexAttribute] = &[
+            (wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x4,
+                offset: 0,
+                shader_location: (VERTEX_ATTRIBUTE_COUNT),
+            }),
+            (wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x2,
+                offset: wgpu::VertexFormat::Float32x4.size(),
+                shader_location: (VERTEX_ATTRIBUTE_COUNT + 1),
+            }),
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<RawInstanceData>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes,
+        }
+    }
+
+    pub fn color(&self) -> [f32; 4] {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: [f32; 4]) {
+        self.color = color;
+        self.is_dirty = true;
+    }
+
+    pub fn uv_offset(&self) -> [f32; 2] {
+        self.uv_offset
+    }
+
+    pub fn set_uv_offset(&mut self, uv_offset: [f32; 2]) {
+        self.uv_offset = uv_offset;
+        self.is_dirty = true;
+    }
+
+    fn raw_data(&self) -> RawInstanceData {
+        RawInstanceData {
+            color: self.color,
+            uv_offset: self.uv_offset,
+        }
+    }
+}
+
+impl ComponentSystem for InstanceDataComponent {
+    fn initialize(&mut self, device: &Device) -> ActionQueue {
+        self.buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+            label: Some(&format!("Instance Data Component {} Buffer", self.id)),
+            contents: bytemuck::cast_slice(&[self.raw_data()]),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        }));
+        self.is_initialized = true;
+
+        Vec::new()
+    }
+
+    fn update(&mut self, UpdateParams { queue, .. }: UpdateParams<'_, '_>) -> ActionQueue {
+        if self.is_dirty
+            && let Some(buffer) = &self.buffer
+        {
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[self.raw_data()]));
+            self.is_dirty = false;
+        }
+        Vec::new()
+    }
+
+    fn render(
+        &self,
+        _device: &Device,
+        _queue: &Queue,
+        render_pass: &mut RenderPass,
+        _other_components: &[&Component],
+    ) {
+        if let Some(buffer) = &self.buffer {
+            render_pass.set_vertex_buffer(self.buffer_slot, buffer.slice(..));
+        }
+    }
+
+    fn render_bundle<'a>(
+        &'a self,
+        _device: &Device,
+        _queue: &Queue,
+        bundle_encoder: &mut RenderBundleEncoder<'a>,
+        _other_components: &[&'a Component],
+    ) -> bool {
+        if let Some(buffer) = &self.buffer {
+            bundle_encoder.set_vertex_buffer(self.buffer_slot, buffer.slice(..));
+        }
+        true
+    }
+
+    fn release_gpu_resources(&mut self) {
+        self.buffer = None;
+        self.is_initialized = false;
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Pod, Zeroable, Clone, Copy)]
+pub struct RawInstanceData {
+    color: [f32; 4],
+    uv_offset: [f32; 2],
+}
@@ -3,13 +3,19 @@ use std::{fmt::Debug, ops::Range};
 use crate::v4;
 use bytemuck::{Pod, Zeroable};
 use nalgebra::Vector3;
-use v4_core::ecs::component::{Component, ComponentDetails, ComponentSystem};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use v4_core::ecs::{
+    component::{Component, ComponentDetails, ComponentId, ComponentSystem, next_component_id},
+    scene_descriptor::SerializableComponent,
+};
 use v4_macros::component;
 use wgpu::{
-    Buffer, Device, Queue, RenderPass, VertexAttribute,
+    Buffer, BufferSlice, Device, Queue, RenderBundleEncoder, RenderPass, VertexAttribute,
     util::{BufferInitDescriptor, DeviceExt},
 };
 
+use super::transform_component::RawTransformData;
+
 #[derive(Debug, Clone, Copy)]
 pub struct VertexData {
     pub pos: [f32; 3],
@@ -34,21 +40,35 @@ pub trait VertexDescriptor: Debug + Pod + Zeroable {
     }
 
     fn from_data(data: VertexData) -> Self;
+
+    /// Used by debug drawing, vertices without a position are skipped
+    fn position(&self) -> Option<[f32; 3]> {
+        None
+    }
+
+    fn normal(&self) -> Option<[f32; 3]> {
+        None
+    }
 }
 
 /// When specifying `enabled_models`, it is possible to specify the vertex range in the vertex buffer
 /// from which to draw. The number of elements in `enabled_models` dictates the number of models
-/// and consequently the number of draw calls
+/// and consequently the number of draw calls.
+///
+/// With `instances`, every model is drawn once per instance transform in a single draw call. The
+/// transforms are bound at vertex buffer slot 1 in the layout of `TransformComponent::vertex_layout`
+/// and replace the transform of a sibling `TransformComponent`.
 #[component(rendering_order = 500)]
 pub struct MeshComponent<V: VertexDescriptor> {
+    #[required]
     vertices: Vec<Vec<V>>,
-    #[default]
     indices: Vec<Vec<u32>>,
-    #[default]
     vertex_buffers: Option<Vec<Buffer>>,
-    #[default]
     index_buffers: Option<Vec<Buffer>>,
+    #[required]
     enabled_models: Vec<(usize, Option<Range<u64>>)>,
+    instances: Vec<RawTransformData>,
+    instance_buffer: Option<Buffer>,
 }
 
 impl<V: VertexDescriptor> MeshComponent<V> {
@@ -115,15 +135,13 @@ impl<V: VertexDescriptor> MeshComponent<V> {
             vertex_buffers: None,
             index_buffers: None,
             enabled_models: (0..model_count).map(|i| (i, None)).collect(),
-            id: {
-                use std::hash::{Hash, Hasher};
-                let mut hasher = std::hash::DefaultHasher::new();
-                std::time::Instant::now().hash(&mut hasher);
-                hasher.finish()
-            },
+            instances: Vec::new(),
+            instance_buffer: None,
+            id: next_component_id(),
             parent_entity_id: 0,
             is_initialized: false,
             is_enabled,
+            rendering_order: Self::DEFAULT_RENDERING_ORDER,
         })
     }
 
@@ -208,6 +226,87 @@ impl<V: VertexDescriptor> MeshComponent<V> {
         }
     }
 
+    /// Replaces the instance transforms, an empty list draws the models once without instancing
+    pub fn set_instances(
+        &mut self,
+        instances: Vec<RawTransformData>,
+        device: &Device,
+        queue: &Queue,
+    ) {
+        self.instances = instances;
+        if self.instances.is_empty() {
+            return;
+        }
+        let contents = bytemuck::cast_slice(&self.instances);
+        let label = format!("Component {} | Instance Buffer", self.id);
+        if let Some(buffer) = &mut self.instance_buffer {
+            v4::engine_support::misc_utils::update_buffer(
+                buffer,
+                contents,
+                device,
+                queue,
+                Some(&label),
+            );
+        } else if self.is_initialized {
+            self.instance_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+                label: Some(&label),
+                contents,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            }));
+        }
+    }
+
+    pub fn instances(&self) -> &[RawTransformData] {
+        &self.instances
+    }
+
+    /// The instance buffer and instance range to draw, the instance buffer is `None` when the mesh
+    /// isn't instanced
+    fn instance_draw(&self) -> (Option<BufferSlice<'_>>, Range<u32>) {
+        match &self.instance_buffer {
+            Some(buffer) if !self.instances.is_empty() => {
+                (Some(buffer.slice(..)), 0..self.instances.len() as u32)
+            }
+            _ => (None, 0..1),
+        }
+    }
+
+    /// The vertex slice, index slice and draw range of every enabled model
+    fn model_draws(&self) -> Vec<(BufferSlice<'_>, Option<BufferSlice<'_>>, Range<u32>)> {
+        let vertex_buffers = self
+            .vertex_buffers
+            .as_ref()
+            .expect("Attempted to render an uninitialized mesh.");
+
+        self.enabled_models
+            .iter()
+            .map(|(index, range_opt)| {
+                let vertex_slice = if let Some(range) = range_opt {
+                    let byte_range =
+                        (range.start * size_of::<V>() as u64)..(range.end * size_of::<V>() as u64);
+                    vertex_buffers[*index].slice(byte_range)
+                } else {
+                    vertex_buffers[*index].slice(..)
+                };
+
+                if let Some(index_buffers) = &self.index_buffers {
+                    (
+                        vertex_slice,
+                        Some(index_buffers[*index].slice(..)),
+                        0..(self.indices[*index].len() as u32),
+                    )
+                } else {
+                    let draw_range = if let Some(range) = range_opt {
+                        0..(range.end - range.start) as u32
+                    } else {
+                        0..self.vertices[*index].len() as u32
+                    };
+                    (vertex_slice, None, draw_range)
+                }
+            })
+            .collect()
+    }
+
     pub fn vertex_buffers(&self) -> Option<&Vec<Buffer>> {
         self.vertex_buffers.as_ref()
     }
@@ -231,6 +330,22 @@ impl<V: VertexDescriptor> MeshComponent<V> {
     pub fn indices(&self) -> &[Vec<u32>] {
         &self.indices
     }
+
+    /// The local-space minimum and maximum corners of the enabled models, or `None` if the vertex
+    /// type does not expose positions
+    pub fn aabb(&self) -> Option<([f32; 3], [f32; 3])> {
+        self.enabled_models
+            .iter()
+            .flat_map(|(index, _)| &self.vertices[*index])
+            .filter_map(|vertex| vertex.position())
+            .fold(None, |bounds, position| {
+                let (min, max) = bounds.unwrap_or((position, position));
+                Some((
+                    std::array::from_fn(|i| min[i].min(position[i])),
+                    std::array::from_fn(|i| max[i].max(position[i])),
+                ))
+            })
+    }
 }
 
 impl<V: VertexDescriptor + Send + Sync> ComponentSystem for MeshComponent<V> {
@@ -247,6 +362,13 @@ impl<V: VertexDescriptor + Send + Sync> ComponentSystem for MeshComponent<V> {
                 })
                 .collect(),
         );
+        if !self.instances.is_empty() {
+            self.instance_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+                label: Some(&format!("Component {} | Instance Buffer", self.id())),
+                contents: bytemuck::cast_slice(&self.instances),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            }));
+        }
         if !self.indices.is_empty() {
             self.index_buffers = Some(
                 self.enabled_models
@@ -273,38 +395,86 @@ impl<V: VertexDescriptor + Send + Sync> ComponentSystem for MeshComponent<V> {
         render_pass: &mut RenderPass,
         _other_components: &[&Component],
     ) {
-        for (index, range_opt) in &self.enabled_models {
-            render_pass.set_vertex_buffer(
-                0,
-                if let Some(range) = range_opt {
-                    let byte_range =
-                        (range.start * size_of::<V>() as u64)..(range.end * size_of::<V>() as u64);
-                    self.vertex_buffers
-                        .as_ref()
-                        .expect("Attempted to render an uninitialized mesh.")[*index]
-                        .slice(byte_range)
-                } else {
-                    self.vertex_buffers
-                        .as_ref()
-                        .expect("Attempted to render an uninitialized mesh.")[*index]
-                        .slice(..)
-                },
-            );
-            if let Some(index_buffers) = &self.index_buffers {
-                render_pass
-                    .set_index_buffer(index_buffers[*index].slice(..), wgpu::IndexFormat::Uint32);
-                render_pass.draw_indexed(0..(self.indices[*index].len() as u32), 0, 0..1);
+        let (instance_slice, instance_range) = self.instance_draw();
+        if let Some(instance_slice) = instance_slice {
+            render_pass.set_vertex_buffer(1, instance_slice);
+        }
+        for (vertex_slice, index_slice, draw_range) in self.model_draws() {
+            render_pass.set_vertex_buffer(0, vertex_slice);
+            if let Some(index_slice) = index_slice {
+                render_pass.set_index_buffer(index_slice, wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(draw_range, 0, instance_range.clone());
             } else {
-                render_pass.draw(
-                    if let Some(range) = range_opt {
-                        0..(range.end - range.start) as u32
-                    } else {
-                        0..self.vertices[*index].len() as u32
-                    },
-                    0..1,
-                );
+                render_pass.draw(draw_range, instance_range.clone());
+            }
+        }
+    }
+
+    fn render_bundle<'a>(
+        &'a self,
+        _device: &Device,
+        _queue: &Queue,
+        bundle_encoder: &mut RenderBundleEncoder<'a>,
+        _other_components: &[&'a Component],
+    ) -> bool {
+        let (instance_slice, instance_range) = self.instance_draw();
+        if let Some(instance_slice) = instance_slice {
+            bundle_encoder.set_vertex_buffer(1, instance_slice);
+        }
+        for (vertex_slice, index_slice, draw_range) in self.model_draws() {
+            bundle_encoder.set_vertex_buffer(0, vertex_slice);
+            if let Some(index_slice) = index_slice {
+                bundle_encoder.set_index_buffer(index_slice, wgpu::IndexFormat::Uint32);
+                bundle_encoder.draw_indexed(draw_range, 0, instance_range.clone());
+            } else {
+                bundle_encoder.draw(draw_range, instance_range.clone());
             }
         }
+        true
+    }
+
+    fn release_gpu_resources(&mut self) {
+        self.vertex_buffers = None;
+        self.index_buffers = None;
+        self.instance_buffer = None;
+        self.is_initialized = false;
+    }
+}
+
+/// A mesh as saved in a scene descriptor, with all of its vertex data. Register
+/// `MeshComponent<V>` once for every vertex type used in the scene.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeshParameters<V> {
+    pub vertices: Vec<Vec<V>>,
+    pub indices: Vec<Vec<u32>>,
+    pub enabled_models: Vec<(usize, Option<Range<u64>>)>,
+    /// The instance transforms as column-major matrices
+    pub instances: Vec<[[f32; 4]; 4]>,
+}
+
+impl<V> SerializableComponent for MeshComponent<V>
+where
+    V: VertexDescriptor + Send + Sync + Serialize + DeserializeOwned,
+{
+    type Parameters = MeshParameters<V>;
+
+    fn to_parameters(&self) -> MeshParameters<V> {
+        MeshParameters {
+            vertices: self.vertices.clone(),
+            indices: self.indices.clone(),
+            enabled_models: self.enabled_models.clone(),
+            instances: bytemuck::cast_slice(&self.instances).to_vec(),
+        }
+    }
+
+    fn from_parameters(parameters: MeshParameters<V>, id: ComponentId) -> Self {
+        MeshComponent::builder()
+            .vertices(parameters.vertices)
+            .indices(parameters.indices)
+            .enabled_models(parameters.enabled_models)
+            .instances(bytemuck::cast_slice(&parameters.instances).to_vec())
+            .id(id)
+            .build()
     }
 }
 
@@ -0,0 +1,295 @@
+use std::borrow::Cow;
+
+use crate::{builtin_actions::SetUiBoundsAction, v4};
+use bytemuck::{Pod, Zeroable};
+use v4_core::{
+    ecs::{
+        actions::ActionQueue,
+        component::{Component, ComponentSystem, UpdateParams},
+        ui_hit_testing::{UiHitState, UiRect},
+    },
+    engine_management::pipeline::{GeometryDetails, PipelineId, PipelineShader},
+};
+use v4_macros::component;
+use wgpu::{
+    Buffer, BufferUsages, Device, Queue, RenderBundleEncoder, RenderPass, VertexBufferLayout,
+    util::{BufferInitDescriptor, DeviceExt},
+};
+
+/// Two triangles for each of the 9 cells of the 4 by 4 vertex grid
+const NINE_SLICE_INDICES: [u32; 54] = {
+    let mut indices = [0; 54];
+    let mut cell = 0;
+    while cell < 9 {
+        let top_left = (cell / 3) * 4 + cell % 3;
+        let quad = [
+            top_left,
+            top_left + 4,
+            top_left + 5,
+            top_left,
+            top_left + 5,
+            top_left + 1,
+        ];
+        let mut i = 0;
+        while i < 6 {
+            indices[cell as usize * 6 + i] = quad[i];
+            i += 1;
+        }
+        cell += 1;
+    }
+    indices
+};
+
+/// A UI panel drawn over the scene with the pipeline from `NineSliceComponent::pipeline_id`,
+/// sampling the texture at binding 0 of its material. The corners keep their size in pixels while
+/// the edges and center stretch, and the geometry is rebuilt when the panel or window is resized.
+/// The panel registers its bounds with the scene, so it can act as a button through `is_hovered`
+/// and `was_clicked`.
+#[component]
+pub struct NineSliceComponent {
+    /// Top left corner in pixels
+    #[default]
+    position: [f32; 2],
+    /// Width and height in pixels
+    size: [f32; 2],
+    /// Left, right, top and bottom insets in pixels on screen
+    border: [f32; 4],
+    /// Left, right, top and bottom insets in UV space on the texture
+    uv_border: [f32; 4],
+    #[default]
+    vertex_buffer: Option<Buffer>,
+    #[default]
+    index_buffer: Option<Buffer>,
+    /// The window resolution the geometry was built for
+    #[default]
+    geometry_resolution: (u32, u32),
+    #[default(true)]
+    is_dirty: bool,
+    #[default]
+    registered_bounds: Option<UiRect>,
+    #[default]
+    hit_state: UiHitState,
+}
+
+impl NineSliceComponent {
+    pub fn pipeline_id() -> PipelineId {
+        const ATTRIBUTES: &[wgpu::VertexAttribute] =
+            &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2];
+        let shader = PipelineShader::Raw(Cow::Borrowed(include_str!("shaders/nine_slice.wgsl")));
+        PipelineId {
+            vertex_shader: shader.clone(),
+            spirv_vertex_shader: false,
+            fragment_shader: shader,
+            spirv_fragment_shader: false,
+            vertex_entry: "vs_main",
+            fragment_entry: "fs_main",
+            vertex_layouts: vec![VertexBufferLayout {
+                array_stride: std::mem::size_of::<NineSliceVertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: ATTRIBUTES,
+            }],
+            uses_camera: false,
+            is_screen_space: false,
+            geometry_details: GeometryDetails {
+                depth_test: false,
+                ..Default::default()
+            },
+            immediate_size: 0,
+            render_priority: 0,
+            blend_state: PipelineId::DEFAULT_BLEND_STATE,
+            writes_gbuffer: false,
+        }
+    }
+
+    /// The 4 by 4 vertex grid, row by row from the top, in clip space for a window of
+    /// `resolution`. Insets that add up to more than the panel are scaled down so opposite edges meet.
+    pub fn vertices(&self, resolution: (u32, u32)) -> [NineSliceVertex; 16] {
+        let [left, right, top, bottom] = self.border;
+        let horizontal_scale = (self.size[0] / (left + right)).min(1.0);
+        let vertical_scale = (self.size[1] / (top + bottom)).min(1.0);
+
+        let xs = [
+            0.0,
+            left * horizontal_scale,
+            self.size[0] - right * horizontal_scale,
+            self.size[0],
+        ];
+        let ys = [
+            0.0,
+            top * vertical_scale,
+            self.size[1] - bottom * vertical_scale,
+            self.size[1],
+        ];
+        let [uv_left, uv_right, uv_top, uv_bottom] = self.uv_border;
+        let us = [0.0, uv_left, 1.0 - uv_right, 1.0];
+        let vs = [0.0, uv_top, 1.0 - uv_bottom, 1.0];
+
+        let (width, height) = (resolution.0.max(1) as f32, resolution.1.max(1) as f32);
+        std::array::from_fn(|i| {
+            let (row, column) = (i / 4, i % 4);
+            NineSliceVertex {
+                position: [
+                    (self.position[0] + xs[column]) / width * 2.0 - 1.0,
+                    1.0 - (self.position[1] + ys[row]) / height * 2.0,
+                ],
+                tex_coords: [us[column], vs[row]],
+            }
+        })
+    }
+
+    pub fn position(&self) -> [f32; 2] {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: [f32; 2]) {
+        self.position = position;
+        self.is_dirty = true;
+    }
+
+    pub fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: [f32; 2]) {
+        self.size = size;
+        self.is_dirty = true;
+    }
+
+    pub fn border(&self) -> [f32; 4] {
+        self.border
+    }
+
+    pub fn set_border(&mut self, border: [f32; 4]) {
+        self.border = border;
+        self.is_dirty = true;
+    }
+
+    pub fn bounds(&self) -> UiRect {
+        UiRect {
+            x: self.position[0],
+            y: self.position[1],
+            width: self.size[0],
+            height: self.size[1],
+        }
+    }
+
+    pub fn is_hovered(&self) -> bool {
+        self.hit_state.is_hovered
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.hit_state.is_pressed
+    }
+
+    pub fn was_clicked(&self) -> bool {
+        self.hit_state.was_clicked
+    }
+
+    pub fn uv_border(&self) -> [f32; 4] {
+        self.uv_border
+    }
+
+    pub fn set_uv_border(&mut self, uv_border: [f32; 4]) {
+        self.uv_border = uv_border;
+        self.is_dirty = true;
+    }
+}
+
+impl ComponentSystem for NineSliceComponent {
+    fn initialize(&mut self, device: &Device) -> ActionQueue {
+        // The vertices are written in `update`, once the window resolution is known
+        self.vertex_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+            label: Some(&format!("Nine Slice Component {} Vertex Buffer", self.id)),
+            contents: bytemuck::cast_slice(&self.vertices(self.geometry_resolution)),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        }));
+        self.index_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+            label: Some(&format!("Nine Slice Component {} Index Buffer", self.id)),
+            contents: bytemuck::cast_slice(&NINE_SLICE_INDICES),
+            usage: BufferUsages::INDEX,
+        }));
+        self.is_dirty = true;
+        self.is_initialized = true;
+
+        Vec::new()
+    }
+
+    fn update(
+        &mut self,
+        UpdateParams {
+            queue,
+            engine_details,
+            ui_hit_states,
+            ..
+        }: UpdateParams<'_, '_>,
+    ) -> ActionQueue {
+        self.hit_state = ui_hit_states.get(&self.id).copied().unwrap_or_default();
+
+        let mut actions: ActionQueue = Vec::new();
+        let bounds = self.bounds();
+        if self.registered_bounds != Some(bounds) {
+            self.registered_bounds = Some(bounds);
+            actions.push(Box::new(SetUiBoundsAction(self.id, Some(bounds))));
+        }
+
+        let resolution = engine_details.window_resolution;
+        if self.is_dirty || resolution != self.geometry_resolution {
+            if let Some(vertex_buffer) = &self.vertex_buffer {
+                queue.write_buffer(
+                    vertex_buffer,
+                    0,
+                    bytemuck::cast_slice(&self.vertices(resolution)),
+                );
+            }
+            self.geometry_resolution = resolution;
+            self.is_dirty = false;
+        }
+
+        actions
+    }
+
+    fn render(
+        &self,
+        _device: &Device,
+        _queue: &Queue,
+        render_pass: &mut RenderPass,
+        _other_components: &[&Component],
+    ) {
+        if let (Some(vertex_buffer), Some(index_buffer)) = (&self.vertex_buffer, &self.index_buffer)
+        {
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..NINE_SLICE_INDICES.len() as u32, 0, 0..1);
+        }
+    }
+
+    fn render_bundle<'a>(
+        &'a self,
+        _device: &Device,
+        _queue: &Queue,
+        bundle_encoder: &mut RenderBundleEncoder<'a>,
+        _other_components: &[&'a Component],
+    ) -> bool {
+        if let (Some(vertex_buffer), Some(index_buffer)) = (&self.vertex_buffer, &self.index_buffer)
+        {
+            bundle_encoder.set_vertex_buffer(0, vertex_buffer.slice(..));
+            bundle_encoder.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            bundle_encoder.draw_indexed(0..NINE_SLICE_INDICES.len() as u32, 0, 0..1);
+        }
+        true
+    }
+
+    fn release_gpu_resources(&mut self) {
+        self.vertex_buffer = None;
+        self.index_buffer = None;
+        self.is_dirty = true;
+        self.is_initialized = false;
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Pod, Zeroable, Clone, Copy, PartialEq)]
+pub struct NineSliceVertex {
+    pub position: [f32; 2],
+    pub tex_coords: [f32; 2],
+}
@@ -0,0 +1,44 @@
+struct VertexInput {
+    @location(0) corner: vec2<f32>,
+}
+
+struct TransformData {
+    @location(1) mat_0: vec4<f32>,
+    @location(2) mat_1: vec4<f32>,
+    @location(3) mat_2: vec4<f32>,
+    @location(4) mat_3: vec4<f32>,
+}
+
+struct SpriteData {
+    @location(5) size: vec2<f32>,
+    @location(6) pivot: vec2<f32>,
+    @location(7) uv_rect: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+@group(0) @binding(0) var sprite_texture: texture_2d<f32>;
+@group(0) @binding(1) var sprite_sampler: sampler;
+
+@vertex
+fn vs_main(input: VertexInput, transform: TransformData, sprite: SpriteData) -> VertexOutput {
+    let mat = mat4x4<f32>(
+        transform.mat_0,
+        transform.mat_1,
+        transform.mat_2,
+        transform.mat_3,
+    );
+
+    var out: VertexOutput;
+    out.pos = mat * vec4f((input.corner - sprite.pivot) * sprite.size, 0.0, 1.0);
+    out.tex_coords = sprite.uv_rect.xy + vec2f(input.corner.x, 1.0 - input.corner.y) * sprite.uv_rect.zw;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(sprite_texture, sprite_sampler, in.tex_coords);
+}
@@ -0,0 +1,220 @@
+use std::borrow::Cow;
+
+use crate::v4;
+use bytemuck::{Pod, Zeroable};
+use v4_core::{
+    ecs::{
+        actions::ActionQueue,
+        component::{Component, ComponentSystem, UpdateParams},
+    },
+    engine_management::pipeline::{PipelineId, PipelineShader},
+};
+use v4_macros::component;
+use wgpu::{
+    Buffer, BufferUsages, Device, Queue, RenderBundleEncoder, RenderPass, VertexAttribute,
+    VertexBufferLayout,
+    util::{BufferInitDescriptor, DeviceExt},
+};
+
+use super::transform_component::TransformComponent;
+
+/// Two triangles covering the unit square, the sprite's size and pivot are applied in the shader
+const QUAD_CORNERS: [[f32; 2]; 6] = [
+    [0.0, 0.0],
+    [1.0, 0.0],
+    [1.0, 1.0],
+    [0.0, 0.0],
+    [1.0, 1.0],
+    [0.0, 1.0],
+];
+
+/// A textured quad drawn with the pipeline from `SpriteComponent::pipeline_id`, sampling the
+/// texture at binding 0 of its material. It is placed by the sibling `TransformComponent`, which
+/// has to come before the sprite in the entity's components.
+#[component]
+pub struct SpriteComponent {
+    #[default([1.0, 1.0])]
+    size: [f32; 2],
+    /// The point the sprite is placed and rotated around, (0, 0) is the bottom left corner and
+    /// (1, 1) the top right
+    #[default([0.5, 0.5])]
+    pivot: [f32; 2],
+    /// The part of the texture that is shown as `[x, y, width, height]` in UV space, e.g. one cell
+    /// of an atlas from `SpriteComponent::atlas_cell`
+    #[default([0.0, 0.0, 1.0, 1.0])]
+    uv_rect: [f32; 4],
+    #[default]
+    quad_buffer: Option<Buffer>,
+    #[default]
+    sprite_buffer: Option<Buffer>,
+    #[default]
+    is_dirty: bool,
+}
+
+impl SpriteComponent {
+    /// The sprite pipeline. With `uses_camera` the sprite is placed in the world, otherwise its
+    /// transform maps it straight to clip space, e.g. for UI.
+    pub fn pipeline_id(uses_camera: bool) -> PipelineId {
+        let shader = PipelineShader::Raw(Cow::Borrowed(if uses_camera {
+            include_str!("shaders/sprite_world.wgsl")
+        } else {
+            include_str!("shaders/sprite.wgsl")
+        }));
+        PipelineId {
+            vertex_shader: shader.clone(),
+            spirv_vertex_shader: false,
+            fragment_shader: shader,
+            spirv_fragment_shader: false,
+            vertex_entry: "vs_main",
+            fragment_entry: "fs_main",
+            vertex_layouts: Self::vertex_layouts(),
+            uses_camera,
+            is_screen_space: false,
+            geometry_details: Default::default(),
+            immediate_size: 0,
+            render_priority: 0,
+            blend_state: PipelineId::DEFAULT_BLEND_STATE,
+            writes_gbuffer: false,
+        }
+    }
+
+    /// The quad corners at slot 0, the transform at slot 1 and the sprite's size, pivot and UV
+    /// rect at slot 2
+    pub fn vertex_layouts() -> Vec<VertexBufferLayout<'static>> {
+        const QUAD_ATTRIBUTES: &[VertexAttribute] = &wgpu::vertex_attr_array![0 => Float32x2];
+        const SPRITE_ATTRIBUTES: &[VertexAttribute] =
+            &wgpu::vertex_attr_array![5 => Float32x2, 6 => Float32x2, 7 => Float32x4];
+        vec![
+            VertexBufferLayout {
+                array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: QUAD_ATTRIBUTES,
+            },
+            TransformComponent::vertex_layout::<1>(),
+            VertexBufferLayout {
+                array_stride: std::mem::size_of::<RawSpriteData>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: SPRITE_ATTRIBUTES,
+            },
+        ]
+    }
+
+    /// The UV rect of cell `index` of an atlas split into `columns` by `rows` equal cells,
+    /// counted left to right and then top to bottom
+    pub fn atlas_cell(columns: u32, rows: u32, index: u32) -> [f32; 4] {
+        let (width, height) = (1.0 / columns as f32, 1.0 / rows as f32);
+        [
+            (index % columns) as f32 * width,
+            (index / columns) as f32 * height,
+            width,
+            height,
+        ]
+    }
+
+    pub fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: [f32; 2]) {
+        self.size = size;
+        self.is_dirty = true;
+    }
+
+    pub fn pivot(&self) -> [f32; 2] {
+        self.pivot
+    }
+
+    pub fn set_pivot(&mut self, pivot: [f32; 2]) {
+        self.pivot = pivot;
+        self.is_dirty = true;
+    }
+
+    pub fn uv_rect(&self) -> [f32; 4] {
+        self.uv_rect
+    }
+
+    pub fn set_uv_rect(&mut self, uv_rect: [f32; 4]) {
+        self.uv_rect = uv_rect;
+        self.is_dirty = true;
+    }
+
+    fn raw_data(&self) -> RawSpriteData {
+        RawSpriteData {
+            size: self.size,
+            pivot: self.pivot,
+            uv_rect: self.uv_rect,
+        }
+    }
+}
+
+impl ComponentSystem for SpriteComponent {
+    fn initialize(&mut self, device: &Device) -> ActionQueue {
+        self.quad_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+            label: Some(&format!("Sprite Component {} Quad Buffer", self.id)),
+            contents: bytemuck::cast_slice(&QUAD_CORNERS),
+            usage: BufferUsages::VERTEX,
+        }));
+        self.sprite_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+            label: Some(&format!("Sprite Component {} Buffer", self.id)),
+            contents: bytemuck::cast_slice(&[self.raw_data()]),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        }));
+        self.is_dirty = false;
+        self.is_initialized = true;
+
+        Vec::new()
+    }
+
+    fn update(&mut self, UpdateParams { queue, .. }: UpdateParams<'_, '_>) -> ActionQueue {
+        if self.is_dirty
+            && let Some(buffer) = &self.sprite_buffer
+        {
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[self.raw_data()]));
+            self.is_dirty = false;
+        }
+        Vec::new()
+    }
+
+    fn render(
+        &self,
+        _device: &Device,
+        _queue: &Queue,
+        render_pass: &mut RenderPass,
+        _other_components: &[&Component],
+    ) {
+        if let (Some(quad_buffer), Some(sprite_buffer)) = (&self.quad_buffer, &self.sprite_buffer) {
+            render_pass.set_vertex_buffer(0, quad_buffer.slice(..));
+            render_pass.set_vertex_buffer(2, sprite_buffer.slice(..));
+            render_pass.draw(0..QUAD_CORNERS.len() as u32, 0..1);
+        }
+    }
+
+    fn render_bundle<'a>(
+        &'a self,
+        _device: &Device,
+        _queue: &Queue,
+        bundle_encoder: &mut RenderBundleEncoder<'a>,
+        _other_components: &[&'a Component],
+    ) -> bool {
+        if let (Some(quad_buffer), Some(sprite_buffer)) = (&self.quad_buffer, &self.sprite_buffer) {
+            bundle_encoder.set_vertex_buffer(0, quad_buffer.slice(..));
+            bundle_encoder.set_vertex_buffer(2, sprite_buffer.slice(..));
+            bundle_encoder.draw(0..QUAD_CORNERS.len() as u32, 0..1);
+        }
+        true
+    }
+
+    fn release_gpu_resources(&mut self) {
+        self.quad_buffer = None;
+        self.sprite_buffer = None;
+        self.is_initialized = false;
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Pod, Zeroable, Clone, Copy)]
+pub struct RawSpriteData {
+    size: [f32; 2],
+    pivot: [f32; 2],
+    uv_rect: [f32; 4],
+}
@@ -1,12 +1,26 @@
-use crate::v4;
-use algoe::rotor::Rotor3;
+use crate::{EngineDetails, v4};
+use algoe::{rotor::Rotor3, vector::GeometricOperations};
 use bytemuck::{cast_slice, Pod, Zeroable};
 use nalgebra::{Matrix3, Matrix4, Translation3, Vector3};
-use v4_core::ecs::component::{Component, ComponentSystem};
+use serde::{Deserialize, Serialize};
+use v4_core::ecs::{
+    actions::ActionQueue,
+    component::{Component, ComponentId, ComponentSystem, UpdateParams},
+    dense_storage::DenseComponent,
+    entity::{Entity, EntityId},
+    scene_descriptor::SerializableComponent,
+};
 use v4_macros::component;
 use wgpu::{util::DeviceExt, BufferUsages, VertexAttribute, VertexBufferLayout};
 
-#[component]
+/// The transform of an entity relative to its parent entity. Child entities are placed by the
+/// transforms of all their ancestors, see `TransformComponent::world_matrix`.
+///
+/// A transform can instead be stored in the scene's dense storage with
+/// `Scene::attach_dense_component`, for scenes with many transforms. Dense transforms are updated
+/// in a single typed pass and bound by the materials drawing their entity, but are not placed by
+/// their ancestors' transforms.
+#[component(clone)]
 pub struct TransformComponent {
     position: Vector3<f32>,
     #[default]
@@ -15,6 +29,21 @@ pub struct TransformComponent {
     scale: Vector3<f32>,
     #[default(true)]
     uses_buffer: bool,
+    /// `None` while the position, rotation or scale changed since the matrix was last computed
+    #[default]
+    cached_matrix: Option<Matrix4<f32>>,
+    /// The combined transforms of the entity's ancestors, found during `update`. `None` when no
+    /// ancestor has a transform.
+    #[default]
+    parent_matrix: Option<Matrix4<f32>>,
+    /// Holds the world matrix for the instance slot, created in `initialize` when `uses_buffer`
+    /// and rewritten by `update` only when the matrix changed, so render bundles can keep it
+    #[no_clone]
+    #[default]
+    instance_buffer: Option<wgpu::Buffer>,
+    #[no_clone]
+    #[default]
+    uploaded_matrix: Option<Matrix4<f32>>,
 }
 
 impl TransformComponent {
@@ -49,6 +78,113 @@ impl TransformComponent {
         }
     }
 
+    /// Returns the matrix cached during `update`, or computes it if the transform changed since
+    pub fn create_matrix(&self) -> Matrix4<f32> {
+        self.cached_matrix
+            .unwrap_or_else(|| self.transform_data().create_matrix())
+    }
+
+    /// The local matrix placed by the ancestors' transforms as of the last `update`
+    pub fn world_matrix(&self) -> Matrix4<f32> {
+        match self.parent_matrix {
+            Some(parent_matrix) => parent_matrix * self.create_matrix(),
+            None => self.create_matrix(),
+        }
+    }
+
+    pub fn parent_matrix(&self) -> Option<Matrix4<f32>> {
+        self.parent_matrix
+    }
+
+    pub fn is_matrix_cached(&self) -> bool {
+        self.cached_matrix.is_some()
+    }
+
+    /// The buffer bound to vertex slot 1 by `render` and `render_bundle`
+    pub fn instance_buffer(&self) -> Option<&wgpu::Buffer> {
+        self.instance_buffer.as_ref()
+    }
+
+    /// Copies out the hot transform data, e.g. to store it in a scene's dense storage
+    pub fn transform_data(&self) -> TransformData {
+        TransformData {
+            position: self.position,
+            rotation: self.rotation,
+            scale: self.scale,
+        }
+    }
+
+    pub fn set_position(&mut self, position: Vector3<f32>) {
+        self.position = position;
+        self.cached_matrix = None;
+    }
+
+    pub fn set_rotation(&mut self, rotation: Rotor3) {
+        self.rotation = rotation;
+        self.cached_matrix = None;
+    }
+
+    pub fn set_scale(&mut self, scale: Vector3<f32>) {
+        self.scale = scale;
+        self.cached_matrix = None;
+    }
+
+    /// Rotates the transform so its forward axis, +Z, points from its position toward `target`,
+    /// with its +Y axis as close to `up` as possible. Does nothing if `target` is the position.
+    pub fn look_at(&mut self, target: Vector3<f32>, up: Vector3<f32>) {
+        let Some(forward) = (target - self.position).try_normalize(f32::EPSILON) else {
+            return;
+        };
+        let forward_rotation = rotation_between(Vector3::z(), forward, Vector3::x());
+
+        // Rolls around the new forward axis, in the plane perpendicular to it
+        let current_up = forward_rotation * Vector3::y();
+        let rotation = match (up - forward * up.dot(&forward)).try_normalize(f32::EPSILON) {
+            Some(desired_up) => {
+                let right = forward_rotation * Vector3::x();
+                rotation_between(current_up, desired_up, right) * forward_rotation
+            }
+            None => forward_rotation,
+        };
+        self.set_rotation(rotation.normalize());
+    }
+
+    pub fn get_position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    pub fn get_rotation(&self) -> Rotor3 {
+        self.rotation
+    }
+
+    pub fn get_scale(&self) -> Vector3<f32> {
+        self.scale
+    }
+}
+
+/// The rotor turning the unit vector `from` onto the unit vector `to`. Opposite vectors are turned
+/// around in the plane of `from` and `fallback`, which must be perpendicular to `from`.
+fn rotation_between(from: Vector3<f32>, to: Vector3<f32>, fallback: Vector3<f32>) -> Rotor3 {
+    let angle = from.dot(&to).clamp(-1.0, 1.0).acos();
+    let sin = from.cross(&to).norm();
+    if sin > 1e-6 {
+        (from.wedge(&to) * (angle / sin / -2.0)).exponentiate()
+    } else if angle < std::f32::consts::FRAC_PI_2 {
+        Rotor3::default()
+    } else {
+        (from.wedge(&fallback) * (std::f32::consts::PI / -2.0)).exponentiate()
+    }
+}
+
+/// The per-frame hot part of a transform, without the component bookkeeping
+#[derive(Debug, Clone, Copy)]
+pub struct TransformData {
+    pub position: Vector3<f32>,
+    pub rotation: Rotor3,
+    pub scale: Vector3<f32>,
+}
+
+impl TransformData {
     pub fn create_matrix(&self) -> Matrix4<f32> {
         let rotation_matrix = Matrix3::from_columns(&[
             self.rotation * Vector3::x(),
@@ -68,50 +204,199 @@ impl TransformComponent {
 
         transformation_matrix * rotation_matrix * scale_matrix
     }
+}
 
-    pub fn set_position(&mut self, position: Vector3<f32>) {
-        self.position = position;
+impl TransformComponent {
+    fn create_instance_buffer(&mut self, device: &wgpu::Device) {
+        let matrix = self.world_matrix();
+        self.instance_buffer = Some(
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("Transform Component {} Buffer", self.id)),
+                contents: cast_slice(&[RawTransformData::new(matrix)]),
+                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            }),
+        );
+        self.uploaded_matrix = Some(matrix);
     }
 
-    pub fn set_rotation(&mut self, rotation: Rotor3) {
-        self.rotation = rotation;
+    /// Rewrites the instance buffer if the world matrix changed since it was last written
+    fn upload_matrix(&mut self, queue: &wgpu::Queue) {
+        if let Some(instance_buffer) = &self.instance_buffer {
+            let matrix = self.world_matrix();
+            if self.uploaded_matrix != Some(matrix) {
+                queue.write_buffer(
+                    instance_buffer,
+                    0,
+                    cast_slice(&[RawTransformData::new(matrix)]),
+                );
+                self.uploaded_matrix = Some(matrix);
+            }
+        }
     }
+}
 
-    pub fn set_scale(&mut self, scale: Vector3<f32>) {
-        self.scale = scale;
+impl DenseComponent for TransformComponent {
+    fn update_dense(
+        &mut self,
+        _entity_id: EntityId,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        _engine_details: &EngineDetails,
+    ) {
+        if self.cached_matrix.is_none() {
+            self.cached_matrix = Some(self.transform_data().create_matrix());
+        }
+        if self.uses_buffer && self.instance_buffer.is_none() {
+            self.create_instance_buffer(device);
+        }
+        self.upload_matrix(queue);
     }
 
-    pub fn get_position(&self) -> Vector3<f32> {
-        self.position
+    fn render(&self, render_pass: &mut wgpu::RenderPass) {
+        if let Some(instance_buffer) = &self.instance_buffer {
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+        }
     }
 
-    pub fn get_rotation(&self) -> Rotor3 {
-        self.rotation
+    fn render_bundle<'a>(&'a self, bundle_encoder: &mut wgpu::RenderBundleEncoder<'a>) {
+        if let Some(instance_buffer) = &self.instance_buffer {
+            bundle_encoder.set_vertex_buffer(1, instance_buffer.slice(..));
+        }
     }
 
-    pub fn get_scale(&self) -> Vector3<f32> {
-        self.scale
+    fn release_gpu_resources(&mut self) {
+        self.instance_buffer = None;
+        self.uploaded_matrix = None;
     }
 }
 
 impl ComponentSystem for TransformComponent {
+    fn initialize(&mut self, device: &wgpu::Device) -> ActionQueue {
+        if self.uses_buffer {
+            self.create_instance_buffer(device);
+        }
+        self.set_initialized();
+        Vec::new()
+    }
+
+    fn update(
+        &mut self,
+        UpdateParams {
+            queue,
+            other_components,
+            entities,
+            entity_component_groupings,
+            dense_storage,
+            ..
+        }: UpdateParams<'_, '_>,
+    ) -> ActionQueue {
+        if self.cached_matrix.is_none() {
+            self.cached_matrix = Some(self.transform_data().create_matrix());
+        }
+
+        // Walks up from the parent entity, top-level entities have a parent id of 0. Ancestors
+        // without a transform are skipped and a cycle in the hierarchy ends the walk.
+        self.parent_matrix = None;
+        let mut visited = vec![self.parent_entity_id];
+        let mut ancestor_id = entities
+            .get(&self.parent_entity_id)
+            .map_or(0, Entity::parent_entity_id);
+        while ancestor_id != 0 && !visited.contains(&ancestor_id) {
+            visited.push(ancestor_id);
+            let ancestor_range = entity_component_groupings
+                .get(&ancestor_id)
+                .cloned()
+                .unwrap_or_default();
+            if let Some(transform) = other_components[ancestor_range]
+                .iter()
+                .find_map(|comp| comp.downcast_ref::<TransformComponent>())
+                .or_else(|| dense_storage.get::<TransformComponent>(ancestor_id))
+            {
+                let matrix = transform.create_matrix();
+                self.parent_matrix = Some(match self.parent_matrix {
+                    Some(parent_matrix) => matrix * parent_matrix,
+                    None => matrix,
+                });
+            }
+            ancestor_id = entities
+                .get(&ancestor_id)
+                .map_or(0, Entity::parent_entity_id);
+        }
+
+        self.upload_matrix(queue);
+
+        Vec::new()
+    }
+
     fn render(
         &self,
-        device: &wgpu::Device,
+        _device: &wgpu::Device,
         _queue: &wgpu::Queue,
         render_pass: &mut wgpu::RenderPass,
         _other_components: &[&Component],
     ) {
-        if self.uses_buffer {
-            let raw_data = RawTransformData::from_component(self);
-            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some(&format!("Transform Component {} Buffer", self.id)),
-                contents: cast_slice(&[raw_data]),
-                usage: BufferUsages::VERTEX,
-            });
+        if let Some(instance_buffer) = &self.instance_buffer {
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+        }
+    }
 
-            render_pass.set_vertex_buffer(1, buffer.slice(..));
+    fn render_bundle<'a>(
+        &'a self,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+        bundle_encoder: &mut wgpu::RenderBundleEncoder<'a>,
+        _other_components: &[&'a Component],
+    ) -> bool {
+        if let Some(instance_buffer) = &self.instance_buffer {
+            bundle_encoder.set_vertex_buffer(1, instance_buffer.slice(..));
         }
+        true
+    }
+
+    fn release_gpu_resources(&mut self) {
+        self.instance_buffer = None;
+        self.uploaded_matrix = None;
+        self.set_uninitialized();
+    }
+}
+
+/// A transform as saved in a scene descriptor. The rotation is saved as the directions of the
+/// rotated +Z and +Y axes and restored with `TransformComponent::look_at`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TransformParameters {
+    pub position: [f32; 3],
+    pub forward: [f32; 3],
+    pub up: [f32; 3],
+    pub scale: [f32; 3],
+    pub uses_buffer: bool,
+}
+
+impl SerializableComponent for TransformComponent {
+    type Parameters = TransformParameters;
+
+    fn to_parameters(&self) -> TransformParameters {
+        TransformParameters {
+            position: self.position.into(),
+            forward: (self.rotation * Vector3::z()).into(),
+            up: (self.rotation * Vector3::y()).into(),
+            scale: self.scale.into(),
+            uses_buffer: self.uses_buffer,
+        }
+    }
+
+    fn from_parameters(parameters: TransformParameters, id: ComponentId) -> Self {
+        let position = Vector3::from(parameters.position);
+        let mut transform = TransformComponent::builder()
+            .position(position)
+            .scale(Vector3::from(parameters.scale))
+            .uses_buffer(parameters.uses_buffer)
+            .id(id)
+            .build();
+        transform.look_at(
+            position + Vector3::from(parameters.forward),
+            Vector3::from(parameters.up),
+        );
+        transform
     }
 }
 
@@ -122,9 +407,9 @@ pub struct RawTransformData {
 }
 
 impl RawTransformData {
-    fn from_component(comp: &TransformComponent) -> Self {
+    pub fn new(matrix: Matrix4<f32>) -> Self {
         RawTransformData {
-            matrix: comp.create_matrix().into(),
+            matrix: matrix.into(),
         }
     }
 }
@@ -32,5 +32,28 @@ pub mod builtin_components {
     pub mod mesh_component;
     pub mod transform_component;
     pub mod camera_component;
+    pub mod debug_draw_component;
+    pub mod instance_data_component;
+    pub mod sprite_component;
+    pub mod animated_sprite_component;
+    pub mod nine_slice_component;
+
+    use serde::{Serialize, de::DeserializeOwned};
+    use v4_core::ecs::scene_descriptor::ComponentRegistry;
+
+    use camera_component::CameraComponent;
+    use mesh_component::{MeshComponent, VertexDescriptor};
+    use transform_component::TransformComponent;
+
+    /// Registers the built-in components that can be saved in a scene descriptor, with
+    /// `MeshComponent`s holding vertices of type `V`
+    pub fn register_builtin_components<V>(registry: &mut ComponentRegistry)
+    where
+        V: VertexDescriptor + Send + Sync + Serialize + DeserializeOwned,
+    {
+        registry.register::<TransformComponent>("TransformComponent");
+        registry.register::<CameraComponent>("CameraComponent");
+        registry.register::<MeshComponent<V>>("MeshComponent");
+    }
 }
 
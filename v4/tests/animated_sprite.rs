@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use v4::builtin_components::animated_sprite_component::AnimatedSpriteComponent;
+
+const FRAME_COUNT: usize = 4;
+/// 4 frames per second, a duration that is exact in nanoseconds
+const FRAME_DURATION: Duration = Duration::from_millis(250);
+
+fn animation(looping: bool) -> AnimatedSpriteComponent {
+    AnimatedSpriteComponent::builder()
+        .frames(AnimatedSpriteComponent::strip_frames(FRAME_COUNT as u32))
+        .frames_per_second(4.0)
+        .looping(looping)
+        .build()
+}
+
+#[test]
+fn the_frame_follows_the_elapsed_time() {
+    let mut animation = animation(true);
+
+    assert!(!animation.advance(FRAME_DURATION - Duration::from_millis(1)));
+    assert_eq!(animation.current_frame(), 0);
+    assert!(animation.advance(Duration::from_millis(1)));
+    assert_eq!(animation.current_frame(), 1);
+
+    // Several frames can pass in a single step
+    assert!(animation.advance(FRAME_DURATION * 2 + Duration::from_millis(100)));
+    assert_eq!(animation.current_frame(), 3);
+}
+
+#[test]
+fn looping_animations_wrap_around() {
+    let mut animation = animation(true);
+
+    animation.advance(FRAME_DURATION * 3);
+    assert_eq!(animation.current_frame(), 3);
+    animation.advance(FRAME_DURATION);
+    assert_eq!(animation.current_frame(), 0);
+
+    // A whole cycle lands on the same frame and reports no change
+    animation.advance(FRAME_DURATION * 2);
+    assert!(!animation.advance(FRAME_DURATION * FRAME_COUNT as u32));
+    assert_eq!(animation.current_frame(), 2);
+    assert!(animation.is_playing());
+}
+
+#[test]
+fn small_steps_match_the_elapsed_time() {
+    let step = Duration::from_millis(16);
+    let mut animation = animation(true);
+
+    for steps in 1..=200u32 {
+        animation.advance(step);
+        let elapsed = step * steps;
+        let expected = (elapsed.as_millis() / FRAME_DURATION.as_millis()) as usize % FRAME_COUNT;
+        assert_eq!(animation.current_frame(), expected, "after {elapsed:?}");
+    }
+}
+
+#[test]
+fn finished_animations_stop_on_the_last_frame() {
+    let mut animation = animation(false);
+
+    animation.advance(FRAME_DURATION * 10);
+
+    assert_eq!(animation.current_frame(), FRAME_COUNT - 1);
+    assert!(!animation.is_playing());
+    assert!(!animation.advance(FRAME_DURATION));
+}
+
+#[test]
+fn paused_animations_keep_their_frame() {
+    let mut animation = animation(true);
+    animation.set_frame(2);
+    animation.pause();
+
+    assert!(!animation.advance(FRAME_DURATION * 3));
+    assert_eq!(animation.current_frame(), 2);
+
+    animation.play();
+    animation.advance(FRAME_DURATION);
+    assert_eq!(animation.current_frame(), 3);
+}
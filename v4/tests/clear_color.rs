@@ -0,0 +1,55 @@
+use v4::{V4, ecs::scene::Scene};
+
+const ENGINE_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.1,
+    g: 0.2,
+    b: 0.3,
+    a: 1.0,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn switching_scenes_switches_the_clear_color() {
+    let mut red_scene = Scene::default();
+    red_scene.set_clear_color(Some(wgpu::Color::RED));
+    let mut blue_scene = Scene::default();
+    blue_scene.set_clear_color(Some(wgpu::Color::BLUE));
+    let plain_scene = Scene::default();
+
+    let engine = V4::builder()
+        .backends(wgpu::Backends::NOOP)
+        .clear_color(ENGINE_COLOR)
+        .build_headless()
+        .await;
+    let rendering_manager = engine.rendering_manager();
+
+    for (scene, expected) in [
+        (&red_scene, wgpu::Color::RED),
+        (&blue_scene, wgpu::Color::BLUE),
+        (&plain_scene, ENGINE_COLOR),
+        (&red_scene, wgpu::Color::RED),
+    ] {
+        assert_eq!(rendering_manager.scene_clear_color(scene), expected);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unsetting_the_clear_color_falls_back_to_the_engine_color() {
+    let mut scene = Scene::default();
+    scene.set_clear_color(Some(wgpu::Color::GREEN));
+
+    let engine = V4::builder()
+        .backends(wgpu::Backends::NOOP)
+        .clear_color(ENGINE_COLOR)
+        .build_headless()
+        .await;
+
+    assert_eq!(
+        engine.rendering_manager().scene_clear_color(&scene),
+        wgpu::Color::GREEN
+    );
+    scene.set_clear_color(None);
+    assert_eq!(
+        engine.rendering_manager().scene_clear_color(&scene),
+        ENGINE_COLOR
+    );
+}
@@ -0,0 +1,63 @@
+#![allow(dead_code)]
+
+use crossbeam_channel::{Receiver, Sender};
+use v4::{
+    EngineDetails,
+    ecs::{
+        actions::ActionQueue,
+        scene::{Scene, WorkloadOutputPacket, WorkloadPacket},
+    },
+    engine_management::engine_action::EngineAction,
+    engine_support::{gamepad_state::GamepadState, input_state::MockInput},
+};
+
+/// The engine's ends of the channels of a scene initialized without an engine
+pub struct SceneChannels {
+    pub workload_receiver: Receiver<WorkloadPacket>,
+    pub workload_output_sender: Sender<WorkloadOutputPacket>,
+    pub engine_action_receiver: Receiver<Box<dyn EngineAction>>,
+}
+
+pub fn noop_device() -> (wgpu::Device, wgpu::Queue) {
+    wgpu::Device::noop(&wgpu::DeviceDescriptor::default())
+}
+
+/// Initializes the scene like the engine does when it becomes active, and executes the actions
+/// returned by its components
+pub fn initialize(scene: &mut Scene, device: &wgpu::Device, queue: &wgpu::Queue) -> SceneChannels {
+    let (workload_sender, workload_receiver) = crossbeam_channel::unbounded();
+    let (workload_output_sender, workload_output_receiver) = crossbeam_channel::unbounded();
+    let (engine_action_sender, engine_action_receiver) = crossbeam_channel::unbounded();
+    let actions = pollster::block_on(scene.initialize(
+        device,
+        queue,
+        workload_sender,
+        workload_output_receiver,
+        engine_action_sender,
+    ));
+    execute(scene, actions, device, queue);
+
+    SceneChannels {
+        workload_receiver,
+        workload_output_sender,
+        engine_action_receiver,
+    }
+}
+
+/// Updates the scene's components and materials like a frame of the engine does, without input
+pub fn update(
+    scene: &mut Scene,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    engine_details: &EngineDetails,
+) {
+    let input = MockInput::default();
+    let gamepads = GamepadState::default();
+    let actions = scene.update(device, queue, &input, &gamepads, engine_details);
+    execute(scene, actions, device, queue);
+    scene.update_materials(device, queue, &input, &gamepads, engine_details);
+}
+
+fn execute(scene: &mut Scene, actions: ActionQueue, device: &wgpu::Device, queue: &wgpu::Queue) {
+    pollster::block_on(scene.execute_action_queue(actions, device, queue));
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use v4::component;
+
+#[component]
+pub struct Settings {
+    scale: Option<f32>,
+    tags: Vec<String>,
+    lookup: HashMap<String, u32>,
+    #[default(Some(2.0))]
+    speed: Option<f32>,
+    #[required]
+    target: Option<u32>,
+}
+
+#[test]
+fn option_and_collection_fields_default_to_empty() {
+    let settings = Settings::builder().target(None).build();
+
+    assert_eq!(settings.scale, None);
+    assert!(settings.tags.is_empty());
+    assert!(settings.lookup.is_empty());
+}
+
+#[test]
+fn explicit_default_overrides_auto_default() {
+    let settings = Settings::builder().target(None).build();
+
+    assert_eq!(settings.speed, Some(2.0));
+}
+
+#[test]
+fn auto_default_fields_can_still_be_set() {
+    let settings = Settings::builder()
+        .scale(Some(0.5))
+        .tags(vec!["player".to_string()])
+        .target(Some(3))
+        .build();
+
+    assert_eq!(settings.scale, Some(0.5));
+    assert_eq!(settings.tags, ["player"]);
+    assert_eq!(settings.target, Some(3));
+}
+
+mod clone {
+    use nalgebra::Vector3;
+    use v4::{
+        builtin_components::transform_component::TransformComponent, component,
+        ecs::component::ComponentDetails,
+    };
+
+    /// Deliberately not `Clone`
+    #[derive(Debug, Default)]
+    pub struct Handle(u32);
+
+    #[component(clone)]
+    pub struct Holder {
+        value: u32,
+        #[no_clone]
+        #[default]
+        handle: Handle,
+    }
+
+    #[test]
+    fn cloned_transform_gets_a_fresh_id() {
+        let transform = TransformComponent::builder()
+            .position(Vector3::new(1.0, 2.0, 3.0))
+            .scale(Vector3::new(2.0, 2.0, 2.0))
+            .build();
+        let clone = transform.clone();
+
+        assert_ne!(clone.id(), transform.id());
+        assert_eq!(clone.get_position(), transform.get_position());
+        assert_eq!(clone.get_scale(), transform.get_scale());
+        assert_eq!(clone.create_matrix(), transform.create_matrix());
+    }
+
+    #[test]
+    fn no_clone_fields_reset_to_default() {
+        let holder = Holder::builder().value(4).handle(Handle(7)).build();
+        let clone = holder.clone();
+
+        assert_ne!(clone.id(), holder.id());
+        assert_eq!(clone.value, 4);
+        assert_eq!(clone.handle.0, 0);
+        assert_eq!(holder.handle.0, 7);
+    }
+}
+
+mod setter {
+    use v4::component;
+
+    fn validate_angle(angle: &f32) -> Result<(), String> {
+        if (0.0..=360.0).contains(angle) {
+            Ok(())
+        } else {
+            Err(format!("{angle} is outside 0 to 360 degrees"))
+        }
+    }
+
+    #[component]
+    pub struct Turret {
+        #[setter(name = "with_angle", validate = validate_angle)]
+        angle: f32,
+    }
+
+    #[test]
+    fn renamed_setter_sets_the_field() {
+        let turret = Turret::builder().with_angle(90.0).build();
+
+        assert_eq!(turret.angle, 90.0);
+    }
+
+    #[test]
+    fn try_build_rejects_invalid_value() {
+        let error = Turret::builder().with_angle(400.0).try_build().unwrap_err();
+
+        assert_eq!(
+            error,
+            "Invalid value for field `angle`: 400 is outside 0 to 360 degrees"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value for field `angle`")]
+    fn build_panics_on_invalid_value() {
+        Turret::builder().with_angle(-1.0).build();
+    }
+}
+
+mod accessors {
+    use v4::{component, ecs::component::ComponentDetails};
+
+    #[component(accessors)]
+    pub struct Health {
+        current: u32,
+        #[default(vec!["player".to_string()])]
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn getters_read_the_fields() {
+        let health = Health::builder().current(80).build();
+
+        assert_eq!(*health.current(), 80);
+        assert_eq!(health.tags(), &["player"]);
+    }
+
+    #[test]
+    fn setters_change_the_fields() {
+        let mut health = Health::builder().current(80).build();
+        let id = health.id();
+        health.set_current(35);
+        health.set_tags(Vec::new());
+
+        assert_eq!(*health.current(), 35);
+        assert!(health.tags().is_empty());
+        assert_eq!(health.id(), id);
+    }
+}
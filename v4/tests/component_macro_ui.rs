@@ -0,0 +1,7 @@
+#[test]
+fn component_macro_diagnostics() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/mesh_missing_vertices.rs");
+    cases.compile_fail("tests/ui/required_option_field.rs");
+    cases.pass("tests/ui/where_clause_and_lifetime.rs");
+}
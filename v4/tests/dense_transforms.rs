@@ -0,0 +1,75 @@
+mod common;
+
+use nalgebra::{Matrix4, Vector3};
+use v4::{
+    EngineDetails,
+    builtin_components::transform_component::TransformComponent,
+    ecs::{component::ComponentDetails, scene::Scene},
+};
+
+fn transform(x: f32) -> TransformComponent {
+    TransformComponent::builder()
+        .position(Vector3::new(x, 0.0, 0.0))
+        .build()
+}
+
+#[test]
+fn dense_transforms_are_updated_without_boxed_components() {
+    let (device, queue) = common::noop_device();
+    let mut scene = Scene::default();
+    let entity = scene.create_entity(None, Vec::new(), Vec::new(), None, true);
+    scene.attach_dense_component(entity, transform(1.0));
+    common::initialize(&mut scene, &device, &queue);
+
+    common::update(&mut scene, &device, &queue, &EngineDetails::default());
+    let dense = scene
+        .dense_storage()
+        .get::<TransformComponent>(entity)
+        .expect("The transform was attached");
+    assert!(scene.all_components().is_empty());
+    assert!(dense.is_matrix_cached());
+    assert!(dense.instance_buffer().is_some());
+
+    scene
+        .dense_storage_mut()
+        .get_mut::<TransformComponent>(entity)
+        .expect("The transform was attached")
+        .set_position(Vector3::new(5.0, 0.0, 0.0));
+    common::update(&mut scene, &device, &queue, &EngineDetails::default());
+    let dense = scene
+        .dense_storage()
+        .get::<TransformComponent>(entity)
+        .expect("The transform was attached");
+    assert_eq!(
+        dense.create_matrix(),
+        Matrix4::new_translation(&Vector3::new(5.0, 0.0, 0.0))
+    );
+}
+
+#[test]
+fn boxed_children_are_placed_by_dense_parents() {
+    let (device, queue) = common::noop_device();
+    let mut scene = Scene::default();
+    let parent = scene.create_entity(None, Vec::new(), Vec::new(), None, true);
+    scene.attach_dense_component(parent, transform(2.0));
+    let child_transform = transform(3.0);
+    let child_transform_id = child_transform.id();
+    scene.create_entity(
+        Some(parent),
+        vec![Box::new(child_transform)],
+        Vec::new(),
+        None,
+        true,
+    );
+    common::initialize(&mut scene, &device, &queue);
+
+    common::update(&mut scene, &device, &queue, &EngineDetails::default());
+    let child = scene
+        .get_component(child_transform_id)
+        .and_then(|component| component.downcast_ref::<TransformComponent>())
+        .expect("The child has a transform");
+    assert_eq!(
+        child.world_matrix(),
+        Matrix4::new_translation(&Vector3::new(5.0, 0.0, 0.0))
+    );
+}
@@ -0,0 +1,71 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use v4::{
+    V4, component,
+    ecs::{
+        actions::ActionQueue,
+        component::{ComponentDetails, ComponentSystem, UpdateParams},
+        scene::Scene,
+    },
+    engine_support::{
+        input_state::MockInput,
+        texture_support::{TextureBundle, TextureProperties},
+    },
+};
+use wgpu::{Device, Queue};
+
+const TEXTURE_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../examples/assets/testing_textures/sprite_strip.png"
+);
+
+/// Loads its texture in `initialize_async`, and records whether it had one on each update
+#[component]
+pub struct TextureLoader {
+    #[default]
+    texture: Option<TextureBundle>,
+    texture_on_update: Arc<Mutex<Vec<bool>>>,
+}
+
+#[async_trait::async_trait]
+impl ComponentSystem for TextureLoader {
+    async fn initialize_async(&mut self, device: &Device, queue: &Queue) -> ActionQueue {
+        let (_texture, texture_bundle) =
+            TextureBundle::from_path(TEXTURE_PATH, device, queue, TextureProperties::default())
+                .await
+                .expect("The test texture is bundled with the examples");
+        self.texture = Some(texture_bundle);
+        self.set_initialized();
+        Vec::new()
+    }
+
+    fn update(&mut self, _params: UpdateParams<'_, '_>) -> ActionQueue {
+        self.texture_on_update
+            .lock()
+            .unwrap()
+            .push(self.texture.is_some());
+        Vec::new()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn async_initialization_finishes_before_the_first_update() {
+    let texture_on_update = Arc::new(Mutex::new(Vec::new()));
+    let loader = TextureLoader::builder()
+        .texture_on_update(texture_on_update.clone())
+        .build();
+    let mut scene = Scene::default();
+    scene.create_entity(None, vec![Box::new(loader)], Vec::new(), None, true);
+
+    let mut engine = V4::builder()
+        .backends(wgpu::Backends::NOOP)
+        .build_headless()
+        .await;
+    engine.attach_scene(scene);
+    engine.step_frame(Duration::from_millis(16), &MockInput::default());
+
+    assert_eq!(*texture_on_update.lock().unwrap(), [true]);
+}
@@ -0,0 +1,69 @@
+use nalgebra::Vector3;
+use v4::builtin_components::transform_component::TransformComponent;
+
+fn look_at(position: Vector3<f32>, target: Vector3<f32>, up: Vector3<f32>) -> TransformComponent {
+    let mut transform = TransformComponent::builder().position(position).build();
+    transform.look_at(target, up);
+    transform
+}
+
+fn assert_close(actual: Vector3<f32>, expected: Vector3<f32>) {
+    assert!(
+        (actual - expected).norm() < 1e-4,
+        "Expected {expected:?}, got {actual:?}"
+    );
+}
+
+#[test]
+fn forward_points_at_the_target_with_up_kept() {
+    let transform = look_at(
+        Vector3::new(1.0, 2.0, 3.0),
+        Vector3::new(4.0, 2.0, 3.0),
+        Vector3::y(),
+    );
+    let rotation = transform.get_rotation();
+
+    assert_close(rotation * Vector3::z(), Vector3::x());
+    assert_close(rotation * Vector3::y(), Vector3::y());
+}
+
+#[test]
+fn degenerate_up_keeps_a_valid_rotation() {
+    for up in [Vector3::zeros(), Vector3::new(1e-9, 0.0, 0.0)] {
+        let transform = look_at(Vector3::zeros(), Vector3::new(0.0, 0.0, -5.0), up);
+        let rotation = transform.get_rotation();
+
+        assert_close(rotation * Vector3::z(), -Vector3::z());
+        let y = rotation * Vector3::y();
+        assert!(
+            (y.norm() - 1.0).abs() < 1e-4,
+            "Up axis {y:?} is not unit length"
+        );
+        assert!(y.dot(&Vector3::z()).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn forward_equal_to_up_keeps_a_valid_rotation() {
+    let transform = look_at(Vector3::zeros(), Vector3::new(0.0, 3.0, 0.0), Vector3::y());
+    let rotation = transform.get_rotation();
+    let x = rotation * Vector3::x();
+    let y = rotation * Vector3::y();
+
+    assert_close(rotation * Vector3::z(), Vector3::y());
+    assert!(x.iter().chain(y.iter()).all(|value| value.is_finite()));
+    assert!(x.dot(&Vector3::y()).abs() < 1e-4);
+    assert!(y.dot(&Vector3::y()).abs() < 1e-4);
+    assert!((x.norm() - 1.0).abs() < 1e-4 && (y.norm() - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn target_at_the_position_leaves_the_rotation() {
+    let transform = look_at(
+        Vector3::new(1.0, 1.0, 1.0),
+        Vector3::new(1.0, 1.0, 1.0),
+        Vector3::y(),
+    );
+
+    assert_close(transform.get_rotation() * Vector3::z(), Vector3::z());
+}
@@ -0,0 +1,63 @@
+use v4::builtin_components::nine_slice_component::{NineSliceComponent, NineSliceVertex};
+
+const RESOLUTION: (u32, u32) = (800, 600);
+
+fn panel(size: [f32; 2]) -> NineSliceComponent {
+    NineSliceComponent::builder()
+        .position([20.0, 40.0])
+        .size(size)
+        .border([10.0, 12.0, 8.0, 6.0])
+        .uv_border([0.25, 0.25, 0.125, 0.375])
+        .build()
+}
+
+/// The vertex grid's column x and row y coordinates, back in pixels
+fn pixel_grid(vertices: &[NineSliceVertex; 16]) -> ([f32; 4], [f32; 4]) {
+    let xs = std::array::from_fn(|column| {
+        (vertices[column].position[0] + 1.0) / 2.0 * RESOLUTION.0 as f32
+    });
+    let ys = std::array::from_fn(|row| {
+        (1.0 - vertices[row * 4].position[1]) / 2.0 * RESOLUTION.1 as f32
+    });
+    (xs, ys)
+}
+
+fn assert_close(actual: [f32; 4], expected: [f32; 4]) {
+    for (actual, expected) in actual.into_iter().zip(expected) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "{actual} is not {expected}"
+        );
+    }
+}
+
+#[test]
+fn resizing_keeps_the_corner_uvs_and_sizes() {
+    let mut panel = panel([100.0, 50.0]);
+    let small = panel.vertices(RESOLUTION);
+    panel.set_size([300.0, 200.0]);
+    let large = panel.vertices(RESOLUTION);
+
+    let tex_coords = |vertices: &[NineSliceVertex; 16]| vertices.map(|vertex| vertex.tex_coords);
+    assert_eq!(tex_coords(&small), tex_coords(&large));
+    assert_eq!(small[5].tex_coords, [0.25, 0.125]);
+    assert_eq!(small[10].tex_coords, [0.75, 0.625]);
+
+    let (small_xs, small_ys) = pixel_grid(&small);
+    assert_close(small_xs, [20.0, 30.0, 108.0, 120.0]);
+    assert_close(small_ys, [40.0, 48.0, 84.0, 90.0]);
+
+    // Only the middle column and row stretch
+    let (large_xs, large_ys) = pixel_grid(&large);
+    assert_close(large_xs, [20.0, 30.0, 308.0, 320.0]);
+    assert_close(large_ys, [40.0, 48.0, 234.0, 240.0]);
+}
+
+#[test]
+fn panels_smaller_than_their_borders_shrink_the_corners() {
+    let (xs, ys) = pixel_grid(&panel([11.0, 7.0]).vertices(RESOLUTION));
+
+    // The insets are scaled down so opposite edges meet in the middle cell
+    assert_close(xs, [20.0, 25.0, 25.0, 31.0]);
+    assert_close(ys, [40.0, 44.0, 44.0, 47.0]);
+}
@@ -0,0 +1,124 @@
+use v4::{
+    V4,
+    engine_management::{
+        pipeline::{
+            GeometryDetails, PipelineId, PipelineShader, PipelineTarget, create_render_pipeline,
+        },
+        pipeline_cache::PipelineCache,
+    },
+    engine_support::texture_support::TextureBundle,
+};
+use wgpu::TextureFormat;
+
+const SHADER: &str = "
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(corner * 2.0 - 1.0, 0.5, 1.0);
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+";
+
+/// Pipelines with different priorities have different ids
+fn pipeline_id(render_priority: i32) -> PipelineId {
+    PipelineId {
+        vertex_shader: PipelineShader::Raw(SHADER.into()),
+        spirv_vertex_shader: false,
+        fragment_shader: PipelineShader::Raw(SHADER.into()),
+        spirv_fragment_shader: false,
+        vertex_entry: "vertex",
+        fragment_entry: "fragment",
+        vertex_layouts: Vec::new(),
+        uses_camera: false,
+        is_screen_space: false,
+        geometry_details: GeometryDetails::default(),
+        immediate_size: 0,
+        render_priority,
+        blend_state: PipelineId::DEFAULT_BLEND_STATE,
+        writes_gbuffer: false,
+    }
+}
+
+async fn headless_engine() -> V4 {
+    V4::builder()
+        .backends(wgpu::Backends::NOOP)
+        .build_headless()
+        .await
+}
+
+fn cache_with_pipeline(engine: &V4, id: &PipelineId) -> PipelineCache {
+    let target = PipelineTarget::new(TextureFormat::Rgba8UnormSrgb);
+    let pipeline = create_render_pipeline(
+        engine.rendering_manager().device(),
+        id,
+        None,
+        &target,
+        TextureBundle::DEPTH_FORMAT,
+        false,
+        false,
+        false,
+    )
+    .unwrap();
+    let mut cache = PipelineCache::new(target);
+    cache.insert(id.clone(), pipeline);
+    cache
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn changing_the_target_drops_the_cached_pipelines() {
+    let engine = headless_engine().await;
+    let id = pipeline_id(0);
+    let mut cache = cache_with_pipeline(&engine, &id);
+
+    assert!(!cache.set_target(PipelineTarget::new(TextureFormat::Rgba8UnormSrgb)));
+    assert!(cache.contains(&id));
+
+    let new_target = PipelineTarget::new(TextureFormat::Bgra8UnormSrgb);
+    assert!(cache.set_target(new_target.clone()));
+    assert!(!cache.contains(&id));
+    assert!(cache.pipelines().is_empty());
+    assert_eq!(*cache.target(), new_target);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn only_cached_pipelines_are_recompiled() {
+    let mut engine = headless_engine().await;
+    let cached = pipeline_id(0);
+    let uncached = pipeline_id(1);
+    let cache = cache_with_pipeline(&engine, &cached);
+    let rendering_manager = engine.rendering_manager_mut();
+
+    cache.recompile(rendering_manager, &cached, None);
+    cache.recompile(rendering_manager, &uncached, None);
+
+    assert!(rendering_manager.is_compiling_pipeline(&cached, cache.target()));
+    assert!(!rendering_manager.is_compiling_pipeline(&uncached, cache.target()));
+    // The cached pipeline stays in use until the recompiled one is inserted
+    assert!(cache.get(&cached).is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn missing_pipelines_are_compiled_for_the_cache_target() {
+    let mut engine = headless_engine().await;
+    let cached = pipeline_id(0);
+    let missing = pipeline_id(1);
+    let cache = cache_with_pipeline(&engine, &cached);
+    let rendering_manager = engine.rendering_manager_mut();
+
+    assert!(
+        cache
+            .get_or_create(rendering_manager, &cached, None)
+            .is_some()
+    );
+    assert!(!rendering_manager.is_compiling_pipeline(&cached, cache.target()));
+    assert!(
+        cache
+            .get_or_create(rendering_manager, &missing, None)
+            .is_none()
+    );
+    assert!(rendering_manager.is_compiling_pipeline(&missing, cache.target()));
+}
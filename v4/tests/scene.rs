@@ -0,0 +1,424 @@
+mod common;
+
+use v4::{
+    EngineDetails, component,
+    ecs::{
+        component::{Component, ComponentDetails, ComponentId, ComponentSystem},
+        entity::EntityId,
+        scene::Scene,
+    },
+    engine_management::pipeline::{GeometryDetails, PipelineId, PipelineShader},
+};
+
+#[component]
+pub struct Marker {
+    #[default]
+    value: u32,
+}
+
+impl ComponentSystem for Marker {}
+
+fn marker() -> (ComponentId, Component) {
+    ordered_marker(0)
+}
+
+fn ordered_marker(rendering_order: i32) -> (ComponentId, Component) {
+    let marker = Marker::builder().rendering_order(rendering_order).build();
+    (marker.id(), Box::new(marker))
+}
+
+fn component_ids(scene: &Scene) -> Vec<ComponentId> {
+    scene
+        .all_components()
+        .iter()
+        .map(|component| component.id())
+        .collect()
+}
+
+fn entity_component_ids(scene: &Scene) -> Vec<(EntityId, Vec<ComponentId>)> {
+    scene
+        .iter_entities()
+        .map(|(entity_id, _, components)| {
+            let ids = components.iter().map(|component| component.id()).collect();
+            (entity_id, ids)
+        })
+        .collect()
+}
+
+mod rendering_order {
+    use super::*;
+
+    #[test]
+    fn changed_orders_resort_the_entity_components() {
+        let mut scene = Scene::default();
+        let (first, first_component) = marker();
+        let (second, second_component) = marker();
+        let (third, third_component) = marker();
+        scene.create_entity(
+            None,
+            vec![first_component, second_component, third_component],
+            Vec::new(),
+            None,
+            true,
+        );
+        assert_eq!(component_ids(&scene), [first, second, third]);
+
+        scene.set_component_rendering_order(first, 10);
+        assert_eq!(component_ids(&scene), [second, third, first]);
+
+        scene.set_component_rendering_order(third, -5);
+        assert_eq!(component_ids(&scene), [third, second, first]);
+        let orders: Vec<i32> = scene
+            .all_components()
+            .iter()
+            .map(|component| component.rendering_order())
+            .collect();
+        assert_eq!(orders, [-5, 0, 10]);
+    }
+
+    #[test]
+    fn other_entities_keep_their_order() {
+        let mut scene = Scene::default();
+        let (first, first_component) = marker();
+        let (second, second_component) = marker();
+        let (other, other_component) = marker();
+        scene.create_entity(
+            None,
+            vec![first_component, second_component],
+            Vec::new(),
+            None,
+            true,
+        );
+        scene.create_entity(None, vec![other_component], Vec::new(), None, true);
+
+        scene.set_component_rendering_order(first, 3);
+        assert_eq!(component_ids(&scene), [second, first, other]);
+
+        // Setting the same order again changes nothing
+        scene.set_component_rendering_order(first, 3);
+        assert_eq!(component_ids(&scene), [second, first, other]);
+    }
+}
+
+mod child_entities {
+    use v4::{
+        builtin_actions::CreateChildEntityAction,
+        ecs::{actions::ActionQueue, component::UpdateParams},
+    };
+
+    use super::*;
+
+    /// Spawns a child of its own entity on its first update
+    #[component]
+    pub struct Spawner {
+        #[default]
+        spawned: bool,
+    }
+
+    impl ComponentSystem for Spawner {
+        fn update(&mut self, _params: UpdateParams<'_, '_>) -> ActionQueue {
+            if self.spawned {
+                return Vec::new();
+            }
+            self.spawned = true;
+            vec![Box::new(CreateChildEntityAction {
+                parent: self.parent_entity_id(),
+                components: vec![marker().1],
+                computes: Vec::new(),
+                active_material: None,
+                is_enabled: true,
+            })]
+        }
+    }
+
+    #[test]
+    fn spawned_children_are_linked_to_their_parent() {
+        let (device, queue) = common::noop_device();
+        let mut scene = Scene::default();
+        let parent = scene.create_entity(
+            None,
+            vec![Box::new(Spawner::builder().build())],
+            Vec::new(),
+            None,
+            true,
+        );
+        common::initialize(&mut scene, &device, &queue);
+
+        common::update(&mut scene, &device, &queue, &EngineDetails::default());
+        common::update(&mut scene, &device, &queue, &EngineDetails::default());
+
+        let children = scene
+            .get_entity(parent)
+            .expect("The parent exists")
+            .children_ids()
+            .to_vec();
+        assert_eq!(children.len(), 1);
+        let child = scene.get_entity(children[0]).expect("The child exists");
+        assert_eq!(child.parent_entity_id(), parent);
+        let (_, _, child_components) = scene
+            .iter_entities()
+            .find(|(entity_id, _, _)| *entity_id == children[0])
+            .expect("The child is iterated");
+        assert_eq!(child_components.len(), 1);
+        assert_eq!(child_components[0].parent_entity_id(), children[0]);
+    }
+
+    #[test]
+    fn children_of_missing_parents_are_not_created() {
+        let (device, queue) = common::noop_device();
+        let mut scene = Scene::default();
+        let removed = scene.create_entity(None, Vec::new(), Vec::new(), None, true);
+        scene.remove_entity(removed);
+
+        pollster::block_on(scene.execute_action_queue(
+            vec![Box::new(CreateChildEntityAction {
+                parent: removed,
+                components: vec![marker().1],
+                computes: Vec::new(),
+                active_material: None,
+                is_enabled: true,
+            })],
+            &device,
+            &queue,
+        ));
+
+        assert_eq!(scene.iter_entities().count(), 0);
+        assert!(scene.all_components().is_empty());
+    }
+}
+
+mod iter_entities {
+    use super::*;
+
+    #[test]
+    fn entities_are_yielded_with_their_own_components() {
+        let mut scene = Scene::default();
+        let (first, first_component) = marker();
+        let (second, second_component) = marker();
+        let (third, third_component) = marker();
+        let parent = scene.create_entity(
+            None,
+            vec![first_component, second_component],
+            Vec::new(),
+            None,
+            true,
+        );
+        let empty_child = scene.create_entity(Some(parent), Vec::new(), Vec::new(), None, true);
+        let other = scene.create_entity(None, vec![third_component], Vec::new(), None, true);
+
+        let entities: Vec<_> = scene
+            .iter_entities()
+            .map(|(entity_id, entity, components)| {
+                assert_eq!(entity.id(), entity_id);
+                let ids: Vec<ComponentId> =
+                    components.iter().map(|component| component.id()).collect();
+                (entity_id, ids)
+            })
+            .collect();
+
+        assert_eq!(
+            entities,
+            [
+                (parent, vec![first, second]),
+                (empty_child, Vec::new()),
+                (other, vec![third]),
+            ]
+        );
+    }
+}
+
+mod material_draw_order {
+    use super::*;
+
+    fn pipeline_id() -> PipelineId {
+        PipelineId {
+            vertex_shader: PipelineShader::Path("shaders/vertex.wgsl"),
+            spirv_vertex_shader: false,
+            fragment_shader: PipelineShader::Path("shaders/fragment.wgsl"),
+            spirv_fragment_shader: false,
+            vertex_entry: "main",
+            fragment_entry: "main",
+            vertex_layouts: Vec::new(),
+            uses_camera: false,
+            is_screen_space: false,
+            geometry_details: GeometryDetails::default(),
+            immediate_size: 0,
+            render_priority: 0,
+            blend_state: PipelineId::DEFAULT_BLEND_STATE,
+            writes_gbuffer: false,
+        }
+    }
+
+    #[test]
+    fn components_are_drawn_by_rendering_order_across_entities() {
+        let (device, queue) = common::noop_device();
+        let mut scene = Scene::default();
+        let material =
+            scene.create_material(pipeline_id(), Vec::new(), Vec::new(), Vec::new(), true);
+        let (back, back_component) = ordered_marker(0);
+        let (front, front_component) = ordered_marker(2);
+        let (middle, middle_component) = ordered_marker(1);
+        let (last, last_component) = ordered_marker(3);
+        scene.create_entity(
+            None,
+            vec![front_component, back_component],
+            Vec::new(),
+            Some(material),
+            true,
+        );
+        scene.create_entity(
+            None,
+            vec![last_component, middle_component],
+            Vec::new(),
+            Some(material),
+            true,
+        );
+        common::initialize(&mut scene, &device, &queue);
+
+        common::update(&mut scene, &device, &queue, &EngineDetails::default());
+
+        let components = scene.all_components();
+        let drawn: Vec<ComponentId> = scene
+            .get_material(material)
+            .expect("The material was created")
+            .draw_order()
+            .iter()
+            .map(|&index| components[index].id())
+            .collect();
+        assert_eq!(drawn, [back, middle, front, last]);
+    }
+}
+
+mod remove_component {
+    use super::*;
+
+    #[test]
+    fn later_groupings_are_shifted_down() {
+        let mut scene = Scene::default();
+        let (first, first_component) = marker();
+        let (removed, removed_component) = marker();
+        let (middle, middle_component) = marker();
+        let (last, last_component) = marker();
+        let (other_last, other_last_component) = marker();
+        let owner = scene.create_entity(
+            None,
+            vec![first_component, removed_component],
+            Vec::new(),
+            None,
+            true,
+        );
+        let next = scene.create_entity(None, vec![middle_component], Vec::new(), None, true);
+        let after = scene.create_entity(
+            None,
+            vec![last_component, other_last_component],
+            Vec::new(),
+            None,
+            true,
+        );
+
+        let component = scene.remove_component(removed);
+
+        assert_eq!(component.map(|component| component.id()), Some(removed));
+        assert!(scene.get_component(removed).is_none());
+        assert_eq!(
+            entity_component_ids(&scene),
+            [
+                (owner, vec![first]),
+                (next, vec![middle]),
+                (after, vec![last, other_last]),
+            ]
+        );
+    }
+
+    #[test]
+    fn removing_an_entitys_only_component_leaves_it_empty() {
+        let mut scene = Scene::default();
+        let (only, only_component) = marker();
+        let (other, other_component) = marker();
+        let emptied = scene.create_entity(None, vec![only_component], Vec::new(), None, true);
+        let kept = scene.create_entity(None, vec![other_component], Vec::new(), None, true);
+
+        scene.remove_component(only);
+
+        assert_eq!(
+            entity_component_ids(&scene),
+            [(emptied, Vec::new()), (kept, vec![other])]
+        );
+    }
+
+    #[test]
+    fn unknown_components_are_not_removed() {
+        let mut scene = Scene::default();
+        let (kept, kept_component) = marker();
+        let (unknown, _) = marker();
+        scene.create_entity(None, vec![kept_component], Vec::new(), None, true);
+
+        assert!(scene.remove_component(unknown).is_none());
+        assert_eq!(component_ids(&scene), [kept]);
+    }
+}
+
+mod remove_entity {
+    use v4::ecs::entity::{entity_generation, entity_index};
+
+    use super::*;
+
+    #[test]
+    fn children_are_removed_recursively() {
+        let mut scene = Scene::default();
+        let (_, parent_component) = marker();
+        let (_, child_component) = marker();
+        let (_, grandchild_component) = marker();
+        let (kept, kept_component) = marker();
+        let parent = scene.create_entity(None, vec![parent_component], Vec::new(), None, true);
+        let child =
+            scene.create_entity(Some(parent), vec![child_component], Vec::new(), None, true);
+        let grandchild = scene.create_entity(
+            Some(child),
+            vec![grandchild_component],
+            Vec::new(),
+            None,
+            true,
+        );
+        let other = scene.create_entity(None, vec![kept_component], Vec::new(), None, true);
+
+        assert!(scene.remove_entity(parent));
+
+        for removed in [parent, child, grandchild] {
+            assert!(!scene.is_entity_alive(removed));
+            assert!(scene.get_entity(removed).is_none());
+        }
+        assert_eq!(entity_component_ids(&scene), [(other, vec![kept])]);
+        assert_eq!(component_ids(&scene), [kept]);
+    }
+
+    #[test]
+    fn removed_children_are_detached_from_their_parent() {
+        let mut scene = Scene::default();
+        let parent = scene.create_entity(None, Vec::new(), Vec::new(), None, true);
+        let removed = scene.create_entity(Some(parent), Vec::new(), Vec::new(), None, true);
+        let kept = scene.create_entity(Some(parent), Vec::new(), Vec::new(), None, true);
+
+        scene.remove_entity(removed);
+
+        assert_eq!(scene.get_entity(parent).unwrap().children_ids(), [kept]);
+    }
+
+    #[test]
+    fn freed_ids_are_reused_with_a_new_generation() {
+        let mut scene = Scene::default();
+        let removed = scene.create_entity(None, Vec::new(), Vec::new(), None, true);
+
+        assert!(scene.remove_entity(removed));
+        assert!(!scene.remove_entity(removed));
+        let replacement = scene.create_entity(None, Vec::new(), Vec::new(), None, true);
+
+        assert_eq!(entity_index(replacement), entity_index(removed));
+        assert_eq!(
+            entity_generation(replacement),
+            entity_generation(removed) + 1
+        );
+        assert!(scene.is_entity_alive(replacement));
+        assert!(!scene.is_entity_alive(removed));
+    }
+}
@@ -0,0 +1,192 @@
+use nalgebra::Vector3;
+use v4::{
+    builtin_components::transform_component::TransformComponent,
+    ecs::{
+        component::{Component, ComponentDetails},
+        entity::{entity_generation, entity_index},
+        scene::Scene,
+        scene_descriptor::{ComponentRegistry, SceneDescriptor},
+    },
+};
+
+fn registry() -> ComponentRegistry {
+    let mut registry = ComponentRegistry::new();
+    registry.register::<TransformComponent>("TransformComponent");
+    registry
+}
+
+fn transform(x: f32) -> Component {
+    Box::new(
+        TransformComponent::builder()
+            .position(Vector3::new(x, 0.0, 0.0))
+            .build(),
+    )
+}
+
+fn load(descriptor: &SceneDescriptor) -> Scene {
+    Scene::from_descriptor(descriptor, &registry(), |_| {
+        unreachable!("The scene has no materials")
+    })
+    .expect("Failed to load the scene")
+}
+
+#[test]
+fn entities_keep_their_ids_and_creation_order() {
+    let mut scene = Scene::default();
+    let first = scene.create_entity(None, vec![transform(1.0)], Vec::new(), None, true);
+    let removed = scene.create_entity(None, vec![transform(2.0)], Vec::new(), None, true);
+    scene.remove_entity(removed);
+    let reused = scene.create_entity(None, vec![transform(3.0)], Vec::new(), None, true);
+    let child = scene.create_entity(Some(reused), vec![transform(4.0)], Vec::new(), None, true);
+    assert_eq!(entity_index(reused), entity_index(removed));
+    assert_eq!(entity_generation(reused), 1);
+
+    let descriptor = scene.to_descriptor(&registry());
+    let saved_ids: Vec<_> = descriptor.entities.iter().map(|entity| entity.id).collect();
+    assert_eq!(saved_ids, vec![first, reused, child]);
+
+    let loaded = load(&SceneDescriptor::from_ron(&descriptor.to_ron().unwrap()).unwrap());
+    let loaded_ids: Vec<_> = loaded.iter_entities().map(|(id, _, _)| id).collect();
+    assert_eq!(loaded_ids, saved_ids);
+    assert!(!loaded.is_entity_alive(removed));
+    assert_eq!(loaded.get_entity(child).unwrap().parent_entity_id(), reused);
+
+    let (_, _, components) = loaded.iter_entities().nth(1).unwrap();
+    assert_eq!(components[0].parent_entity_id(), reused);
+}
+
+#[test]
+fn parents_without_components_are_saved_before_their_children() {
+    let mut scene = Scene::default();
+    let removed = scene.create_entity(None, Vec::new(), Vec::new(), None, true);
+    scene.remove_entity(removed);
+    let parent = scene.create_entity(None, Vec::new(), Vec::new(), None, true);
+    let child = scene.create_entity(Some(parent), Vec::new(), Vec::new(), None, true);
+    // The reused slot gives the parent the larger id
+    assert!(child < parent);
+
+    let descriptor = scene.to_descriptor(&registry());
+    let saved_ids: Vec<_> = descriptor.entities.iter().map(|entity| entity.id).collect();
+    assert_eq!(saved_ids, vec![parent, child]);
+    let loaded = load(&descriptor);
+    assert_eq!(loaded.get_entity(child).unwrap().parent_entity_id(), parent);
+}
+
+mod reload {
+    use std::{
+        fs::File,
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    use v4::{
+        ecs::{material::ShaderAttachment, scene::Scene, scene_descriptor::MaterialDescriptor},
+        engine_management::pipeline::{GeometryDetails, PipelineId, PipelineShader},
+    };
+
+    use super::{registry, transform};
+
+    fn pipeline_id() -> PipelineId {
+        PipelineId {
+            vertex_shader: PipelineShader::Path("shaders/vertex.wgsl"),
+            spirv_vertex_shader: false,
+            fragment_shader: PipelineShader::Path("shaders/fragment.wgsl"),
+            spirv_fragment_shader: false,
+            vertex_entry: "main",
+            fragment_entry: "main",
+            vertex_layouts: Vec::new(),
+            uses_camera: false,
+            is_screen_space: false,
+            geometry_details: GeometryDetails::default(),
+            immediate_size: 0,
+            render_priority: 0,
+            blend_state: PipelineId::DEFAULT_BLEND_STATE,
+            writes_gbuffer: false,
+        }
+    }
+
+    fn material_source(_: &MaterialDescriptor) -> (PipelineId, Vec<ShaderAttachment>) {
+        (pipeline_id(), Vec::new())
+    }
+
+    /// Each version gets its own modification time, as some file systems only store whole seconds
+    fn save(scene: &Scene, path: &PathBuf, version: u64) {
+        let ron = scene.to_descriptor(&registry()).to_ron().unwrap();
+        std::fs::write(path, ron).unwrap();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + version))
+            .unwrap();
+    }
+
+    fn scene_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("v4_{name}_{}.ron", std::process::id()))
+    }
+
+    #[test]
+    fn loads_entities_and_materials_from_file() {
+        let mut saved = Scene::default();
+        let material =
+            saved.create_material(pipeline_id(), Vec::new(), Vec::new(), Vec::new(), true);
+        saved.create_material(pipeline_id(), Vec::new(), Vec::new(), Vec::new(), true);
+        let parent =
+            saved.create_entity(None, vec![transform(1.0)], Vec::new(), Some(material), true);
+        saved.create_entity(Some(parent), vec![transform(2.0)], Vec::new(), None, true);
+        saved.create_entity(None, Vec::new(), Vec::new(), None, false);
+        let path = scene_path("load");
+        save(&saved, &path, 0);
+
+        let mut scene = Scene::default();
+        scene.create_entity(None, vec![transform(3.0)], Vec::new(), None, true);
+        scene
+            .reload_from_ron(&path, registry(), material_source)
+            .expect("Failed to load the scene");
+
+        assert_eq!(scene.iter_entities().count(), 3);
+        assert_eq!(scene.materials().len(), 2);
+        let (_, _, components) = scene.iter_entities().next().unwrap();
+        assert_eq!(components.len(), 1);
+        assert!(!scene.reload_changed_ron());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn reload_keeps_ids_of_named_entities() {
+        let path = scene_path("rename");
+        let mut first_version = Scene::default();
+        let player =
+            first_version.create_entity(None, vec![transform(1.0)], Vec::new(), None, true);
+        first_version.set_entity_name(player, "player");
+        save(&first_version, &path, 0);
+
+        let mut scene = Scene::default();
+        scene
+            .reload_from_ron(&path, registry(), material_source)
+            .expect("Failed to load the scene");
+        let player = scene.entity_by_name("player").unwrap();
+
+        // The player is now saved under another id, after a new entity
+        let mut second_version = Scene::default();
+        let enemy =
+            second_version.create_entity(None, vec![transform(2.0)], Vec::new(), None, true);
+        second_version.set_entity_name(enemy, "enemy");
+        let moved_player =
+            second_version.create_entity(None, vec![transform(3.0)], Vec::new(), None, true);
+        second_version.set_entity_name(moved_player, "player");
+        assert_ne!(moved_player, player);
+        save(&second_version, &path, 1);
+
+        assert!(scene.reload_changed_ron());
+        assert_eq!(scene.iter_entities().count(), 2);
+        assert_eq!(scene.entity_by_name("player"), Some(player));
+        let enemy = scene.entity_by_name("enemy").unwrap();
+        assert_ne!(enemy, player);
+        assert!(scene.is_entity_alive(enemy));
+        assert_eq!(scene.entity_name(player), Some("player"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
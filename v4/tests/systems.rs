@@ -0,0 +1,74 @@
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use v4::{
+    EngineDetails, V4, component,
+    ecs::{
+        actions::ActionQueue,
+        component::{ComponentDetails, ComponentSystem, UpdateParams},
+        scene::Scene,
+        system::System,
+    },
+    engine_support::input_state::MockInput,
+};
+
+type Events = Arc<Mutex<Vec<&'static str>>>;
+
+#[derive(Debug)]
+struct CountingSystem {
+    runs: Arc<AtomicUsize>,
+    events: Events,
+}
+
+impl System for CountingSystem {
+    fn run(&mut self, _scene: &mut Scene, _engine_details: &EngineDetails) {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        self.events.lock().unwrap().push("system");
+    }
+}
+
+#[component]
+pub struct UpdateRecorder {
+    events: Events,
+}
+
+impl ComponentSystem for UpdateRecorder {
+    fn update(&mut self, _params: UpdateParams<'_, '_>) -> ActionQueue {
+        self.events.lock().unwrap().push("update");
+        Vec::new()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn systems_run_once_per_step_before_the_scene_updates() {
+    const FRAMES: usize = 4;
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let events = Events::default();
+    let mut scene = Scene::default();
+    let recorder = UpdateRecorder::builder().events(events.clone()).build();
+    scene.create_entity(None, vec![Box::new(recorder)], Vec::new(), None, true);
+
+    let mut engine = V4::builder()
+        .backends(wgpu::Backends::NOOP)
+        .build_headless()
+        .await;
+    engine.attach_scene(scene);
+    engine.add_system(Box::new(CountingSystem {
+        runs: runs.clone(),
+        events: events.clone(),
+    }));
+
+    let input = MockInput::default();
+    for _ in 0..FRAMES {
+        engine.step_frame(Duration::from_millis(16), &input);
+    }
+
+    assert_eq!(runs.load(Ordering::Relaxed), FRAMES);
+    assert_eq!(*events.lock().unwrap(), ["system", "update"].repeat(FRAMES));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use nalgebra::{Matrix4, Vector3};
+use v4::{
+    EngineDetails,
+    builtin_components::transform_component::TransformComponent,
+    ecs::{
+        component::{ComponentDetails, ComponentId},
+        scene::Scene,
+    },
+};
+
+fn transform(scene: &Scene, id: ComponentId) -> &TransformComponent {
+    scene
+        .get_component(id)
+        .and_then(|component| component.downcast_ref::<TransformComponent>())
+        .expect("The entity has a transform")
+}
+
+fn transform_mut(scene: &mut Scene, id: ComponentId) -> &mut TransformComponent {
+    scene
+        .get_component_mut(id)
+        .and_then(|component| component.downcast_mut::<TransformComponent>())
+        .expect("The entity has a transform")
+}
+
+#[test]
+fn matrices_are_cached_until_the_transform_changes() {
+    let (device, queue) = common::noop_device();
+    let mut scene = Scene::default();
+    let component = TransformComponent::builder()
+        .position(Vector3::new(1.0, 0.0, 0.0))
+        .build();
+    let id = component.id();
+    scene.create_entity(None, vec![Box::new(component)], Vec::new(), None, true);
+    common::initialize(&mut scene, &device, &queue);
+    assert!(!transform(&scene, id).is_matrix_cached());
+
+    let translation = Matrix4::new_translation(&Vector3::new(1.0, 0.0, 0.0));
+    for _ in 0..2 {
+        common::update(&mut scene, &device, &queue, &EngineDetails::default());
+        assert!(transform(&scene, id).is_matrix_cached());
+        assert_eq!(transform(&scene, id).create_matrix(), translation);
+    }
+
+    transform_mut(&mut scene, id).set_scale(Vector3::new(2.0, 2.0, 2.0));
+    let scaled = translation * Matrix4::new_scaling(2.0);
+    assert!(!transform(&scene, id).is_matrix_cached());
+    assert_eq!(transform(&scene, id).create_matrix(), scaled);
+
+    common::update(&mut scene, &device, &queue, &EngineDetails::default());
+    assert!(transform(&scene, id).is_matrix_cached());
+    assert_eq!(transform(&scene, id).create_matrix(), scaled);
+}
+
+#[test]
+fn every_setter_marks_the_matrix_dirty() {
+    let (device, queue) = common::noop_device();
+    let mut scene = Scene::default();
+    let component = TransformComponent::builder().build();
+    let id = component.id();
+    scene.create_entity(None, vec![Box::new(component)], Vec::new(), None, true);
+    common::initialize(&mut scene, &device, &queue);
+
+    let mutations: [fn(&mut TransformComponent); 3] = [
+        |transform| transform.set_position(Vector3::new(0.0, 1.0, 0.0)),
+        |transform| transform.set_scale(Vector3::new(1.0, 3.0, 1.0)),
+        |transform| transform.look_at(Vector3::new(5.0, 0.0, 5.0), Vector3::y()),
+    ];
+    for mutate in mutations {
+        common::update(&mut scene, &device, &queue, &EngineDetails::default());
+        assert!(transform(&scene, id).is_matrix_cached());
+
+        mutate(transform_mut(&mut scene, id));
+        assert!(!transform(&scene, id).is_matrix_cached());
+    }
+}
@@ -0,0 +1,22 @@
+use bytemuck::{Pod, Zeroable};
+use v4::builtin_components::mesh_component::{MeshComponent, VertexData, VertexDescriptor};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct Vertex {
+    pos: [f32; 3],
+}
+
+impl VertexDescriptor for Vertex {
+    const ATTRIBUTES: &[wgpu::VertexAttribute] = &wgpu::vertex_attr_array![0 => Float32x3];
+
+    fn from_data(data: VertexData) -> Self {
+        Vertex { pos: data.pos }
+    }
+}
+
+fn main() {
+    let _mesh = MeshComponent::<Vertex>::builder()
+        .enabled_models(vec![(0, None)])
+        .build();
+}
@@ -0,0 +1,11 @@
+use v4::component;
+
+#[component]
+pub struct Aim {
+    #[required]
+    target: Option<u32>,
+}
+
+fn main() {
+    let _aim = Aim::builder().build();
+}
@@ -0,0 +1,15 @@
+use v4::component;
+
+#[component]
+pub struct Holder<'a, T: Clone>
+where
+    T: Send,
+{
+    data: &'a T,
+}
+
+fn main() {
+    let value = 5;
+    let holder = Holder::builder().data(&value).build();
+    assert_eq!(*holder.data, 5);
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use std::{sync::mpsc, time::Duration};
+
+use common::SceneChannels;
+use v4::{
+    EngineDetails,
+    ecs::{
+        component::ComponentId,
+        scene::{Scene, WorkloadOutput, WorkloadOutputKey, WorkloadOutputPacket, WorkloadPacket},
+    },
+};
+
+const COMPONENT_ID: ComponentId = 7;
+
+/// Receives the workloads the scene launched so far, acting as the engine's executor
+fn launched_workloads(channels: &SceneChannels) -> Vec<WorkloadPacket> {
+    channels.workload_receiver.try_iter().collect()
+}
+
+/// Runs the workload to completion and sends its output back to the scene
+fn complete(channels: &SceneChannels, workload_packet: WorkloadPacket) {
+    let output = pollster::block_on(workload_packet.workload);
+    channels
+        .workload_output_sender
+        .send(WorkloadOutputPacket {
+            component_id: workload_packet.component_id,
+            output_key: workload_packet.output_key,
+            output,
+        })
+        .unwrap();
+}
+
+fn take_sequenced(scene: &mut Scene, sequence: u64) -> Option<&'static str> {
+    scene
+        .take_output_for(COMPONENT_ID, sequence)
+        .map(|output| *output.downcast::<&'static str>().unwrap())
+}
+
+#[test]
+fn sequenced_outputs_arriving_out_of_order_match_their_workloads() {
+    let (device, queue) = common::noop_device();
+    let mut scene = Scene::default();
+    let channels = common::initialize(&mut scene, &device, &queue);
+
+    for (sequence, value) in [(0, "first"), (1, "second")] {
+        pollster::block_on(scene.attach_sequenced_workload(
+            COMPONENT_ID,
+            sequence,
+            Box::pin(async move { Box::new(value) as WorkloadOutput }),
+        ));
+    }
+    let mut workloads = launched_workloads(&channels);
+    assert_eq!(workloads.len(), 2);
+
+    // The second workload finishes first
+    while let Some(workload_packet) = workloads.pop() {
+        complete(&channels, workload_packet);
+    }
+    common::update(&mut scene, &device, &queue, &EngineDetails::default());
+
+    assert_eq!(take_sequenced(&mut scene, 0), Some("first"));
+    assert_eq!(take_sequenced(&mut scene, 1), Some("second"));
+    assert_eq!(take_sequenced(&mut scene, 0), None);
+}
+
+#[test]
+fn progress_is_reported_until_the_output_arrives() {
+    let (device, queue) = common::noop_device();
+    let mut scene = Scene::default();
+    let channels = common::initialize(&mut scene, &device, &queue);
+
+    let progress_sender = scene.workload_progress_sender(COMPONENT_ID);
+    let (reported_sender, reported_receiver) = mpsc::channel();
+    let (finish_sender, finish_receiver) = tokio::sync::oneshot::channel::<()>();
+    let workload_progress_sender = progress_sender.clone();
+    pollster::block_on(scene.attach_workload_with_progress(
+        COMPONENT_ID,
+        Box::pin(async move {
+            workload_progress_sender.send(0.5);
+            reported_sender.send(()).unwrap();
+            finish_receiver.await.unwrap();
+            Box::new("loaded") as WorkloadOutput
+        }),
+        progress_sender,
+    ));
+    assert_eq!(scene.workload_progress(COMPONENT_ID), Some(0.0));
+
+    let mut workloads = launched_workloads(&channels);
+    assert_eq!(workloads.len(), 1);
+    let WorkloadPacket {
+        component_id,
+        output_key,
+        workload,
+        ..
+    } = workloads.pop().unwrap();
+    let executor = std::thread::spawn(move || pollster::block_on(workload));
+
+    reported_receiver.recv().unwrap();
+    common::update(&mut scene, &device, &queue, &EngineDetails::default());
+    assert_eq!(scene.workload_progress(COMPONENT_ID), Some(0.5));
+
+    finish_sender.send(()).unwrap();
+    channels
+        .workload_output_sender
+        .send(WorkloadOutputPacket {
+            component_id,
+            output_key,
+            output: executor.join().unwrap(),
+        })
+        .unwrap();
+    common::update(&mut scene, &device, &queue, &EngineDetails::default());
+    assert_eq!(scene.workload_progress(COMPONENT_ID), None);
+}
+
+mod interval {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(10);
+
+    fn interval_scene() -> (Scene, wgpu::Device, wgpu::Queue, SceneChannels) {
+        let (device, queue) = common::noop_device();
+        let mut scene = Scene::default();
+        let channels = common::initialize(&mut scene, &device, &queue);
+        scene.attach_interval_workload(COMPONENT_ID, Duration::from_millis(50), || {
+            Box::pin(async { Box::new(()) as WorkloadOutput })
+        });
+
+        (scene, device, queue, channels)
+    }
+
+    /// Updates the scene for `frames` frames of `FRAME` each, returning the launched workloads
+    fn run_frames(
+        scene: &mut Scene,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        channels: &SceneChannels,
+        frames: usize,
+    ) -> Vec<WorkloadOutputKey> {
+        let mut engine_details = EngineDetails::default();
+        engine_details.delta_time = FRAME;
+        let mut launched = Vec::new();
+        for _ in 0..frames {
+            common::update(scene, device, queue, &engine_details);
+            launched.extend(
+                launched_workloads(channels)
+                    .into_iter()
+                    .map(|workload_packet| workload_packet.output_key),
+            );
+        }
+        launched
+    }
+
+    #[test]
+    fn launches_once_per_interval_of_simulated_time() {
+        let (mut scene, device, queue, channels) = interval_scene();
+
+        // 230ms of frames cross the 50, 100, 150 and 200ms marks
+        let launched = run_frames(&mut scene, &device, &queue, &channels, 23);
+
+        assert_eq!(
+            launched,
+            (0..4).map(WorkloadOutputKey::Interval).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn cancelling_stops_further_launches() {
+        let (mut scene, device, queue, channels) = interval_scene();
+        assert_eq!(
+            run_frames(&mut scene, &device, &queue, &channels, 10).len(),
+            2
+        );
+
+        scene.cancel_interval_workloads(COMPONENT_ID);
+
+        assert!(run_frames(&mut scene, &device, &queue, &channels, 20).is_empty());
+    }
+}